@@ -520,6 +520,68 @@ export interface NxJsonConfiguration<T = '*' | string[]> {
    */
   cacheDirectory?: string;
 
+  /**
+   * The algorithm used to hash files and tasks.
+   * `xxh3_128` and `blake3` are less likely to collide in large shared caches.
+   */
+  hashAlgorithm?: 'xxh3' | 'xxh3_128' | 'blake3';
+
+  /**
+   * Reads the hashes of unchanged tracked files from the git index instead of hashing them,
+   * which makes hashing the workspace on fresh clones much faster.
+   * Files are hashed as git object ids then.
+   * Files that git converts on checkout, e.g. with LFS, filters or CRLF line endings, are always hashed,
+   * and the index is not used when `core.autocrlf` is `true`.
+   */
+  useGitIndex?: boolean;
+
+  /**
+   * Always rehashes files that were modified after the workspace files were last hashed,
+   * instead of trusting that their mod time, size and inode changed.
+   */
+  paranoidFileHashing?: boolean;
+
+  /**
+   * Files with more bytes than this are hashed by streaming them instead of reading them into memory at once.
+   * Defaults to 8 MiB.
+   */
+  largeFileThreshold?: number;
+
+  /**
+   * Hashes files above the `largeFileThreshold` by their size and mod time instead of their content.
+   * This is much faster for large fixtures, but their hashes differ between machines.
+   */
+  hashLargeFilesByStats?: boolean;
+
+  /**
+   * How workspaces within this workspace, which have their own nx.json, are handled.
+   * `exclude` leaves their files out of this workspace, `mount` also gives each of them its own workspace context.
+   */
+  nestedWorkspaces?: 'include' | 'exclude' | 'mount';
+
+  /**
+   * How symlinks are walked. `content` hashes symlinked files by their content and skips symlinked directories,
+   * `follow` also walks symlinked directories within the workspace, `record` hashes symlinks by the path they link to.
+   */
+  symlinks?: 'content' | 'follow' | 'record';
+
+  /**
+   * Whether the files of git submodules are part of the workspace. Defaults to `true`.
+   */
+  includeSubmodules?: boolean;
+
+  /**
+   * Names of the ignore files that apply to their directory and below. Defaults to `.gitignore` and `.nxignore`.
+   * Later files take precedence over earlier ones, and `.gitignore` files always take the least precedence.
+   */
+  ignoreFiles?: string[];
+
+  /**
+   * Globs of files that are ignored besides `node_modules`, `.git`, `.nx/cache`, `.nx/workspace-data` and `.yarn/cache`,
+   * in the format of .gitignore files.
+   */
+  ignoreGlobs?: string[];
+
   /**
    * Set this to false to disable the daemon.
    */
@@ -186,14 +186,15 @@ export interface TargetDependencyConfig {
 }
 
 export type InputDefinition =
-  | { input: string; projects: string | string[] }
-  | { input: string; dependencies: true }
-  | { input: string }
+  | { input: string; projects: string | string[]; configurations?: string[] }
+  | { input: string; dependencies: true; configurations?: string[] }
+  | { input: string; configurations?: string[] }
   | { fileset: string }
   | { runtime: string }
   | { externalDependencies: string[] }
   | { dependentTasksOutputFiles: string; transitive?: boolean }
-  | { env: string };
+  | { env: string }
+  | { json: string };
 
 /**
  * Target's configuration
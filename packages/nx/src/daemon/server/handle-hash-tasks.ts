@@ -1,8 +1,14 @@
 import { Task, TaskGraph } from '../../config/task-graph';
-import { getCachedSerializedProjectGraphPromise } from './project-graph-incremental-recomputation';
+import {
+  getCachedSerializedProjectGraphPromise,
+  registerWorkspaceContextUpdateListener,
+} from './project-graph-incremental-recomputation';
 import { InProcessTaskHasher } from '../../hasher/task-hasher';
 import { readNxJson } from '../../config/configuration';
 import { DaemonProjectGraphError } from '../../project-graph/error-types';
+import type { HashingService, WorkspaceContext } from '../../native';
+import { getWorkspaceContext } from '../../utils/workspace-context';
+import { workspaceRoot } from '../../utils/workspace-root';
 
 /**
  * We use this not to recreated hasher for every hash operation
@@ -11,6 +17,50 @@ import { DaemonProjectGraphError } from '../../project-graph/error-types';
 let storedProjectGraph: any = null;
 let storedHasher: InProcessTaskHasher | null = null;
 
+/**
+ * The hashing service keeps the hashes of the files which did not change between project graphs.
+ * It is updated with the same file hashes as the workspace context, so the changed files are only hashed once.
+ */
+let storedHashingService: {
+  service: HashingService;
+  context: WorkspaceContext;
+  selectivelyHashTsConfig: boolean;
+} | null = null;
+
+registerWorkspaceContextUpdateListener((updatedFileHashes, deletedFiles) => {
+  if (!storedHashingService) {
+    return;
+  }
+  try {
+    storedHashingService.service.updateFiles(updatedFileHashes, deletedFiles);
+  } catch {
+    // a new service is created from the files of the workspace context
+    storedHashingService = null;
+  }
+});
+
+function getHashingService(runnerOptions: any): HashingService {
+  const context = getWorkspaceContext(workspaceRoot);
+  const selectivelyHashTsConfig =
+    runnerOptions?.selectivelyHashTsConfig ?? false;
+  if (
+    storedHashingService?.context !== context ||
+    storedHashingService.selectivelyHashTsConfig !== selectivelyHashTsConfig
+  ) {
+    const { HashingService } =
+      require('../../native') as typeof import('../../native');
+    const service = new HashingService(context, { selectivelyHashTsConfig });
+    storedHashingService = {
+      service,
+      context,
+      selectivelyHashTsConfig,
+    };
+    // the project graph of the new service has to be set
+    storedProjectGraph = null;
+  }
+  return storedHashingService.service;
+}
+
 export async function handleHashTasks(payload: {
   runnerOptions: any;
   env: any;
@@ -35,6 +85,7 @@ export async function handleHashTasks(payload: {
   }
 
   const nxJson = readNxJson();
+  const hashingService = getHashingService(payload.runnerOptions);
 
   if (projectGraph !== storedProjectGraph) {
     storedProjectGraph = projectGraph;
@@ -42,7 +93,8 @@ export async function handleHashTasks(payload: {
       projectGraph,
       nxJson,
       rustReferences,
-      payload.runnerOptions
+      payload.runnerOptions,
+      hashingService
     );
   }
   const response = JSON.stringify(
@@ -65,6 +65,9 @@ const collectedDeletedFiles = new Set<string>();
 const projectGraphRecomputationListeners = new Set<
   (projectGraph: ProjectGraph) => void
 >();
+const workspaceContextUpdateListeners = new Set<
+  (updatedFileHashes: Record<string, string>, deletedFiles: string[]) => void
+>();
 let storedWorkspaceConfigHash: string | undefined;
 let waitPeriod = 100;
 let scheduledTimeoutId;
@@ -163,6 +166,19 @@ export function registerProjectGraphRecomputationListener(
   projectGraphRecomputationListeners.add(listener);
 }
 
+/**
+ * The listeners are called with the file hashes that the workspace context was updated with,
+ * so that they do not have to hash the changed files again
+ */
+export function registerWorkspaceContextUpdateListener(
+  listener: (
+    updatedFileHashes: Record<string, string>,
+    deletedFiles: string[]
+  ) => void
+) {
+  workspaceContextUpdateListeners.add(listener);
+}
+
 function computeWorkspaceConfigHash(
   projectsConfigurations: Record<string, ProjectConfiguration>
 ) {
@@ -241,6 +257,10 @@ async function processFilesAndCreateAndSerializeProjectGraph(
       updatedFiles,
       deletedFiles
     );
+    notifyWorkspaceContextUpdateListeners(
+      updatedFileHashes ?? {},
+      deletedFiles
+    );
     performance.mark('hash-watched-changes-end');
     performance.measure(
       'hash changed files from watcher',
@@ -446,3 +466,12 @@ function notifyProjectGraphRecomputationListeners(projectGraph: ProjectGraph) {
     listener(projectGraph);
   }
 }
+
+function notifyWorkspaceContextUpdateListeners(
+  updatedFileHashes: Record<string, string>,
+  deletedFiles: string[]
+) {
+  for (const listener of workspaceContextUpdateListeners) {
+    listener(updatedFileHashes, deletedFiles);
+  }
+}
@@ -1,5 +1,5 @@
 import { workspaceRoot } from '../../utils/workspace-root';
-import { dirname, relative } from 'path';
+import { basename, dirname, relative } from 'path';
 import { getFullOsSocketPath } from '../socket-utils';
 import { handleServerProcessTermination } from './shutdown-utils';
 import { Server } from 'net';
@@ -7,8 +7,11 @@ import { normalizePath } from '../../utils/path';
 import {
   getAlwaysIgnore,
   getIgnoredGlobs,
+  getIgnoreFileNames,
   getIgnoreObject,
+  getNativeIgnoreOptions,
 } from '../../utils/ignore';
+import { readNxJson } from '../../config/nx-json';
 import { platform } from 'os';
 import { getDaemonProcessIdSync, serverProcessJsonPath } from '../cache';
 import type { WatchEvent } from '../../native';
@@ -27,14 +30,21 @@ export type FileWatcherCallback = (
 export async function watchWorkspace(server: Server, cb: FileWatcherCallback) {
   const { Watcher } = await import('../../native');
 
-  const watcher = new Watcher(workspaceRoot);
+  const nxJson = readNxJson(workspaceRoot);
+  const ignoreFileNames = getIgnoreFileNames(nxJson);
+  const watcher = new Watcher(
+    workspaceRoot,
+    undefined,
+    undefined,
+    getNativeIgnoreOptions(nxJson)
+  );
   watcher.watch((err, events) => {
     if (err) {
       return cb(err, null);
     }
 
     for (const event of events) {
-      if (event.path.endsWith('.gitignore') || event.path === '.nxignore') {
+      if (ignoreFileNames.includes(basename(event.path))) {
         // If the ignore files themselves have changed we need to dynamically update our cached ignoreGlobs
         handleServerProcessTermination({
           server,
@@ -1,7 +1,12 @@
-export function hashArray(content: string[]): string {
+import type { HashAlgorithm } from '../native';
+
+export function hashArray(
+  content: string[],
+  hashAlgorithm?: HashAlgorithm
+): string {
   // Import as needed. There is also an issue running unit tests in Nx repo if this is a top-level import.
   const { hashArray } = require('../native');
-  return hashArray(content);
+  return hashArray(content, hashAlgorithm);
 }
 
 export function hashObject(obj: object): string {
@@ -0,0 +1,12 @@
+import type { HashAlgorithm } from '../native';
+import { NxJsonConfiguration, readNxJson } from '../config/nx-json';
+
+/**
+ * The hash algorithm from nx.json, which the workspace context, the task hasher
+ * and the cache of a workspace all need to be created with.
+ */
+export function getHashAlgorithm(
+  nxJson: NxJsonConfiguration = readNxJson()
+): HashAlgorithm {
+  return (nxJson.hashAlgorithm ?? 'xxh3') as HashAlgorithm;
+}
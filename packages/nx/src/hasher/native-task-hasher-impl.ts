@@ -5,9 +5,12 @@ import {
   ExternalObject,
   FileData,
   HasherOptions,
+  HashingService,
   HashPlanner,
+  IS_WASM,
   NxWorkspaceFilesExternals,
   ProjectGraph as NativeProjectGraph,
+  ResolvedInputs,
   TaskHasher,
   transferProjectGraph,
 } from '../native';
@@ -15,9 +18,12 @@ import { transformProjectGraphForRust } from '../native/transform-objects';
 import { PartialHash, TaskHasherImpl } from './task-hasher';
 import { readJsonFile } from '../utils/fileutils';
 import { getRootTsConfigPath } from '../plugins/js/utils/typescript';
+import { getDbConnection } from '../utils/db-connection';
+import { workspaceDataDirectory } from '../utils/cache-directory';
+import { getHashAlgorithm } from './hash-algorithm';
 
 export class NativeTaskHasherImpl implements TaskHasherImpl {
-  hasher: TaskHasher;
+  hasher: TaskHasher | HashingService;
   planner: HashPlanner;
   projectGraphRef: ExternalObject<NativeProjectGraph>;
   allWorkspaceFilesRef: ExternalObject<FileData[]>;
@@ -29,7 +35,14 @@ export class NativeTaskHasherImpl implements TaskHasherImpl {
     nxJson: NxJsonConfiguration,
     projectGraph: ProjectGraph,
     externals: NxWorkspaceFilesExternals,
-    options: { selectivelyHashTsConfig: boolean }
+    options: {
+      selectivelyHashTsConfig: boolean;
+      /**
+       * A long-lived hasher which keeps the hashes of files that did not change.
+       * Its project graph is replaced with this one.
+       */
+      hashingService?: HashingService;
+    }
   ) {
     this.projectGraphRef = transferProjectGraph(
       transformProjectGraphForRust(projectGraph)
@@ -50,16 +63,39 @@ export class NativeTaskHasherImpl implements TaskHasherImpl {
       }
     }
 
-    this.planner = new HashPlanner(nxJson, this.projectGraphRef);
-    this.hasher = new TaskHasher(
-      workspaceRoot,
+    this.planner = new HashPlanner(
+      nxJson,
       this.projectGraphRef,
-      this.projectFileMapRef,
-      this.allWorkspaceFilesRef,
-      Buffer.from(JSON.stringify(tsconfig)),
-      paths,
-      options
+      process.env.NX_CACHE_HASH_PLANS !== 'false'
+        ? workspaceDataDirectory
+        : undefined
     );
+    if (options.hashingService) {
+      options.hashingService.updateProjectGraph(
+        this.projectGraphRef,
+        this.projectFileMapRef,
+        Buffer.from(JSON.stringify(tsconfig)),
+        paths
+      );
+      this.hasher = options.hashingService;
+    } else {
+      this.hasher = new TaskHasher(
+        workspaceRoot,
+        this.projectGraphRef,
+        this.projectFileMapRef,
+        this.allWorkspaceFilesRef,
+        Buffer.from(JSON.stringify(tsconfig)),
+        paths,
+        {
+          selectivelyHashTsConfig: options.selectivelyHashTsConfig,
+          hashAlgorithm: getHashAlgorithm(nxJson),
+        }
+      );
+    }
+    // TODO: Remove when wasm supports sqlite
+    if (process.env.NX_DISABLE_DB !== 'true' && !IS_WASM) {
+      this.hasher.useRecordedTaskOutputs(getDbConnection());
+    }
   }
 
   async hashTask(
@@ -85,4 +121,17 @@ export class NativeTaskHasherImpl implements TaskHasherImpl {
     const hashes = this.hasher.hashPlans(plans, env);
     return tasks.map((t) => hashes[t.id]);
   }
+
+  /**
+   * Resolves the files, environment variables and externals that a task is hashed with,
+   * without hashing them
+   */
+  resolveInputs(
+    task: Task,
+    taskGraph: TaskGraph,
+    env: NodeJS.ProcessEnv
+  ): ResolvedInputs {
+    const plans = this.planner.getPlansReference([task.id], taskGraph);
+    return this.hasher.resolveInputs(task.id, plans, env);
+  }
 }
@@ -1,6 +1,19 @@
 // This must come before the Hasher import
 
-import { expandNamedInput, filterUsingGlobPatterns } from './task-hasher';
+import { Task } from '../config/task-graph';
+import {
+  expandNamedInput,
+  filterUsingGlobPatterns,
+  InProcessTaskHasher,
+} from './task-hasher';
+
+jest.mock('./native-task-hasher-impl', () => ({
+  NativeTaskHasherImpl: class {
+    async hashTask() {
+      return { value: '123', details: {} };
+    }
+  },
+}));
 
 describe('TaskHasher', () => {
   describe('expandNamedInput', () => {
@@ -112,4 +125,39 @@ describe('TaskHasher', () => {
       expect(filtered.map((f) => f.file)).toEqual(['a.ts', 'dir/a.ts']);
     });
   });
+
+  describe('InProcessTaskHasher', () => {
+    const task = {
+      id: 'proj:build',
+      target: { project: 'proj', target: 'build' },
+      overrides: {},
+      outputs: [],
+      parallelism: true,
+    } as Task;
+
+    it.each([
+      ['xxh3', '15674877818010787213', 20],
+      ['xxh3_128', '329574770587828792375539469216678315143', 39],
+      [
+        'blake3',
+        '6fed23c4099ca67316807310781741ba3e216d943458d714e630f5f592bfa0fd',
+        64,
+      ],
+    ] as const)(
+      'should create the final task hash with %s',
+      async (hashAlgorithm, value, length) => {
+        const hasher = new InProcessTaskHasher(
+          { nodes: {}, dependencies: {} },
+          { hashAlgorithm },
+          null,
+          {}
+        );
+
+        const hash = await hasher.hashTask(task);
+
+        expect(hash.value).toEqual(value);
+        expect(hash.value).toHaveLength(length);
+      }
+    );
+  });
 });
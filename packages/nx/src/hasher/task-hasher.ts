@@ -11,7 +11,13 @@ import { InputDefinition } from '../config/workspace-json-project-json';
 import { minimatch } from 'minimatch';
 import { NativeTaskHasherImpl } from './native-task-hasher-impl';
 import { workspaceRoot } from '../utils/workspace-root';
-import { NxWorkspaceFilesExternals } from '../native';
+import { getHashAlgorithm } from './hash-algorithm';
+import {
+  HashAlgorithm,
+  HashingService,
+  NxWorkspaceFilesExternals,
+  ResolvedInputs,
+} from '../native';
 
 /**
  * A data structure returned by the default hasher.
@@ -70,6 +76,16 @@ export interface TaskHasher {
     taskGraph: TaskGraph,
     env: NodeJS.ProcessEnv
   ): Promise<Hash[]>;
+
+  /**
+   * Resolves the files, environment variables and externals that a task is hashed with,
+   * without hashing them. Only available when hashing in process.
+   */
+  resolveInputs?(
+    task: Task,
+    taskGraph: TaskGraph,
+    env: NodeJS.ProcessEnv
+  ): ResolvedInputs;
 }
 
 export interface TaskHasherImpl {
@@ -85,6 +101,12 @@ export interface TaskHasherImpl {
     env: NodeJS.ProcessEnv,
     visited?: string[]
   ): Promise<PartialHash>;
+
+  resolveInputs(
+    task: Task,
+    taskGraph: TaskGraph,
+    env: NodeJS.ProcessEnv
+  ): ResolvedInputs;
 }
 
 export type Hasher = TaskHasher;
@@ -126,13 +148,16 @@ export class DaemonBasedTaskHasher implements TaskHasher {
 
 export class InProcessTaskHasher implements TaskHasher {
   private taskHasher: TaskHasherImpl;
+  private hashAlgorithm: HashAlgorithm;
 
   constructor(
     private readonly projectGraph: ProjectGraph,
     private readonly nxJson: NxJsonConfiguration,
     private readonly externalRustReferences: NxWorkspaceFilesExternals | null,
-    private readonly options: any
+    private readonly options: any,
+    private readonly hashingService?: HashingService
   ) {
+    this.hashAlgorithm = getHashAlgorithm(this.nxJson);
     this.taskHasher = new NativeTaskHasherImpl(
       workspaceRoot,
       this.nxJson,
@@ -140,6 +165,7 @@ export class InProcessTaskHasher implements TaskHasher {
       this.externalRustReferences,
       {
         selectivelyHashTsConfig: this.options?.selectivelyHashTsConfig ?? false,
+        hashingService: this.hashingService,
       }
     );
   }
@@ -172,10 +198,18 @@ export class InProcessTaskHasher implements TaskHasher {
     return this.createHashDetails(task, res);
   }
 
+  resolveInputs(
+    task: Task,
+    taskGraph: TaskGraph,
+    env?: NodeJS.ProcessEnv
+  ): ResolvedInputs {
+    return this.taskHasher.resolveInputs(task, taskGraph, env ?? process.env);
+  }
+
   private createHashDetails(task: Task, res: PartialHash) {
     const command = this.hashCommand(task);
     return {
-      value: hashArray([res.value, command]),
+      value: hashArray([res.value, command], this.hashAlgorithm),
       details: {
         command,
         nodes: res.details,
@@ -185,20 +219,18 @@ export class InProcessTaskHasher implements TaskHasher {
     };
   }
 
+  /**
+   * The options and overrides of the task are hashed natively, together with the other inputs
+   */
   private hashCommand(task: Task): string {
-    const overrides = { ...task.overrides };
-    delete overrides['__overrides_unparsed__'];
-    const sortedOverrides = {};
-    for (let k of Object.keys(overrides).sort()) {
-      sortedOverrides[k] = overrides[k];
-    }
-
-    return hashArray([
-      task.target.project ?? '',
-      task.target.target ?? '',
-      task.target.configuration ?? '',
-      JSON.stringify(sortedOverrides),
-    ]);
+    return hashArray(
+      [
+        task.target.project ?? '',
+        task.target.target ?? '',
+        task.target.configuration ?? '',
+      ],
+      this.hashAlgorithm
+    );
   }
 }
 
@@ -206,6 +238,7 @@ export type ExpandedSelfInput =
   | { fileset: string }
   | { runtime: string }
   | { env: string }
+  | { json: string }
   | { externalDependencies: string[] };
 export type ExpandedDepsOutput = {
   dependentTasksOutputFiles: string;
@@ -224,13 +257,38 @@ const DEFAULT_INPUTS: ReadonlyArray<InputDefinition> = [
 
 export function getNamedInputs(
   nxJson: NxJsonConfiguration,
-  project: ProjectGraphProjectNode
+  project: ProjectGraphProjectNode,
+  configuration?: string
 ) {
-  return {
-    default: [{ fileset: '{projectRoot}/**/*' }],
+  const namedInputs = {
     ...nxJson.namedInputs,
     ...project.data.namedInputs,
   };
+  return {
+    default: [{ fileset: '{projectRoot}/**/*' }],
+    ...Object.fromEntries(
+      Object.entries(namedInputs).map(([name, inputs]) => [
+        name,
+        filterConfigurationInputs(inputs, configuration),
+      ])
+    ),
+  };
+}
+
+/**
+ * Drops the inputs that are only used with other configurations than the configuration of the task
+ */
+function filterConfigurationInputs(
+  inputs: ReadonlyArray<InputDefinition | string>,
+  configuration: string | undefined
+) {
+  return inputs.filter(
+    (input) =>
+      typeof input === 'string' ||
+      !('configurations' in input) ||
+      !input.configurations ||
+      input.configurations.includes(configuration)
+  );
 }
 
 export function getTargetInputs(
@@ -271,12 +329,16 @@ export function getInputs(
   nxJson: NxJsonConfiguration
 ) {
   const projectNode = projectGraph.nodes[task.target.project];
-  const namedInputs = getNamedInputs(nxJson, projectNode);
+  const configuration = task.target.configuration;
+  const namedInputs = getNamedInputs(nxJson, projectNode, configuration);
   const targetData = projectNode.data.targets[task.target.target];
   const targetDefaults = (nxJson.targetDefaults || {})[task.target.target];
   const { selfInputs, depsInputs, depsOutputs, projectInputs } =
     splitInputsIntoSelfAndDependencies(
-      targetData.inputs || targetDefaults?.inputs || (DEFAULT_INPUTS as any),
+      filterConfigurationInputs(
+        targetData.inputs || targetDefaults?.inputs || (DEFAULT_INPUTS as any),
+        configuration
+      ),
       namedInputs
     );
   return { selfInputs, depsInputs, depsOutputs, projectInputs };
@@ -352,12 +414,17 @@ export function expandSingleProjectInputs(
   namedInputs: { [inputName: string]: ReadonlyArray<InputDefinition | string> }
 ): ExpandedInput[] {
   const expanded = [];
+  const excluded = new Set<string>();
   for (const d of inputs) {
     if (typeof d === 'string') {
       if (d.startsWith('^'))
         throw new Error(`namedInputs definitions cannot start with ^`);
 
-      if (namedInputs[d]) {
+      if (d.startsWith('!') && namedInputs[d.substring(1)]) {
+        for (const input of expandNamedInput(d.substring(1), namedInputs)) {
+          excluded.add(JSON.stringify(input));
+        }
+      } else if (namedInputs[d]) {
         expanded.push(...expandNamedInput(d, namedInputs));
       } else {
         expanded.push({ fileset: d });
@@ -371,6 +438,7 @@ export function expandSingleProjectInputs(
       if (
         (d as any).fileset ||
         (d as any).env ||
+        (d as any).json ||
         (d as any).runtime ||
         (d as any).externalDependencies ||
         (d as any).dependentTasksOutputFiles
@@ -381,7 +449,10 @@ export function expandSingleProjectInputs(
       }
     }
   }
-  return expanded;
+  // `!namedInput` removes the inputs of a named input, wherever they are in the list
+  return excluded.size > 0
+    ? expanded.filter((input) => !excluded.has(JSON.stringify(input)))
+    : expanded;
 }
 
 export function expandNamedInput(
@@ -10,7 +10,11 @@ use tracing::trace;
 
 use crate::native::cache::expand_outputs::_expand_outputs;
 use crate::native::cache::file_ops::_copy;
+use crate::native::cache::task_output_files::{
+    create_task_output_files_table, get_task_output_files_hash_algorithm, record_task_output_files,
+};
 use crate::native::db::connection::NxDbConnection;
+use crate::native::hasher::HashAlgorithm;
 use crate::native::utils::Normalize;
 
 #[napi(object)]
@@ -28,6 +32,7 @@ pub struct NxCache {
     cache_path: PathBuf,
     db: External<NxDbConnection>,
     link_task_details: bool,
+    hash_algorithm: HashAlgorithm,
 }
 
 #[napi]
@@ -38,6 +43,7 @@ impl NxCache {
         cache_path: String,
         db_connection: External<NxDbConnection>,
         link_task_details: Option<bool>,
+        hash_algorithm: Option<HashAlgorithm>,
     ) -> anyhow::Result<Self> {
         let cache_path = PathBuf::from(&cache_path);
 
@@ -50,6 +56,7 @@ impl NxCache {
             cache_directory: cache_path.to_normalized_string(),
             cache_path,
             link_task_details: link_task_details.unwrap_or(true),
+            hash_algorithm: hash_algorithm.unwrap_or_default(),
         };
 
         r.setup()?;
@@ -78,6 +85,30 @@ impl NxCache {
         };
 
         self.db.execute(query, []).map_err(anyhow::Error::from)?;
+        create_task_output_files_table(&self.db)?;
+        self.record_hash_algorithm()?;
+        Ok(())
+    }
+
+    /// Records the hash algorithm in the metadata.
+    /// File hashes recorded with a different algorithm can not be reused, so they are cleared.
+    fn record_hash_algorithm(&self) -> anyhow::Result<()> {
+        let hash_algorithm = self.hash_algorithm;
+        let recorded_hash_algorithm = get_task_output_files_hash_algorithm(&self.db)?;
+        if recorded_hash_algorithm.as_deref() == Some(hash_algorithm.as_ref()) {
+            return Ok(());
+        }
+
+        trace!(
+            "Hash algorithm changed from {:?} to {}",
+            recorded_hash_algorithm,
+            hash_algorithm.as_ref()
+        );
+        self.db.execute("DELETE FROM task_output_files", [])?;
+        self.db.execute(
+            "INSERT OR REPLACE INTO metadata (key, value) VALUES ('HASH_ALGORITHM', ?1)",
+            [hash_algorithm.as_ref()],
+        )?;
         Ok(())
     }
 
@@ -141,6 +172,9 @@ impl NxCache {
         trace!("Writing terminal outputs to: {:?}", &task_outputs_path);
         write(task_outputs_path, terminal_output)?;
 
+        // Record the hashes of the output files, so dependent tasks can be hashed without reading them again
+        self.record_task_output_files(&outputs);
+
         // Expand the outputs
         let expanded_outputs = _expand_outputs(&self.workspace_root, outputs)?;
 
@@ -198,13 +232,13 @@ impl NxCache {
 
     #[napi]
     pub fn copy_files_from_cache(
-        &self,
+        &mut self,
         cached_result: CachedResult,
         outputs: Vec<String>,
     ) -> anyhow::Result<()> {
         let outputs_path = Path::new(&cached_result.outputs_path);
 
-        let expanded_outputs = _expand_outputs(outputs_path, outputs)?;
+        let expanded_outputs = _expand_outputs(outputs_path, outputs.clone())?;
 
         trace!("Removing expanded outputs: {:?}", &expanded_outputs);
         remove_items(
@@ -222,9 +256,23 @@ impl NxCache {
         );
         _copy(outputs_path, &self.workspace_root)?;
 
+        self.record_task_output_files(&outputs);
+
         Ok(())
     }
 
+    /// Recording the output file hashes is best-effort, a failure should not fail the cache operation
+    fn record_task_output_files(&mut self, outputs: &[String]) {
+        if let Err(e) = record_task_output_files(
+            &mut self.db,
+            &self.workspace_root,
+            outputs,
+            self.hash_algorithm,
+        ) {
+            trace!("Failed to record the task output files: {:?}", e);
+        }
+    }
+
     #[napi]
     pub fn remove_old_cache_records(&self) -> anyhow::Result<()> {
         let outdated_cache = self
@@ -263,7 +311,7 @@ impl NxCache {
             .unwrap_or(false);
 
         if !cache_records_exist {
-            let hash_regex = Regex::new(r"^\d+$").expect("Hash regex is invalid");
+            let hash_regex = Regex::new(r"^(\d+|[0-9a-f]{64})$").expect("Hash regex is invalid");
             let fs_entries = std::fs::read_dir(&self.cache_path).map_err(anyhow::Error::from)?;
 
             for entry in fs_entries {
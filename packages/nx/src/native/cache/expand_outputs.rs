@@ -259,6 +259,52 @@ mod test {
         );
     }
 
+    #[test]
+    fn should_handle_negated_globs() {
+        let temp = setup_fs();
+        let entries = vec!["multi/*".to_string(), "!multi/*.map".to_string()];
+        let mut result = expand_outputs(temp.display().to_string(), entries).unwrap();
+        result.sort();
+        assert_eq!(
+            result,
+            vec!["multi/file.js", "multi/file.txt", "multi/src.ts"]
+        );
+    }
+
+    #[test]
+    fn should_handle_nested_negated_globs() {
+        let temp = setup_fs();
+        let entries = vec!["apps/web/.next/**".to_string(), "!**/cache".to_string()];
+        let mut result = expand_outputs(temp.display().to_string(), entries).unwrap();
+        result.sort();
+        assert_eq!(
+            result,
+            vec![
+                "apps/web/.next/content-file",
+                "apps/web/.next/static",
+                "apps/web/.next/static/contents"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_match_negated_globs_in_any_directory() {
+        let temp = setup_fs();
+        let entries = vec!["apps/**".to_string(), "!web/.next/cache".to_string()];
+        let mut result = expand_outputs(temp.display().to_string(), entries).unwrap();
+        result.sort();
+        assert_eq!(
+            result,
+            vec![
+                "apps/web",
+                "apps/web/.next",
+                "apps/web/.next/content-file",
+                "apps/web/.next/static",
+                "apps/web/.next/static/contents"
+            ]
+        );
+    }
+
     #[test]
     fn should_get_files_for_outputs_with_glob() {
         let temp = setup_fs();
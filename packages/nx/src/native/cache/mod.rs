@@ -3,4 +3,6 @@ pub mod file_ops;
 pub mod validate_outputs;
 
 #[cfg(not(target_arch = "wasm32"))]
-pub mod cache;
\ No newline at end of file
+pub mod cache;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod task_output_files;
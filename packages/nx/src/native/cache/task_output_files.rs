@@ -0,0 +1,267 @@
+use std::path::Path;
+
+use rayon::prelude::*;
+use rusqlite::params;
+use tracing::trace;
+
+use crate::native::cache::expand_outputs::get_files_for_outputs;
+use crate::native::db::connection::NxDbConnection;
+use crate::native::hasher::{hash_file_path_with_algorithm, HashAlgorithm};
+use crate::native::types::FileData;
+use crate::native::utils::{get_mod_time, Normalize};
+
+struct RecordedFile {
+    file: String,
+    hash: String,
+    mod_time: i64,
+    size: i64,
+}
+
+pub(crate) fn create_task_output_files_table(db: &NxDbConnection) -> anyhow::Result<()> {
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS task_output_files (
+            output TEXT NOT NULL,
+            file TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            mod_time INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            PRIMARY KEY (output, file)
+        );",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Hashes the files that currently exist in the workspace for the given outputs and records them.
+/// This is called when outputs are written to or restored from the cache,
+/// so that tasks depending on these outputs do not need to walk and read them again.
+pub(crate) fn record_task_output_files(
+    db: &mut NxDbConnection,
+    workspace_root: &Path,
+    outputs: &[String],
+    hash_algorithm: HashAlgorithm,
+) -> anyhow::Result<()> {
+    let now = std::time::Instant::now();
+    let recorded_outputs = outputs
+        .par_iter()
+        .map(|output| {
+            let files =
+                get_files_for_outputs(workspace_root.to_normalized_string(), vec![output.clone()])?;
+            let recorded_files = files
+                .into_par_iter()
+                .filter_map(|file| {
+                    let full_path = workspace_root.join(&file);
+                    let metadata = full_path.metadata().ok()?;
+                    let hash = hash_file_path_with_algorithm(hash_algorithm, &full_path)?;
+                    Some(RecordedFile {
+                        file,
+                        hash,
+                        mod_time: get_mod_time(&metadata),
+                        size: metadata.len() as i64,
+                    })
+                })
+                .collect::<Vec<_>>();
+            Ok((output, recorded_files))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    trace!("hashed task output files in {:?}", now.elapsed());
+
+    db.transaction(|conn| {
+        let mut delete_stmt = conn.prepare("DELETE FROM task_output_files WHERE output = ?1")?;
+        let mut insert_stmt = conn.prepare(
+            "INSERT OR REPLACE INTO task_output_files (output, file, hash, mod_time, size)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for (output, recorded_files) in recorded_outputs.iter() {
+            delete_stmt.execute(params![output])?;
+            for recorded_file in recorded_files {
+                insert_stmt.execute(params![
+                    output,
+                    recorded_file.file,
+                    recorded_file.hash,
+                    recorded_file.mod_time,
+                    recorded_file.size
+                ])?;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// The hash algorithm that the recorded files were hashed with
+pub(crate) fn get_task_output_files_hash_algorithm(
+    db: &NxDbConnection,
+) -> anyhow::Result<Option<String>> {
+    db.query_row(
+        "SELECT value FROM metadata WHERE key='HASH_ALGORITHM'",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Gets the recorded files of an output, if they are still the same files that are on disk.
+/// Returns `None` when the output was never recorded, when any of its files were changed or removed since,
+/// or when files were added to it since, e.g. by a task that was not cached.
+pub(crate) fn get_recorded_task_output_files(
+    db: &NxDbConnection,
+    workspace_root: &Path,
+    output: &str,
+) -> anyhow::Result<Option<Vec<FileData>>> {
+    let mut stmt = db.prepare(
+        "SELECT file, hash, mod_time, size FROM task_output_files WHERE output = ?1 ORDER BY file",
+    )?;
+    let recorded_files = stmt
+        .query_map(params![output], |row| {
+            Ok(RecordedFile {
+                file: row.get(0)?,
+                hash: row.get(1)?,
+                mod_time: row.get(2)?,
+                size: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    if recorded_files.is_empty() {
+        return Ok(None);
+    }
+
+    // listing the files is much cheaper than hashing them, and catches files added since
+    let mut current_files =
+        get_files_for_outputs(workspace_root.to_normalized_string(), vec![output.into()])?;
+    current_files.sort();
+    if !current_files.iter().eq(recorded_files
+        .iter()
+        .map(|recorded_file| &recorded_file.file))
+    {
+        trace!("files were added to or removed from {} on disk", output);
+        return Ok(None);
+    }
+
+    let unchanged = recorded_files.par_iter().all(|recorded_file| {
+        workspace_root
+            .join(&recorded_file.file)
+            .metadata()
+            .is_ok_and(|metadata| {
+                get_mod_time(&metadata) == recorded_file.mod_time
+                    && metadata.len() as i64 == recorded_file.size
+            })
+    });
+
+    if !unchanged {
+        trace!("recorded files for {} have changed on disk", output);
+        return Ok(None);
+    }
+
+    Ok(Some(
+        recorded_files
+            .into_iter()
+            .map(|recorded_file| FileData {
+                file: recorded_file.file,
+                hash: recorded_file.hash,
+            })
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+    use rusqlite::Connection;
+
+    fn setup_db() -> NxDbConnection {
+        let db = NxDbConnection::new(Connection::open_in_memory().unwrap());
+        create_task_output_files_table(&db).unwrap();
+        db
+    }
+
+    #[test]
+    fn should_record_and_get_task_output_files() {
+        let temp = TempDir::new().unwrap();
+        temp.child("dist/libs/lib1/index.js")
+            .write_str("content")
+            .unwrap();
+        temp.child("dist/libs/lib1/index.d.ts")
+            .write_str("content2")
+            .unwrap();
+        let mut db = setup_db();
+
+        let outputs = vec!["dist/libs/lib1".to_string()];
+        record_task_output_files(&mut db, temp.path(), &outputs, HashAlgorithm::default()).unwrap();
+
+        let recorded = get_recorded_task_output_files(&db, temp.path(), "dist/libs/lib1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            recorded
+                .iter()
+                .map(|f| (f.file.as_str(), f.hash.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("dist/libs/lib1/index.d.ts", "1707056588989152788"),
+                ("dist/libs/lib1/index.js", "6193209363630369380"),
+            ]
+        );
+
+        assert!(
+            get_recorded_task_output_files(&db, temp.path(), "dist/libs/lib2")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn should_not_get_task_output_files_when_files_were_added() {
+        let temp = TempDir::new().unwrap();
+        temp.child("dist/libs/lib1/index.js")
+            .write_str("content")
+            .unwrap();
+        let mut db = setup_db();
+
+        record_task_output_files(
+            &mut db,
+            temp.path(),
+            &["dist/libs/lib1".to_string()],
+            HashAlgorithm::default(),
+        )
+        .unwrap();
+
+        temp.child("dist/libs/lib1/main.js")
+            .write_str("written by another build")
+            .unwrap();
+
+        assert!(
+            get_recorded_task_output_files(&db, temp.path(), "dist/libs/lib1")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn should_not_get_task_output_files_that_changed() {
+        let temp = TempDir::new().unwrap();
+        temp.child("dist/libs/lib1/index.js")
+            .write_str("content")
+            .unwrap();
+        let mut db = setup_db();
+
+        record_task_output_files(
+            &mut db,
+            temp.path(),
+            &["dist/libs/lib1".to_string()],
+            HashAlgorithm::default(),
+        )
+        .unwrap();
+
+        temp.child("dist/libs/lib1/index.js")
+            .write_str("changed content")
+            .unwrap();
+
+        assert!(
+            get_recorded_task_output_files(&db, temp.path(), "dist/libs/lib1")
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -1,14 +1,96 @@
+use std::io::Read;
 use std::path::Path;
 
 use tracing::trace;
 use xxhash_rust::xxh3;
 
+/// The algorithm used for the hashes of workspace files, tasks and cached outputs.
+/// `xxh3` is the fastest, `xxh3_128` and `blake3` are less likely to collide in large shared caches.
+/// Each workspace context, task hasher and cache is configured with its own algorithm.
+#[napi(string_enum)]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    #[allow(non_camel_case_types)]
+    #[default]
+    xxh3,
+    #[allow(non_camel_case_types)]
+    xxh3_128,
+    #[allow(non_camel_case_types)]
+    blake3,
+}
+
+impl AsRef<str> for HashAlgorithm {
+    fn as_ref(&self) -> &str {
+        match self {
+            HashAlgorithm::xxh3 => "xxh3",
+            HashAlgorithm::xxh3_128 => "xxh3_128",
+            HashAlgorithm::blake3 => "blake3",
+        }
+    }
+}
+
+/// Incremental hasher for each of the hash algorithms
+pub enum Hasher {
+    Xxh3(Box<xxh3::Xxh3>),
+    Xxh3_128(Box<xxh3::Xxh3>),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    pub fn new() -> Self {
+        Self::with_algorithm(HashAlgorithm::default())
+    }
+
+    pub fn with_algorithm(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::xxh3 => Hasher::Xxh3(Box::new(xxh3::Xxh3::new())),
+            HashAlgorithm::xxh3_128 => Hasher::Xxh3_128(Box::new(xxh3::Xxh3::new())),
+            HashAlgorithm::blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    pub fn update(&mut self, content: &[u8]) {
+        match self {
+            Hasher::Xxh3(hasher) | Hasher::Xxh3_128(hasher) => hasher.update(content),
+            Hasher::Blake3(hasher) => {
+                hasher.update(content);
+            }
+        }
+    }
+
+    pub fn digest(&self) -> String {
+        match self {
+            Hasher::Xxh3(hasher) => hasher.digest().to_string(),
+            Hasher::Xxh3_128(hasher) => hasher.digest128().to_string(),
+            Hasher::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+impl Default for Hasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub fn hash(content: &[u8]) -> String {
-    xxh3::xxh3_64(content).to_string()
+    hash_with_algorithm(HashAlgorithm::default(), content)
+}
+
+pub fn hash_with_algorithm(algorithm: HashAlgorithm, content: &[u8]) -> String {
+    match algorithm {
+        HashAlgorithm::xxh3 => xxh3::xxh3_64(content).to_string(),
+        HashAlgorithm::xxh3_128 => xxh3::xxh3_128(content).to_string(),
+        HashAlgorithm::blake3 => blake3::hash(content).to_hex().to_string(),
+    }
 }
 
 #[napi]
-pub fn hash_array(input: Vec<Option<String>>) -> String {
+pub fn hash_array(input: Vec<Option<String>>, hash_algorithm: Option<HashAlgorithm>) -> String {
+    hash_array_with_algorithm(hash_algorithm.unwrap_or_default(), input)
+}
+
+pub fn hash_array_with_algorithm(algorithm: HashAlgorithm, input: Vec<Option<String>>) -> String {
     let joined = input.iter().filter_map(|s| {
         if s.is_none() {
             trace!("Encountered None value in hash_array input: {:?}", input);
@@ -16,7 +98,7 @@ pub fn hash_array(input: Vec<Option<String>>) -> String {
         s.as_deref()
     }).collect::<Vec<_>>().join(",");
     let content = joined.as_bytes();
-    hash(content)
+    hash_with_algorithm(algorithm, content)
 }
 
 #[napi]
@@ -26,14 +108,67 @@ pub fn hash_file(file: String) -> Option<String> {
 
 #[inline]
 pub fn hash_file_path<P: AsRef<Path>>(path: P) -> Option<String> {
+    hash_file_path_with_algorithm(HashAlgorithm::default(), path)
+}
+
+/// Hashes the content of the file, streaming files above `STREAMED_FILE_SIZE`
+pub fn hash_file_path_with_algorithm<P: AsRef<Path>>(
+    algorithm: HashAlgorithm,
+    path: P,
+) -> Option<String> {
     let path = path.as_ref();
+    if std::fs::metadata(path).is_ok_and(|metadata| metadata.len() > STREAMED_FILE_SIZE) {
+        return hash_file_path_streaming(algorithm, path);
+    }
     trace!("Reading {:?} to hash", path);
     let Ok(content) = std::fs::read(path) else {
         trace!("Failed to read file: {:?}", path);
         return None;
     };
     trace!("Hashing {:?}", path);
-    let hash = hash(&content);
+    let hash = hash_with_algorithm(algorithm, &content);
+    trace!("Hashed file {:?} - {:?}", path, hash);
+
+    Some(hash)
+}
+
+/// Files above 8 MiB are streamed instead of read into memory at once
+pub const STREAMED_FILE_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Size of the buffer that streamed files are read through
+const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Reads the file through a bounded buffer, so that large files are never held in memory at once
+pub fn read_file_in_chunks<P: AsRef<Path>>(
+    path: P,
+    mut read_chunk: impl FnMut(&[u8]),
+) -> std::io::Result<()> {
+    let mut file = std::fs::File::open(path)?;
+    let mut buffer = vec![0; STREAM_BUFFER_SIZE];
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(length) => read_chunk(&buffer[..length]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Hashes the file by streaming it, which gives the same hash as `hash_file_path_with_algorithm`
+/// without reading the whole file
+pub fn hash_file_path_streaming<P: AsRef<Path>>(
+    algorithm: HashAlgorithm,
+    path: P,
+) -> Option<String> {
+    let path = path.as_ref();
+    trace!("Streaming {:?} to hash", path);
+    let mut hasher = Hasher::with_algorithm(algorithm);
+    if let Err(e) = read_file_in_chunks(path, |chunk| hasher.update(chunk)) {
+        trace!("Failed to read file: {:?} {:?}", path, e);
+        return None;
+    }
+    let hash = hasher.digest();
     trace!("Hashed file {:?} - {:?}", path, hash);
 
     Some(hash)
@@ -41,7 +176,10 @@ pub fn hash_file_path<P: AsRef<Path>>(path: P) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use crate::native::hasher::{hash_file, hash_array};
+    use crate::native::hasher::{
+        hash_array, hash_file, hash_file_path_streaming, hash_file_path_with_algorithm,
+        hash_with_algorithm, HashAlgorithm, Hasher, STREAMED_FILE_SIZE,
+    };
     use assert_fs::prelude::*;
     use assert_fs::TempDir;
 
@@ -81,7 +219,87 @@ mod tests {
     #[test]
     fn it_hashes_an_array() {
         // Resilient to None values (e.g. null values passed from the JS side)
-        let content = hash_array(vec![Some("foo".to_string()), None, Some("bar".to_string())]);
+        let content = hash_array(
+            vec![Some("foo".to_string()), None, Some("bar".to_string())],
+            None,
+        );
         assert_eq!(content, "10292076446133652019");
     }
+
+    #[test]
+    fn it_hashes_an_array_with_the_given_algorithm() {
+        let input = vec![Some("foo".to_string()), Some("bar".to_string())];
+        for algorithm in [
+            HashAlgorithm::xxh3,
+            HashAlgorithm::xxh3_128,
+            HashAlgorithm::blake3,
+        ] {
+            assert_eq!(
+                hash_array(input.clone(), Some(algorithm)),
+                hash_with_algorithm(algorithm, b"foo,bar")
+            );
+        }
+    }
+
+    #[test]
+    fn it_hashes_with_each_algorithm() {
+        assert_eq!(
+            hash_with_algorithm(HashAlgorithm::xxh3, b"content"),
+            "6193209363630369380"
+        );
+        assert_eq!(
+            hash_with_algorithm(HashAlgorithm::xxh3_128, b"content"),
+            "193392663078994241937916373705623688844"
+        );
+        assert_eq!(
+            hash_with_algorithm(HashAlgorithm::blake3, b"content"),
+            "3fba5250be9ac259c56e7250c526bc83bacb4be825f2799d3d59e5b4878dd74e"
+        );
+    }
+
+    #[test]
+    fn it_hashes_incrementally_the_same_as_all_at_once() {
+        for algorithm in [
+            HashAlgorithm::xxh3,
+            HashAlgorithm::xxh3_128,
+            HashAlgorithm::blake3,
+        ] {
+            let mut hasher = Hasher::with_algorithm(algorithm);
+            hasher.update(b"con");
+            hasher.update(b"tent");
+            assert_eq!(hasher.digest(), hash_with_algorithm(algorithm, b"content"));
+        }
+    }
+
+    #[test]
+    fn it_streams_large_files() {
+        let temp_dir = setup_fs();
+        let content = vec![b'a'; STREAMED_FILE_SIZE as usize + 1];
+        temp_dir.child("large.bin").write_binary(&content).unwrap();
+
+        assert_eq!(
+            hash_file_path_with_algorithm(HashAlgorithm::xxh3, temp_dir.join("large.bin")),
+            Some(hash_with_algorithm(HashAlgorithm::xxh3, &content))
+        );
+    }
+
+    #[test]
+    fn it_hashes_a_streamed_file_the_same_as_a_read_file() {
+        let temp_dir = setup_fs();
+        // larger than the stream buffer, so that it is read in multiple chunks
+        let content = "content".repeat(20_000);
+        temp_dir.child("large.txt").write_str(&content).unwrap();
+
+        assert_eq!(
+            hash_file_path_streaming(HashAlgorithm::xxh3, temp_dir.join("large.txt")),
+            hash_file(temp_dir.display().to_string() + "/large.txt")
+        );
+        assert_eq!(
+            hash_file_path_streaming(HashAlgorithm::blake3, temp_dir.join("large.txt")),
+            hash_file_path_with_algorithm(HashAlgorithm::blake3, temp_dir.join("large.txt"))
+        );
+        assert!(
+            hash_file_path_streaming(HashAlgorithm::xxh3, temp_dir.join("missing.txt")).is_none()
+        );
+    }
 }
@@ -22,8 +22,37 @@ export declare class FileLock {
   lock(): void
 }
 
+/**
+ * A long-lived task hasher which keeps its files up to date as they change.
+ * Only the hashes of the inputs that depend on the changed files are recomputed,
+ * so that tasks can be rehashed without rehashing the whole workspace.
+ */
+export declare class HashingService {
+  /**
+   * The service starts with the files of the workspace context, and is kept up to date with `updateFiles`.
+   * Tasks are hashed with the hash algorithm of the workspace context,
+   * once the project graph is set with `updateProjectGraph`.
+   */
+  constructor(context: WorkspaceContext, options?: HasherOptions | undefined | null)
+  /**
+   * Replaces the project graph, the files of the projects and the root tsconfig after the project graph
+   * was recomputed. Only the hashes that depend on what changed are recomputed.
+   */
+  updateProjectGraph(projectGraph: ExternalObject<ProjectGraph>, projectFiles: ExternalObject<ProjectFiles>, tsConfig: Buffer, tsConfigPaths: Record<string, Array<string>>): void
+  /**
+   * Updates the files with the file hashes that the workspace context was updated with,
+   * so that the changed files are not hashed again
+   */
+  updateFiles(updatedFiles: Record<string, string>, deletedFiles: Array<string>): void
+  /** Replaces the root tsconfig after it changed */
+  updateRootTsConfig(tsConfig: Buffer, tsConfigPaths: Record<string, Array<string>>): void
+  hashPlans(hashPlans: ExternalObject<Record<string, Array<HashInstruction>>>, jsEnv: Record<string, string>): NapiDashMap
+  resolveInputs(taskId: string, hashPlans: ExternalObject<Record<string, Array<HashInstruction>>>, jsEnv: Record<string, string>): ResolvedInputs
+  useRecordedTaskOutputs(db: ExternalObject<NxDbConnection>): void
+}
+
 export declare class HashPlanner {
-  constructor(nxJson: NxJson, projectGraph: ExternalObject<ProjectGraph>)
+  constructor(nxJson: NxJson, projectGraph: ExternalObject<ProjectGraph>, cacheDir?: string | undefined | null)
   getPlans(taskIds: Array<string>, taskGraph: TaskGraph): Record<string, string[]>
   getPlansReference(taskIds: Array<string>, taskGraph: TaskGraph): JsExternal
 }
@@ -37,7 +66,7 @@ export declare class ImportResult {
 
 export declare class NxCache {
   cacheDirectory: string
-  constructor(workspaceRoot: string, cachePath: string, dbConnection: ExternalObject<NxDbConnection>, linkTaskDetails?: boolean | undefined | null)
+  constructor(workspaceRoot: string, cachePath: string, dbConnection: ExternalObject<NxDbConnection>, linkTaskDetails?: boolean | undefined | null, hashAlgorithm?: HashAlgorithm | undefined | null)
   get(hash: string): CachedResult | null
   put(hash: string, terminalOutput: string, outputs: Array<string>, code: number): void
   applyRemoteCacheResults(hash: string, result: CachedResult): void
@@ -72,6 +101,35 @@ export declare class TaskDetails {
 export declare class TaskHasher {
   constructor(workspaceRoot: string, projectGraph: ExternalObject<ProjectGraph>, projectFileMap: ExternalObject<ProjectFiles>, allWorkspaceFiles: ExternalObject<Array<FileData>>, tsConfig: Buffer, tsConfigPaths: Record<string, Array<string>>, options?: HasherOptions | undefined | null)
   hashPlans(hashPlans: ExternalObject<Record<string, Array<HashInstruction>>>, jsEnv: Record<string, string>): NapiDashMap
+  /**
+   * Expands the hash plan of a task into the files, environment variables and externals it depends on,
+   * without hashing them
+   */
+  resolveInputs(taskId: string, hashPlans: ExternalObject<Record<string, Array<HashInstruction>>>, jsEnv: Record<string, string>): ResolvedInputs
+  /**
+   * Hash dependent task outputs with the file hashes that the cache recorded in the db,
+   * instead of walking and reading the outputs from disk
+   */
+  useRecordedTaskOutputs(db: ExternalObject<NxDbConnection>): void
+}
+
+/**
+ * Schedules the tasks of a task graph, starting the tasks on the longest paths through the graph first,
+ * so that long chains of tasks do not start late.
+ * Durations are the estimated task timings of the task history, by task id.
+ */
+export declare class TaskScheduler {
+  constructor(taskGraph: TaskGraph, projectGraph: ExternalObject<ProjectGraph>, estimatedTimings: Record<string, number>, parallel: number)
+  /**
+   * Gets the tasks that can start now, and marks them as running.
+   * Returns nothing when the running tasks need to finish first.
+   */
+  nextTasks(): Array<string>
+  completeTasks(taskIds: Array<string>): void
+  /** Whether every task has been scheduled and completed */
+  isComplete(): boolean
+  /** The estimated time to run every task of the task graph, in the unit of the estimated timings */
+  getPredictedWallTime(): number
 }
 
 export declare class Watcher {
@@ -81,17 +139,25 @@ export declare class Watcher {
    * Will always ignore the following directories:
    * * .git/
    * * node_modules/
-   * * .nx/
+   * * .nx/cache/ and .nx/workspace-data/
+   *   The rest of .nx/, such as .nx/installation, is watched, because it is part of the workspace context too.
+   * * .yarn/cache/
+   *
+   * The ignore options should be the same as for the workspace context, so that both agree on which files exist.
    */
-  constructor(origin: string, additionalGlobs?: Array<string> | undefined | null, useIgnore?: boolean | undefined | null)
+  constructor(origin: string, additionalGlobs?: Array<string> | undefined | null, useIgnore?: boolean | undefined | null, ignores?: IgnoreOptions | undefined | null)
   watch(callback: (err: string | null, events: WatchEvent[]) => void): void
   stop(): Promise<void>
 }
 
 export declare class WorkspaceContext {
   workspaceRoot: string
-  constructor(workspaceRoot: string, cacheDir: string)
-  getWorkspaceFiles(projectRootMap: Record<string, string>): NxWorkspaceFiles
+  constructor(workspaceRoot: string, cacheDir: string, options?: WorkspaceContextOptions | undefined | null)
+  /** The roots of the nested workspaces that are mounted as their own contexts, relative to this workspace */
+  getNestedWorkspaces(): Array<string>
+  /** The context of the mounted nested workspace at the root, which is relative to this workspace */
+  getNestedContext(root: string): WorkspaceContext | null
+  getWorkspaceFiles(projectRootMap: Record<string, string>, options?: WorkspaceFilesOptions | undefined | null): NxWorkspaceFiles
   glob(globs: Array<string>, exclude?: Array<string> | undefined | null): Array<string>
   /**
    * Performs multiple glob pattern matches against workspace files in parallel
@@ -102,12 +168,35 @@ export declare class WorkspaceContext {
   multiGlob(globs: Array<string>, exclude?: Array<string> | undefined | null): Array<Array<string>>
   hashFilesMatchingGlobs(globGroups: Array<Array<string>>): Array<string>
   hashFilesMatchingGlob(globs: Array<string>, exclude?: Array<string> | undefined | null): string
+  /**
+   * Updates the files that changed, and returns the hashes of the updated files of this workspace.
+   * Files of nested workspaces are not part of this workspace, they update the mounted nested contexts instead.
+   */
   incrementalUpdate(updatedFiles: Array<string>, deletedFiles: Array<string>): Record<string, string>
-  updateProjectFiles(projectRootMappings: ProjectRootMappings, projectFiles: ExternalObject<ProjectFiles>, globalFiles: ExternalObject<Array<FileData>>, updatedFiles: Record<string, string>, deletedFiles: Array<string>): UpdatedWorkspaceFiles
+  updateProjectFiles(projectRootMappings: ProjectRootMappings, projectFiles: ExternalObject<ProjectFiles>, globalFiles: ExternalObject<Array<FileData>>, updatedFiles: Record<string, string>, deletedFiles: Array<string>, options?: WorkspaceFilesOptions | undefined | null): UpdatedWorkspaceFiles
   allFileData(): Array<FileData>
+  /**
+   * Searches the content of the workspace files that match the globs, or of every file when there are no globs
+   * @returns The matches of the regex pattern in each line, sorted by file and line
+   */
+  search(pattern: string, globs: Array<string>, options?: SearchOptions | undefined | null): Array<SearchMatch>
+  /** Queries the workspace files with their size and mod time, one page at a time */
+  queryFiles(query: WorkspaceFilesQuery, projectRootMap?: ProjectRootMappings | undefined | null): WorkspaceFilesPage
+  /** The largest files of the workspace, which are good candidates for the .nxignore when they slow down hashing */
+  getLargestFiles(count: number): Array<WorkspaceFileSize>
   getFilesInDirectory(directory: string): Array<string>
 }
 
+export interface AffectedProjectsOptions {
+  /** The named inputs of nx.json, which are used to find the projects that depend on files outside of projects */
+  namedInputs?: Record<string, Array<JsInputs>>
+  /**
+   * The projects that are affected when a lock file changes.
+   * Either `all` (the default), `auto` to leave it to the changed external nodes, or project patterns.
+   */
+  projectsAffectedByDependencyUpdates?: string | Array<string>
+}
+
 export interface CachedResult {
   code: number
   terminalOutput: string
@@ -120,11 +209,22 @@ export declare export function connectToNxDb(cacheDir: string, nxVersion: string
 
 export declare export function copy(src: string, dest: string): void
 
+/**
+ * Creates the tasks for the targets of the projects, and the tasks that they depend on through `dependsOn`.
+ * `target_dependencies` are the `dependsOn` of the target defaults, for targets that do not define their own.
+ */
+export declare export function createTaskGraph(projectGraph: ExternalObject<ProjectGraph>, targetDependencies: Record<string, Array<string | TargetDependencyConfig>>, projectNames: Array<string>, targets: Array<string>, options?: TaskGraphOptions | undefined | null): TaskGraph
+
 export interface DepsOutputsInput {
   dependentTasksOutputFiles: string
   transitive?: boolean
 }
 
+export interface DuplicateProjectRoot {
+  root: string
+  projects: Array<string>
+}
+
 export interface EnvironmentInput {
   env: string
 }
@@ -157,12 +257,27 @@ export interface FileMap {
   nonProjectFiles: Array<FileData>
 }
 
+export interface FileOwnershipConflict {
+  file: string
+  /** The project with the nearest root, which the file belongs to */
+  project: string
+  /** The other projects whose roots contain the file, from the nearest root to the farthest */
+  otherProjects: Array<string>
+}
+
 export interface FileSetInput {
   fileset: string
 }
 
 export declare export function findImports(projectFileMap: Record<string, Array<string>>): Array<ImportResult>
 
+/**
+ * Gets the projects that are affected by the changed files, sorted by name.
+ * These are the projects that own a changed file, the projects whose inputs include a changed global file,
+ * every project when nx.json or a lock file changed, and the projects that depend on them.
+ */
+export declare export function getAffectedProjects(changedFiles: Array<string>, projectGraph: ExternalObject<ProjectGraph>, options?: AffectedProjectsOptions | undefined | null): Array<string>
+
 export declare export function getBinaryTarget(): string
 
 /**
@@ -173,7 +288,18 @@ export declare export function getFilesForOutputs(directory: string, entries: Ar
 
 export declare export function getTransformableOutputs(outputs: Array<string>): Array<string>
 
-export declare export function hashArray(input: Array<string | undefined | null>): string
+/**
+ * The algorithm used for the hashes of workspace files, tasks and cached outputs.
+ * `xxh3` is the fastest, `xxh3_128` and `blake3` are less likely to collide in large shared caches.
+ * Each workspace context, task hasher and cache is configured with its own algorithm.
+ */
+export declare const enum HashAlgorithm {
+  xxh3 = 'xxh3',
+  xxh3_128 = 'xxh3_128',
+  blake3 = 'blake3'
+}
+
+export declare export function hashArray(input: Array<string | undefined | null>, hashAlgorithm?: HashAlgorithm | undefined | null): string
 
 export interface HashDetails {
   value: string
@@ -189,18 +315,59 @@ export interface HashedTask {
 
 export interface HasherOptions {
   selectivelyHashTsConfig: boolean
+  /** The algorithm of the workspace file hashes, which task hashes are also computed with */
+  hashAlgorithm?: HashAlgorithm
 }
 
 export declare export function hashFile(file: string): string | null
 
+/** Which files are ignored. The workspace context and the watcher take the same options, so that they agree on which files exist. */
+export interface IgnoreOptions {
+  /**
+   * Names of the ignore files that apply to their directory and below, `.gitignore` and `.nxignore` by default.
+   * Later files take precedence over earlier ones, and `.gitignore` files always take the least precedence.
+   */
+  ignoreFiles?: Array<string>
+  /**
+   * Globs that are ignored besides `node_modules`, `.git`, `.nx/cache`, `.nx/workspace-data` and `.yarn/cache`,
+   * in the format of .gitignore files
+   */
+  ignoreGlobs?: Array<string>
+}
+
 export interface InputsInput {
   input: string
   dependencies?: boolean
   projects?: string | Array<string>
+  /** When set, the input is only used by tasks that run with one of these configurations */
+  configurations?: Array<string>
 }
 
 export const IS_WASM: boolean
 
+export interface JsonInput {
+  /**
+   * A json file and a JSON pointer to the value in it that is hashed,
+   * e.g. `{workspaceRoot}/package.json#/dependencies/react`
+   */
+  json: string
+}
+
+export interface MissingProjectRoot {
+  project: string
+  root: string
+}
+
+/** How workspaces within the workspace, which have their own nx.json, are handled */
+export declare const enum NestedWorkspaces {
+  /** Their files are files of this workspace */
+  include = 'include',
+  /** Their files are left out of this workspace */
+  exclude = 'exclude',
+  /** Their files are left out of this workspace, and each of them gets its own context */
+  mount = 'mount'
+}
+
 /** Stripped version of the NxJson interface for use in rust */
 export interface NxJson {
   namedInputs?: Record<string, Array<JsInputs>>
@@ -210,6 +377,7 @@ export interface NxWorkspaceFiles {
   projectFileMap: ProjectFiles
   globalFiles: Array<FileData>
   externalReferences?: NxWorkspaceFilesExternals
+  diagnostics?: ProjectFilesDiagnostics
 }
 
 export interface NxWorkspaceFilesExternals {
@@ -225,6 +393,17 @@ export interface Project {
   targets: Record<string, Target>
 }
 
+export interface ProjectFilesDiagnostics {
+  /** Files within the roots of several projects, which only belong to the project with the nearest root */
+  conflictingFiles: Array<FileOwnershipConflict>
+  /** Projects that have no files */
+  emptyProjects: Array<string>
+  /** Projects whose roots do not exist in the workspace */
+  missingProjectRoots: Array<MissingProjectRoot>
+  /** Roots of several projects, whose files only belong to one of them */
+  duplicateProjectRoots: Array<DuplicateProjectRoot>
+}
+
 export interface ProjectGraph {
   nodes: Record<string, Project>
   dependencies: Record<string, Array<string>>
@@ -233,10 +412,55 @@ export interface ProjectGraph {
 
 export declare export function remove(src: string): void
 
+/** The concrete inputs of a task, as they would be hashed */
+export interface ResolvedInputs {
+  /** The files matched by each project fileset, workspace fileset and dependent task output instruction */
+  files: Record<string, Array<string>>
+  /** The environment variables that are set and match the env inputs */
+  environment: Array<string>
+  runtime: Array<string>
+  externals: Array<string>
+  /** Filesets that did not match any files */
+  warnings: Array<string>
+}
+
 export interface RuntimeInput {
   runtime: string
 }
 
+export interface SearchMatch {
+  file: string
+  /** Starts at 1 */
+  line: number
+  /** Starts at 1, and counts characters instead of bytes */
+  column: number
+  /** The line that matched, without the line ending */
+  text: string
+  /** The part of the line that matched */
+  matched: string
+}
+
+export interface SearchOptions {
+  /** Leaves out files that match any of these globs */
+  exclude?: Array<string>
+  /** Matches the pattern regardless of case */
+  caseInsensitive?: boolean
+  /** Returns at most this many matches, of the first files that match */
+  maxMatches?: number
+  /** Files with more bytes than this are not searched, 1 MiB by default */
+  maxFileSize?: number
+}
+
+/** How the walker treats symlinks */
+export declare const enum SymlinkPolicy {
+  /** Symlinks to files are hashed by the content of the file, symlinks to directories are skipped */
+  content = 'content',
+  /** Symlinks to files and directories within the directory are followed, symlinks that loop are skipped */
+  follow = 'follow',
+  /** Symlinks are not followed, and are hashed by the path that they link to */
+  record = 'record'
+}
+
 export interface Target {
   executor?: string
   inputs?: Array<JsInputs>
@@ -244,6 +468,16 @@ export interface Target {
   options?: string
   configurations?: string
   parallelism?: boolean
+  defaultConfiguration?: string
+  dependsOn?: Array<string | TargetDependencyConfig>
+}
+
+export interface TargetDependencyConfig {
+  projects?: string | Array<string>
+  dependencies?: boolean
+  target: string
+  /** `forward` passes the overrides of a task on to the tasks it depends on, `ignore` (the default) does not */
+  params?: string
 }
 
 export interface Task {
@@ -251,6 +485,8 @@ export interface Task {
   target: TaskTarget
   outputs: Array<string>
   projectRoot?: string
+  /** Options passed on the command line, which override the options of the target */
+  overrides?: any
 }
 
 export interface TaskGraph {
@@ -259,6 +495,15 @@ export interface TaskGraph {
   dependencies: Record<string, Array<string>>
 }
 
+export interface TaskGraphOptions {
+  configuration?: string
+  overrides?: any
+  /** Only creates the tasks of the projects and targets, without the tasks that they depend on */
+  excludeTaskDependencies?: boolean
+  /** Creates the task graph even if the tasks depend on each other circularly */
+  ignoreCycles?: boolean
+}
+
 export interface TaskRun {
   hash: string
   status: string
@@ -284,6 +529,7 @@ export declare export function transferProjectGraph(projectGraph: ProjectGraph):
 export interface UpdatedWorkspaceFiles {
   fileMap: FileMap
   externalReferences: NxWorkspaceFilesExternals
+  diagnostics?: ProjectFilesDiagnostics
 }
 
 export declare export function validateOutputs(outputs: Array<string>): void
@@ -293,9 +539,89 @@ export interface WatchEvent {
   type: EventType
 }
 
+export interface WorkspaceContextOptions {
+  /** The algorithm that the files are hashed with, `xxh3` by default */
+  hashAlgorithm?: HashAlgorithm
+  /**
+   * Reads the hashes of tracked files that did not change from the git index, instead of reading the files.
+   * Every file is then hashed as a git object id, so that the hashes do not depend on whether files changed.
+   */
+  useGitIndex?: boolean
+  /**
+   * Hashes the files that were modified after the workspace files were last hashed again,
+   * even when their mod time, size and inode did not change
+   */
+  paranoidFileHashing?: boolean
+  /** How symlinks are walked, `content` by default */
+  symlinks?: SymlinkPolicy
+  /** Whether to walk git submodules and nested repositories, `true` by default */
+  includeSubmodules?: boolean
+  /** Files with more bytes than this are streamed instead of read into memory at once, 8 MiB by default */
+  largeFileThreshold?: number
+  /** Hashes files above the large file threshold by their size and mod time, without reading them */
+  hashLargeFilesByStats?: boolean
+  /** How workspaces within this workspace, which have their own nx.json, are handled, `include` by default */
+  nestedWorkspaces?: NestedWorkspaces
+  /** Which files are ignored, which should be the same as for the watcher of the workspace */
+  ignores?: IgnoreOptions
+}
+
 /** Public NAPI error codes that are for Node */
 export declare const enum WorkspaceErrors {
   ParseError = 'ParseError',
   Generic = 'Generic'
 }
 
+export interface WorkspaceFileMetadata {
+  file: string
+  hash: string
+  project?: string
+  size: number
+  /** In milliseconds since the unix epoch */
+  modTime: number
+}
+
+export interface WorkspaceFileSize {
+  file: string
+  size: number
+}
+
+export interface WorkspaceFilesOptions {
+  /** Reports files within the roots of several projects, projects without files, and project roots that do not exist */
+  diagnostics?: boolean
+  /**
+   * The roots of the projects by their name, which the diagnostics are reported for.
+   * Projects with the same root share one entry of the project root map, so they are only reported when these are given.
+   */
+  projectRoots?: Record<string, string>
+}
+
+export interface WorkspaceFilesPage {
+  files: Array<WorkspaceFileMetadata>
+  /** Pass this as `after` to get the next page, which is missing on the last page */
+  next?: string
+}
+
+export interface WorkspaceFilesQuery {
+  /** Only files that match any of these globs */
+  globs?: Array<string>
+  /** Leaves out files that match any of these globs */
+  exclude?: Array<string>
+  /** Only files of these projects, which needs the project root map */
+  projects?: Array<string>
+  /** Only files with one of these extensions, such as `ts` or `d.ts` */
+  extensions?: Array<string>
+  /** Only files of at least this many bytes */
+  minSize?: number
+  /** Only files of at most this many bytes */
+  maxSize?: number
+  /** Only files modified at or after this time, in milliseconds since the unix epoch */
+  modifiedAfter?: number
+  /** Only files modified before this time, in milliseconds since the unix epoch */
+  modifiedBefore?: number
+  /** Only files after this one, which is the `next` file of the previous page */
+  after?: string
+  /** How many files are returned at most, 1000 by default */
+  limit?: number
+}
+
@@ -363,6 +363,7 @@ if (!nativeBinding) {
 
 module.exports.ChildProcess = nativeBinding.ChildProcess
 module.exports.FileLock = nativeBinding.FileLock
+module.exports.HashingService = nativeBinding.HashingService
 module.exports.HashPlanner = nativeBinding.HashPlanner
 module.exports.ImportResult = nativeBinding.ImportResult
 module.exports.NxCache = nativeBinding.NxCache
@@ -370,21 +371,27 @@ module.exports.NxTaskHistory = nativeBinding.NxTaskHistory
 module.exports.RustPseudoTerminal = nativeBinding.RustPseudoTerminal
 module.exports.TaskDetails = nativeBinding.TaskDetails
 module.exports.TaskHasher = nativeBinding.TaskHasher
+module.exports.TaskScheduler = nativeBinding.TaskScheduler
 module.exports.Watcher = nativeBinding.Watcher
 module.exports.WorkspaceContext = nativeBinding.WorkspaceContext
 module.exports.closeDbConnection = nativeBinding.closeDbConnection
 module.exports.connectToNxDb = nativeBinding.connectToNxDb
 module.exports.copy = nativeBinding.copy
+module.exports.createTaskGraph = nativeBinding.createTaskGraph
 module.exports.EventType = nativeBinding.EventType
 module.exports.expandOutputs = nativeBinding.expandOutputs
 module.exports.findImports = nativeBinding.findImports
+module.exports.getAffectedProjects = nativeBinding.getAffectedProjects
 module.exports.getBinaryTarget = nativeBinding.getBinaryTarget
 module.exports.getFilesForOutputs = nativeBinding.getFilesForOutputs
 module.exports.getTransformableOutputs = nativeBinding.getTransformableOutputs
+module.exports.HashAlgorithm = nativeBinding.HashAlgorithm
 module.exports.hashArray = nativeBinding.hashArray
 module.exports.hashFile = nativeBinding.hashFile
 module.exports.IS_WASM = nativeBinding.IS_WASM
+module.exports.NestedWorkspaces = nativeBinding.NestedWorkspaces
 module.exports.remove = nativeBinding.remove
+module.exports.SymlinkPolicy = nativeBinding.SymlinkPolicy
 module.exports.testOnlyTransferFileMap = nativeBinding.testOnlyTransferFileMap
 module.exports.transferProjectGraph = nativeBinding.transferProjectGraph
 module.exports.validateOutputs = nativeBinding.validateOutputs
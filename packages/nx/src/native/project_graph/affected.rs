@@ -0,0 +1,301 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use napi::bindgen_prelude::{Either, Either8, External};
+use tracing::trace;
+
+use crate::native::glob::build_glob_set;
+use crate::native::project_graph::types::ProjectGraph;
+use crate::native::project_graph::utils::{create_project_root_mappings, find_project_for_path};
+use crate::native::types::JsInputs;
+use crate::native::utils::find_matching_projects;
+
+const LOCK_FILES: [&str; 6] = [
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "pnpm-lock.yml",
+    "bun.lockb",
+    "bun.lock",
+];
+
+#[napi(object)]
+#[derive(Default)]
+pub struct AffectedProjectsOptions {
+    /// The named inputs of nx.json, which are used to find the projects that depend on files outside of projects
+    pub named_inputs: Option<HashMap<String, Vec<JsInputs>>>,
+    /// The projects that are affected when a lock file changes.
+    /// Either `all` (the default), `auto` to leave it to the changed external nodes, or project patterns.
+    pub projects_affected_by_dependency_updates: Option<Either<String, Vec<String>>>,
+}
+
+/// Gets the projects that are affected by the changed files, sorted by name.
+/// These are the projects that own a changed file, the projects whose inputs include a changed global file,
+/// every project when nx.json or a lock file changed, and the projects that depend on them.
+#[napi]
+pub fn get_affected_projects(
+    changed_files: Vec<String>,
+    project_graph: External<ProjectGraph>,
+    options: Option<AffectedProjectsOptions>,
+) -> anyhow::Result<Vec<String>> {
+    affected_projects(&changed_files, &project_graph, &options.unwrap_or_default())
+}
+
+fn affected_projects(
+    changed_files: &[String],
+    project_graph: &ProjectGraph,
+    options: &AffectedProjectsOptions,
+) -> anyhow::Result<Vec<String>> {
+    let all_projects = || project_graph.nodes.keys().map(String::as_str).collect();
+
+    let touched_projects: HashSet<&str> = if changed_files.iter().any(|f| f == "nx.json") {
+        trace!("nx.json changed, every project is affected");
+        all_projects()
+    } else {
+        let mut touched_projects = HashSet::new();
+
+        let project_root_mappings = create_project_root_mappings(&project_graph.nodes);
+        for file in changed_files {
+            if let Some(project) = find_project_for_path(file, &project_root_mappings) {
+                if let Some((project, _)) = project_graph.nodes.get_key_value(project) {
+                    touched_projects.insert(project.as_str());
+                }
+            }
+        }
+
+        if changed_files
+            .iter()
+            .any(|f| LOCK_FILES.contains(&f.as_str()))
+        {
+            match &options.projects_affected_by_dependency_updates {
+                Some(Either::A(value)) if value == "auto" => {}
+                Some(Either::B(patterns)) => {
+                    let patterns = patterns.iter().map(String::as_str).collect::<Vec<_>>();
+                    for project in find_matching_projects(&patterns, project_graph)? {
+                        if let Some((project, _)) = project_graph.nodes.get_key_value(project) {
+                            touched_projects.insert(project.as_str());
+                        }
+                    }
+                }
+                _ => {
+                    trace!("a lock file changed, every project is affected");
+                    touched_projects = all_projects();
+                }
+            }
+        }
+
+        touched_projects.extend(get_implicitly_touched_projects(
+            changed_files,
+            project_graph,
+            options.named_inputs.as_ref(),
+        )?);
+        touched_projects
+    };
+
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (source, targets) in &project_graph.dependencies {
+        for target in targets {
+            dependents.entry(target).or_default().push(source);
+        }
+    }
+
+    let mut affected: HashSet<&str> = HashSet::new();
+    let mut queue: VecDeque<&str> = touched_projects.into_iter().collect();
+    while let Some(project) = queue.pop_front() {
+        if !affected.insert(project) {
+            continue;
+        }
+        if let Some(project_dependents) = dependents.get(project) {
+            queue.extend(project_dependents.iter().copied());
+        }
+    }
+
+    let mut affected = affected
+        .into_iter()
+        .filter(|project| project_graph.nodes.contains_key(*project))
+        .map(String::from)
+        .collect::<Vec<_>>();
+    affected.sort();
+    Ok(affected)
+}
+
+/// Gets the projects that have a changed `{workspaceRoot}` file in the inputs of their targets
+fn get_implicitly_touched_projects<'a>(
+    changed_files: &[String],
+    project_graph: &'a ProjectGraph,
+    nx_named_inputs: Option<&HashMap<String, Vec<JsInputs>>>,
+) -> anyhow::Result<Vec<&'a str>> {
+    let mut touched_projects = vec![];
+    for (project_name, project) in &project_graph.nodes {
+        let named_inputs = nx_named_inputs
+            .into_iter()
+            .chain(project.named_inputs.as_ref())
+            .flatten()
+            .map(|(name, inputs)| (name.as_str(), inputs))
+            .collect::<HashMap<_, _>>();
+
+        let mut workspace_files = vec![];
+        let mut expanded_named_inputs = HashSet::new();
+        for target in project.targets.values() {
+            if let Some(inputs) = &target.inputs {
+                collect_workspace_files(
+                    inputs,
+                    &named_inputs,
+                    &mut expanded_named_inputs,
+                    &mut workspace_files,
+                );
+            }
+        }
+        if workspace_files.is_empty() {
+            continue;
+        }
+
+        let glob = build_glob_set(&workspace_files)?;
+        if changed_files.iter().any(|file| glob.is_match(file)) {
+            trace!("{project_name} has a changed file in its inputs");
+            touched_projects.push(project_name.as_str());
+        }
+    }
+    Ok(touched_projects)
+}
+
+fn collect_workspace_files<'a>(
+    inputs: &'a [JsInputs],
+    named_inputs: &HashMap<&str, &'a Vec<JsInputs>>,
+    expanded_named_inputs: &mut HashSet<&'a str>,
+    workspace_files: &mut Vec<&'a str>,
+) {
+    for input in inputs {
+        let file_set = match input {
+            Either8::A(inputs_input) => inputs_input.input.as_str(),
+            Either8::B(string) => string.as_str(),
+            Either8::C(file_set) => file_set.fileset.as_str(),
+            _ => continue,
+        };
+
+        if let Some(named_input) = named_inputs.get(file_set) {
+            if expanded_named_inputs.insert(file_set) {
+                collect_workspace_files(
+                    named_input,
+                    named_inputs,
+                    expanded_named_inputs,
+                    workspace_files,
+                );
+            }
+        } else if let Some(file) = file_set.strip_prefix("{workspaceRoot}/") {
+            workspace_files.push(file);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native::project_graph::types::{Project, Target};
+    use crate::native::types::FileSetInput;
+
+    fn create_project_graph() -> ProjectGraph {
+        let project = |root: &str, inputs: Option<Vec<JsInputs>>| Project {
+            root: root.into(),
+            targets: HashMap::from([(
+                "build".to_string(),
+                Target {
+                    inputs,
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+
+        ProjectGraph {
+            nodes: HashMap::from([
+                ("app".into(), project("apps/app", None)),
+                ("lib".into(), project("libs/lib", None)),
+                (
+                    "shared".into(),
+                    project("libs/shared", Some(vec![Either8::B("production".into())])),
+                ),
+                (
+                    "tools".into(),
+                    project(
+                        "tools",
+                        Some(vec![Either8::C(FileSetInput {
+                            fileset: "{workspaceRoot}/scripts/**/*.js".into(),
+                        })]),
+                    ),
+                ),
+            ]),
+            dependencies: HashMap::from([
+                ("app".into(), vec!["lib".into(), "npm:react".into()]),
+                ("lib".into(), vec!["shared".into()]),
+                ("shared".into(), vec![]),
+                ("tools".into(), vec![]),
+            ]),
+            external_nodes: HashMap::new(),
+        }
+    }
+
+    fn affected(changed_files: &[&str], options: AffectedProjectsOptions) -> Vec<String> {
+        let changed_files = changed_files
+            .iter()
+            .map(|f| f.to_string())
+            .collect::<Vec<_>>();
+        affected_projects(&changed_files, &create_project_graph(), &options).unwrap()
+    }
+
+    #[test]
+    fn should_walk_the_projects_that_depend_on_changed_projects() {
+        assert_eq!(
+            affected(&["libs/lib/index.ts"], Default::default()),
+            vec!["app", "lib"]
+        );
+        assert_eq!(
+            affected(&["libs/shared/src/index.ts"], Default::default()),
+            vec!["app", "lib", "shared"]
+        );
+        assert!(affected(&["README.md"], Default::default()).is_empty());
+    }
+
+    #[test]
+    fn should_affect_projects_with_changed_global_files_in_their_inputs() {
+        let options = || AffectedProjectsOptions {
+            named_inputs: Some(HashMap::from([(
+                "production".to_string(),
+                vec![
+                    Either8::B("{projectRoot}/**/*".into()),
+                    Either8::B("{workspaceRoot}/babel.config.json".into()),
+                ],
+            )])),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            affected(&["babel.config.json"], options()),
+            vec!["app", "lib", "shared"]
+        );
+        assert_eq!(affected(&["scripts/release.js"], options()), vec!["tools"]);
+        assert_eq!(affected(&["nx.json"], options()).len(), 4);
+    }
+
+    #[test]
+    fn should_affect_projects_by_lock_file_changes() {
+        assert_eq!(affected(&["yarn.lock"], Default::default()).len(), 4);
+        assert!(affected(
+            &["yarn.lock"],
+            AffectedProjectsOptions {
+                projects_affected_by_dependency_updates: Some(Either::A("auto".into())),
+                ..Default::default()
+            }
+        )
+        .is_empty());
+        assert_eq!(
+            affected(
+                &["yarn.lock"],
+                AffectedProjectsOptions {
+                    projects_affected_by_dependency_updates: Some(Either::B(vec!["lib".into()])),
+                    ..Default::default()
+                }
+            ),
+            vec!["app", "lib"]
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod affected;
 pub mod transfer_project_graph;
 pub mod types;
 pub mod utils;
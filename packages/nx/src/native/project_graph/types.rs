@@ -1,4 +1,5 @@
 use crate::native::types::JsInputs;
+use napi::Either;
 use std::collections::HashMap;
 
 #[napi(object)]
@@ -17,8 +18,22 @@ pub struct Target {
     pub options: Option<String>,
     pub configurations: Option<String>,
     pub parallelism: Option<bool>,
+    pub default_configuration: Option<String>,
+    pub depends_on: Option<Vec<JsTargetDependency>>,
 }
 
+#[napi(object)]
+#[derive(Default)]
+pub struct TargetDependencyConfig {
+    pub projects: Option<Either<String, Vec<String>>>,
+    pub dependencies: Option<bool>,
+    pub target: String,
+    /// `forward` passes the overrides of a task on to the tasks it depends on, `ignore` (the default) does not
+    pub params: Option<String>,
+}
+
+pub(crate) type JsTargetDependency = Either<String, TargetDependencyConfig>;
+
 #[napi(object)]
 #[derive(Default)]
 pub struct Project {
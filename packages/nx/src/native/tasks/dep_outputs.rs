@@ -1,5 +1,14 @@
 use crate::native::tasks::types::HashInstruction;
 use crate::native::tasks::types::{Task, TaskGraph};
+use thiserror::Error;
+
+/// Transitive dependency outputs cannot be planned when the task dependencies are circular.
+/// `path` starts and ends with the same task.
+#[derive(Debug, Error)]
+#[error("The dependent tasks of '{}' are circular: {}", .path[0], .path.join(" --> "))]
+pub struct TaskGraphCycleError {
+    pub path: Vec<String>,
+}
 
 pub(super) fn get_dep_output(
     task: &Task,
@@ -7,12 +16,29 @@ pub(super) fn get_dep_output(
     dependent_tasks_output_files: &str,
     transitive: bool,
 ) -> anyhow::Result<Vec<HashInstruction>> {
-    if !task_graph.dependencies.contains_key(task.id.as_str()) {
+    let mut path = vec![task.id.as_str()];
+    collect_dep_output(
+        task,
+        task_graph,
+        dependent_tasks_output_files,
+        transitive,
+        &mut path,
+    )
+}
+
+fn collect_dep_output<'a>(
+    task: &Task,
+    task_graph: &'a TaskGraph,
+    dependent_tasks_output_files: &str,
+    transitive: bool,
+    path: &mut Vec<&'a str>,
+) -> anyhow::Result<Vec<HashInstruction>> {
+    let Some(task_deps) = task_graph.dependencies.get(task.id.as_str()) else {
         return Ok(vec![]);
-    }
+    };
 
     let mut inputs: Vec<HashInstruction> = vec![];
-    for task_dep in &task_graph.dependencies[task.id.as_str()] {
+    for task_dep in task_deps {
         let child_task = &task_graph.tasks[task_dep.as_str()];
 
         if !child_task.outputs.is_empty() {
@@ -23,14 +49,104 @@ pub(super) fn get_dep_output(
         }
 
         if transitive {
-            inputs.extend(get_dep_output(
+            if let Some(start) = path.iter().position(|id| id == task_dep) {
+                let mut cycle: Vec<String> =
+                    path[start..].iter().map(|id| id.to_string()).collect();
+                cycle.push(task_dep.to_string());
+                return Err(TaskGraphCycleError { path: cycle }.into());
+            }
+
+            path.push(task_dep);
+            inputs.extend(collect_dep_output(
                 child_task,
                 task_graph,
                 dependent_tasks_output_files,
                 transitive,
+                path,
             )?);
+            path.pop();
         }
     }
 
     Ok(inputs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native::tasks::types::TaskTarget;
+    use std::collections::HashMap;
+
+    fn create_task_graph(dependencies: &[(&str, &[&str])]) -> TaskGraph {
+        TaskGraph {
+            roots: vec![],
+            tasks: dependencies
+                .iter()
+                .map(|(id, _)| {
+                    let (project, target) = id.split_once(':').unwrap();
+                    (
+                        id.to_string(),
+                        Task {
+                            id: id.to_string(),
+                            target: TaskTarget {
+                                project: project.to_string(),
+                                target: target.to_string(),
+                                configuration: None,
+                            },
+                            outputs: vec![format!("dist/{project}")],
+                            project_root: Some(project.to_string()),
+                            overrides: None,
+                        },
+                    )
+                })
+                .collect(),
+            dependencies: dependencies
+                .iter()
+                .map(|(id, deps)| (id.to_string(), deps.iter().map(|d| d.to_string()).collect()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn should_get_transitive_dep_outputs() {
+        let task_graph = create_task_graph(&[
+            ("a:build", &["b:build"]),
+            ("b:build", &["c:build"]),
+            ("c:build", &[]),
+        ]);
+
+        let outputs =
+            get_dep_output(&task_graph.tasks["a:build"], &task_graph, "**/*", true).unwrap();
+
+        assert_eq!(
+            outputs,
+            vec![
+                HashInstruction::TaskOutput("**/*".into(), vec!["dist/b".into()]),
+                HashInstruction::TaskOutput("**/*".into(), vec!["dist/c".into()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_name_the_cycle_in_circular_task_dependencies() {
+        let task_graph = create_task_graph(&[
+            ("a:build", &["b:build"]),
+            ("b:build", &["c:build"]),
+            ("c:build", &["b:build"]),
+        ]);
+
+        let direct =
+            get_dep_output(&task_graph.tasks["a:build"], &task_graph, "**/*", false).unwrap();
+        assert_eq!(direct.len(), 1);
+
+        let error = get_dep_output(&task_graph.tasks["a:build"], &task_graph, "**/*", true)
+            .unwrap_err()
+            .downcast::<TaskGraphCycleError>()
+            .unwrap();
+        assert_eq!(error.path, vec!["b:build", "c:build", "b:build"]);
+        assert_eq!(
+            error.to_string(),
+            "The dependent tasks of 'b:build' are circular: b:build --> c:build --> b:build"
+        );
+    }
+}
@@ -1,39 +1,81 @@
+use crate::native::hasher::{HashAlgorithm, Hasher};
 use crate::native::logger::enable_logger;
 use crate::native::tasks::{
     dep_outputs::get_dep_output,
+    hash_plans_archive::{read_hash_plans_archive, write_hash_plans_archive, HashPlansArchive},
     types::{HashInstruction, TaskGraph},
 };
-use crate::native::types::{Input, NxJson};
+use crate::native::types::{Input, JsInputs, NxJson};
 use crate::native::{
     project_graph::types::ProjectGraph,
     tasks::{inputs::SplitInputs, types::Task},
 };
-use napi::bindgen_prelude::External;
+use dashmap::DashMap;
+use napi::bindgen_prelude::{Either8, External};
 use napi::{Env, JsExternal};
+use parking_lot::Mutex;
 use rayon::prelude::*;
 use std::collections::HashMap;
-use tracing::trace;
+use std::sync::{Arc, OnceLock};
+use tracing::{trace, warn};
 
+use crate::native::tasks::hashers::{canonical_json, resolve_task_options};
 use crate::native::tasks::inputs::{
     expand_single_project_inputs, get_inputs, get_inputs_for_dependency, get_named_inputs,
+    split_json_input,
 };
 use crate::native::tasks::utils;
 use crate::native::utils::find_matching_projects;
 
+/// Bump this when the way plans are planned changes, so that plans archived by older versions are not reused
+const HASH_PLANS_VERSION: &str = "2";
+
+/// The instructions that dependencies add to a plan for a named input.
+/// These are the same for every task, so they are computed once and shared between tasks.
+#[derive(Default)]
+struct DependencyInputs {
+    instructions: Vec<HashInstruction>,
+    /// `dependentTasksOutputFiles` inputs of the dependencies, which are resolved for each task
+    deps_outputs: Vec<(String, bool)>,
+}
+
+/// (project, named input, configuration of the task)
+type InputsCacheKey = (String, String, Option<String>);
+
 #[napi]
 pub struct HashPlanner {
     nx_json: NxJson,
     project_graph: External<ProjectGraph>,
+    /// the inputs of a project's transitive dependencies
+    dependency_inputs_cache: DashMap<InputsCacheKey, Arc<Vec<Arc<DependencyInputs>>>>,
+    /// the inputs that a project adds to the plans of its dependents
+    project_inputs_cache: DashMap<InputsCacheKey, Arc<DependencyInputs>>,
+    /// When set, plans are archived in this directory and reused between runs
+    cache_dir: Option<String>,
+    archived_plans: Mutex<HashPlansArchive>,
+    project_graph_fingerprint: OnceLock<String>,
+    /// Circular project dependencies are reported once per planner
+    reported_project_cycles: OnceLock<()>,
 }
 
 #[napi]
 impl HashPlanner {
     #[napi(constructor)]
-    pub fn new(nx_json: NxJson, project_graph: External<ProjectGraph>) -> Self {
+    pub fn new(
+        nx_json: NxJson,
+        project_graph: External<ProjectGraph>,
+        cache_dir: Option<String>,
+    ) -> Self {
         enable_logger();
         Self {
             nx_json,
             project_graph,
+            dependency_inputs_cache: DashMap::new(),
+            project_inputs_cache: DashMap::new(),
+            cache_dir,
+            archived_plans: Mutex::new(HashPlansArchive::default()),
+            project_graph_fingerprint: OnceLock::new(),
+            reported_project_cycles: OnceLock::new(),
         }
     }
 
@@ -42,6 +84,52 @@ impl HashPlanner {
         task_ids: Vec<&str>,
         task_graph: TaskGraph,
     ) -> anyhow::Result<HashMap<String, Vec<HashInstruction>>> {
+        let Some(cache_dir) = &self.cache_dir else {
+            return self.plan_tasks(&task_ids, &task_graph);
+        };
+
+        let fingerprint = self.fingerprint(&task_graph);
+        let mut archive = self.archived_plans.lock();
+        if archive.fingerprint != fingerprint {
+            *archive = read_hash_plans_archive(cache_dir, &fingerprint).unwrap_or_else(|| {
+                HashPlansArchive {
+                    fingerprint,
+                    plans: HashMap::new(),
+                }
+            });
+        }
+
+        let unplanned_task_ids = task_ids
+            .iter()
+            .filter(|id| !archive.plans.contains_key(**id))
+            .copied()
+            .collect::<Vec<_>>();
+        trace!(
+            "reusing {} archived plans",
+            task_ids.len() - unplanned_task_ids.len()
+        );
+        if !unplanned_task_ids.is_empty() {
+            let plans = self.plan_tasks(&unplanned_task_ids, &task_graph)?;
+            archive.plans.extend(plans);
+            write_hash_plans_archive(cache_dir, &archive);
+        }
+
+        Ok(task_ids
+            .into_iter()
+            .map(|id| (id.to_string(), archive.plans[id].clone()))
+            .collect())
+    }
+
+    fn plan_tasks(
+        &self,
+        task_ids: &[&str],
+        task_graph: &TaskGraph,
+    ) -> anyhow::Result<HashMap<String, Vec<HashInstruction>>> {
+        self.reported_project_cycles.get_or_init(|| {
+            for cycle in find_project_cycles(&self.project_graph) {
+                warn!("Circular project dependency: {}", cycle.join(" --> "));
+            }
+        });
         let external_deps_mapped = self.setup_external_deps();
         task_ids
             .par_iter()
@@ -63,11 +151,12 @@ impl HashPlanner {
                     &task.target.project,
                     task,
                     &inputs,
-                    &task_graph,
+                    task_graph,
                     &external_deps_mapped,
-                    &mut Box::new(hashbrown::HashSet::from([task.target.project.to_string()])),
                 )?;
 
+                let options = self.task_options(task)?;
+
                 let mut inputs: Vec<HashInstruction> = target
                     .unwrap_or(vec![])
                     .into_iter()
@@ -78,6 +167,7 @@ impl HashPlanner {
                             "{workspaceRoot}/.gitignore".to_string(),
                             "{workspaceRoot}/.nxignore".to_string(),
                         ]),
+                        options,
                     ])
                     .chain(self_inputs)
                     .collect();
@@ -220,33 +310,138 @@ impl HashPlanner {
         inputs: &SplitInputs,
         task_graph: &TaskGraph,
         external_deps_mapped: &hashbrown::HashMap<&String, Vec<&String>>,
-        visited: &mut Box<hashbrown::HashSet<String>>,
     ) -> anyhow::Result<Vec<HashInstruction>> {
-        let project_deps = &self.project_graph.dependencies[project_name]
-            .iter()
-            .collect::<Vec<_>>();
-        let self_inputs = self.gather_self_inputs(project_name, &inputs.self_inputs);
-        let deps_inputs = self.gather_dependency_inputs(
-            task,
-            &inputs.deps_inputs,
-            task_graph,
-            project_deps,
-            external_deps_mapped,
-            visited,
-        )?;
+        let self_inputs = self.gather_self_inputs(project_name, &inputs.self_inputs)?;
+        // each dependency is only visited once per task,
+        // so dependencies are always planned with the first dependency input
+        let deps_inputs = match inputs.deps_inputs.first() {
+            Some(Input::Inputs { input, .. }) => self.gather_dependency_inputs(
+                project_name,
+                input,
+                task.target.configuration.as_deref(),
+                external_deps_mapped,
+            )?,
+            _ => Arc::default(),
+        };
 
         let deps_outputs =
             self.gather_dependency_outputs(task, task_graph, &inputs.deps_outputs)?;
-        let projects = self.gather_project_inputs(&inputs.project_inputs)?;
+        let mut dependencies_deps_outputs = vec![];
+        let mut deps_inputs_deps_outputs = deps_inputs
+            .iter()
+            .flat_map(|inputs| &inputs.deps_outputs)
+            .collect::<Vec<_>>();
+        deps_inputs_deps_outputs.sort();
+        deps_inputs_deps_outputs.dedup();
+        for (dependent_tasks_output_files, transitive) in deps_inputs_deps_outputs {
+            dependencies_deps_outputs.extend(get_dep_output(
+                task,
+                task_graph,
+                dependent_tasks_output_files,
+                *transitive,
+            )?);
+        }
+        let projects = self
+            .gather_project_inputs(&inputs.project_inputs, task.target.configuration.as_deref())?;
 
         Ok(self_inputs
             .into_iter()
-            .chain(deps_inputs)
+            .chain(
+                deps_inputs
+                    .iter()
+                    .flat_map(|inputs| inputs.instructions.iter().cloned()),
+            )
             .chain(deps_outputs)
+            .chain(dependencies_deps_outputs)
             .chain(projects)
             .collect())
     }
 
+    /// Plans only depend on the named inputs in nx.json, the project graph and the task graph,
+    /// so plans from a previous run can be reused when the fingerprint of these is the same.
+    fn fingerprint(&self, task_graph: &TaskGraph) -> String {
+        let project_graph_fingerprint = self
+            .project_graph_fingerprint
+            .get_or_init(|| self.project_graph_fingerprint());
+
+        let mut hasher = Hasher::with_algorithm(HashAlgorithm::xxh3_128);
+        update_fingerprint(&mut hasher, &[project_graph_fingerprint]);
+        for (id, task) in sorted(&task_graph.tasks) {
+            update_fingerprint(
+                &mut hasher,
+                &[
+                    id,
+                    &task.target.project,
+                    &task.target.target,
+                    task.target.configuration.as_deref().unwrap_or_default(),
+                    task.project_root.as_deref().unwrap_or_default(),
+                    &task
+                        .overrides
+                        .as_ref()
+                        .map(canonical_json)
+                        .unwrap_or_default(),
+                ],
+            );
+            update_fingerprint(&mut hasher, &task.outputs);
+        }
+        for (id, dependencies) in sorted(&task_graph.dependencies) {
+            update_fingerprint(&mut hasher, &[id]);
+            update_fingerprint(&mut hasher, dependencies);
+        }
+        hasher.digest()
+    }
+
+    fn project_graph_fingerprint(&self) -> String {
+        let mut hasher = Hasher::with_algorithm(HashAlgorithm::xxh3_128);
+        update_fingerprint(&mut hasher, &[HASH_PLANS_VERSION]);
+        if let Some(named_inputs) = &self.nx_json.named_inputs {
+            update_named_inputs_fingerprint(&mut hasher, named_inputs);
+        }
+        for (name, project) in sorted(&self.project_graph.nodes) {
+            update_fingerprint(&mut hasher, &[name, &project.root]);
+            update_fingerprint(&mut hasher, project.tags.as_deref().unwrap_or_default());
+            if let Some(named_inputs) = &project.named_inputs {
+                update_named_inputs_fingerprint(&mut hasher, named_inputs);
+            }
+            for (target_name, target) in sorted(&project.targets) {
+                update_fingerprint(
+                    &mut hasher,
+                    &[
+                        target_name,
+                        target.executor.as_deref().unwrap_or_default(),
+                        target.options.as_deref().unwrap_or_default(),
+                        target.configurations.as_deref().unwrap_or_default(),
+                    ],
+                );
+                if let Some(inputs) = &target.inputs {
+                    update_inputs_fingerprint(&mut hasher, inputs);
+                }
+            }
+        }
+        for (name, dependencies) in sorted(&self.project_graph.dependencies) {
+            update_fingerprint(&mut hasher, &[name]);
+            update_fingerprint(&mut hasher, dependencies);
+        }
+        for (name, _) in sorted(&self.project_graph.external_nodes) {
+            update_fingerprint(&mut hasher, &[name]);
+        }
+        hasher.digest()
+    }
+
+    fn task_options(&self, task: &Task) -> anyhow::Result<HashInstruction> {
+        let target = &self.project_graph.nodes[&task.target.project].targets[&task.target.target];
+        let options = resolve_task_options(
+            target,
+            task.target.configuration.as_deref(),
+            task.overrides.as_ref(),
+        )
+        .map_err(|e| anyhow::anyhow!("{}: {e}", task.id))?;
+        Ok(HashInstruction::TaskOptions(
+            task.id.clone(),
+            canonical_json(&options),
+        ))
+    }
+
     fn setup_external_deps(&self) -> hashbrown::HashMap<&String, Vec<&String>> {
         self.project_graph
             .external_nodes
@@ -264,64 +459,133 @@ impl HashPlanner {
             .collect()
     }
 
-    // todo(jcammisuli): parallelize this more. This function takes the longest time to run
-    fn gather_dependency_inputs<'a>(
-        &'a self,
-        task: &Task,
-        inputs: &[Input],
-        task_graph: &TaskGraph,
-        project_deps: &[&'a String],
-        external_deps_mapped: &hashbrown::HashMap<&String, Vec<&'a String>>,
-        visited: &mut Box<hashbrown::HashSet<String>>,
-    ) -> anyhow::Result<Vec<HashInstruction>> {
-        let mut deps_inputs: Vec<HashInstruction> = vec![];
+    fn gather_dependency_inputs(
+        &self,
+        project_name: &str,
+        input: &str,
+        configuration: Option<&str>,
+        external_deps_mapped: &hashbrown::HashMap<&String, Vec<&String>>,
+    ) -> anyhow::Result<Arc<Vec<Arc<DependencyInputs>>>> {
+        let cache_key = (
+            project_name.to_string(),
+            input.to_string(),
+            configuration.map(String::from),
+        );
+        if let Some(dependency_inputs) = self.dependency_inputs_cache.get(&cache_key) {
+            return Ok(Arc::clone(&dependency_inputs));
+        }
+
+        let (project_deps, external_deps) = self.find_dependencies(project_name);
+        let mut dependency_inputs = project_deps
+            .par_iter()
+            .map(|dep| self.gather_project_inputs_for_dependents(dep, input, configuration))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut external_instructions = vec![];
+        for dep in external_deps {
+            // todo(jcammisuli): add a check to skip this when the new task hasher is ready, and when `AllExternalDependencies` is used
+            if let Some(external_deps) = external_deps_mapped.get(dep) {
+                external_instructions.push(HashInstruction::External(dep.to_string()));
+                external_instructions.extend(
+                    external_deps
+                        .iter()
+                        .map(|s| HashInstruction::External(s.to_string())),
+                );
+            }
+        }
+        dependency_inputs.push(Arc::new(DependencyInputs {
+            instructions: external_instructions,
+            deps_outputs: vec![],
+        }));
+
+        let dependency_inputs = Arc::new(dependency_inputs);
+        self.dependency_inputs_cache
+            .insert(cache_key, Arc::clone(&dependency_inputs));
+        Ok(dependency_inputs)
+    }
+
+    fn gather_project_inputs_for_dependents(
+        &self,
+        project_name: &str,
+        input: &str,
+        configuration: Option<&str>,
+    ) -> anyhow::Result<Arc<DependencyInputs>> {
+        let cache_key = (
+            project_name.to_string(),
+            input.to_string(),
+            configuration.map(String::from),
+        );
+        if let Some(project_inputs) = self.project_inputs_cache.get(&cache_key) {
+            return Ok(Arc::clone(&project_inputs));
+        }
+
+        let named_input = Input::Inputs {
+            input,
+            dependencies: true,
+        };
+        let project_inputs = match get_inputs_for_dependency(
+            &self.project_graph.nodes[project_name],
+            &self.nx_json,
+            &named_input,
+            configuration,
+        )? {
+            Some(inputs) => Arc::new(DependencyInputs {
+                instructions: self.gather_self_inputs(project_name, &inputs.self_inputs)?,
+                deps_outputs: inputs
+                    .deps_outputs
+                    .iter()
+                    .filter_map(|input| match input {
+                        Input::DepsOutputs {
+                            dependent_tasks_output_files,
+                            transitive,
+                        } => Some((dependent_tasks_output_files.to_string(), *transitive)),
+                        _ => None,
+                    })
+                    .collect(),
+            }),
+            None => Arc::default(),
+        };
 
-        for input in inputs {
-            for dep in project_deps {
-                if visited.contains(*dep) {
+        self.project_inputs_cache
+            .insert(cache_key, Arc::clone(&project_inputs));
+        Ok(project_inputs)
+    }
+
+    /// Finds the projects and external nodes that a project depends on, directly or through other projects.
+    /// Dependencies of external nodes are not included.
+    ///
+    /// Circular project dependencies are allowed: every project of a cycle is a dependency of the others,
+    /// so the result does not depend on the order that the graph is traversed in.
+    fn find_dependencies(&self, project_name: &str) -> (Vec<&String>, Vec<&String>) {
+        let mut visited = hashbrown::HashSet::new();
+        visited.insert(project_name);
+        let mut stack = vec![project_name];
+        let mut project_deps = vec![];
+        let mut external_deps = vec![];
+        while let Some(current) = stack.pop() {
+            let Some(dependencies) = self.project_graph.dependencies.get(current) else {
+                continue;
+            };
+            for dep in dependencies {
+                if !visited.insert(dep.as_str()) {
                     continue;
                 }
-                visited.insert(dep.to_string());
-
-                if self.project_graph.nodes.contains_key(*dep) {
-                    let Some(dep_inputs) = get_inputs_for_dependency(
-                        &self.project_graph.nodes[*dep],
-                        &self.nx_json,
-                        input,
-                    )?
-                    else {
-                        continue;
-                    };
-                    deps_inputs.extend(self.self_and_deps_inputs(
-                        dep,
-                        task,
-                        &dep_inputs,
-                        task_graph,
-                        external_deps_mapped,
-                        visited,
-                    )?);
+                if self.project_graph.nodes.contains_key(dep) {
+                    project_deps.push(dep);
+                    stack.push(dep);
                 } else {
-                    // todo(jcammisuli): add a check to skip this when the new task hasher is ready, and when `AllExternalDependencies` is used
-                    if let Some(external_deps) = external_deps_mapped.get(dep) {
-                        deps_inputs.push(HashInstruction::External(dep.to_string()));
-                        deps_inputs.extend(
-                            external_deps
-                                .iter()
-                                .map(|s| HashInstruction::External(s.to_string())),
-                        );
-                    }
+                    external_deps.push(dep);
                 }
             }
         }
-
-        Ok(deps_inputs)
+        (project_deps, external_deps)
     }
 
     fn gather_self_inputs(
         &self,
         project_name: &str,
         self_inputs: &[Input],
-    ) -> Vec<HashInstruction> {
+    ) -> anyhow::Result<Vec<HashInstruction>> {
         let (project_file_sets, workspace_file_sets): (Vec<&str>, Vec<&str>) = self_inputs
             .iter()
             .filter_map(|input| match input {
@@ -355,17 +619,38 @@ impl HashPlanner {
                 workspace_file_sets.iter().map(|f| f.to_string()).collect(),
             )]
         };
-        let runtime_and_env_inputs = self_inputs.iter().filter_map(|i| match i {
-            Input::Runtime(runtime) => Some(HashInstruction::Runtime(runtime.to_string())),
-            Input::Environment(env) => Some(HashInstruction::Environment(env.to_string())),
-            _ => None,
-        });
+        let runtime_and_env_inputs = self_inputs
+            .iter()
+            .filter_map(|i| match i {
+                Input::Runtime(runtime) => Some(Ok(HashInstruction::Runtime(runtime.to_string()))),
+                Input::Environment(env) => Some(Ok(HashInstruction::Environment(env.to_string()))),
+                Input::Json(json) => Some(self.json_input(project_name, json)),
+                _ => None,
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
-        project_inputs
+        Ok(project_inputs
             .into_iter()
             .chain(workspace_file_set_inputs)
             .chain(runtime_and_env_inputs)
-            .collect()
+            .collect())
+    }
+
+    fn json_input(&self, project_name: &str, json: &str) -> anyhow::Result<HashInstruction> {
+        let (file, pointer) = split_json_input(json)?;
+        let file = if let Some(file) = file.strip_prefix("{workspaceRoot}/") {
+            file.to_string()
+        } else if let Some(file) = file.strip_prefix("{projectRoot}/") {
+            let project_root = &self.project_graph.nodes[project_name].root;
+            if project_root == "." {
+                file.to_string()
+            } else {
+                format!("{project_root}/{file}")
+            }
+        } else {
+            anyhow::bail!(r#""{json}" is an invalid json input"#);
+        };
+        Ok(HashInstruction::JsonFile(file, pointer.to_string()))
     }
 
     fn gather_dependency_outputs(
@@ -402,6 +687,7 @@ impl HashPlanner {
     fn gather_project_inputs(
         &self,
         project_inputs: &[Input],
+        configuration: Option<&str>,
     ) -> anyhow::Result<Vec<HashInstruction>> {
         let mut result: Vec<HashInstruction> = vec![];
         for project in project_inputs {
@@ -410,8 +696,11 @@ impl HashPlanner {
             };
             let projects = find_matching_projects(projects, &self.project_graph)?;
             for project in projects {
-                let named_inputs =
-                    get_named_inputs(&self.nx_json, &self.project_graph.nodes[project]);
+                let named_inputs = get_named_inputs(
+                    &self.nx_json,
+                    &self.project_graph.nodes[project],
+                    configuration,
+                );
                 let expanded_input = expand_single_project_inputs(
                     &vec![Input::Inputs {
                         input,
@@ -419,13 +708,99 @@ impl HashPlanner {
                     }],
                     &named_inputs,
                 )?;
-                result.extend(self.gather_self_inputs(project, &expanded_input))
+                result.extend(self.gather_self_inputs(project, &expanded_input)?)
             }
         }
         Ok(result)
     }
 }
 
+fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(key, _)| *key);
+    entries
+}
+
+/// Every value is terminated so that values cannot run into the next one
+fn update_fingerprint<S: AsRef<str>>(hasher: &mut Hasher, values: &[S]) {
+    for value in values {
+        hasher.update(value.as_ref().as_bytes());
+        hasher.update(b"\0");
+    }
+    hasher.update(b"\n");
+}
+
+fn update_inputs_fingerprint(hasher: &mut Hasher, inputs: &[JsInputs]) {
+    let inputs = inputs
+        .iter()
+        .map(|input| match input {
+            Either8::A(inputs_input) => {
+                format!("{:?} {:?}", Input::from(input), inputs_input.configurations)
+            }
+            _ => format!("{:?}", Input::from(input)),
+        })
+        .collect::<Vec<_>>();
+    update_fingerprint(hasher, &inputs);
+}
+
+fn update_named_inputs_fingerprint(
+    hasher: &mut Hasher,
+    named_inputs: &HashMap<String, Vec<JsInputs>>,
+) {
+    for (name, inputs) in sorted(named_inputs) {
+        update_fingerprint(hasher, &[name]);
+        update_inputs_fingerprint(hasher, inputs);
+    }
+}
+
+/// Finds the circular dependencies between projects, as the path from the first project
+/// of each cycle back to itself. Each dependency that closes a cycle is reported once.
+fn find_project_cycles(project_graph: &ProjectGraph) -> Vec<Vec<&str>> {
+    let dependencies_of = |project: &str| {
+        project_graph
+            .dependencies
+            .get(project)
+            .map(|deps| deps.as_slice())
+            .unwrap_or_default()
+            .iter()
+    };
+    let mut project_names = project_graph.nodes.keys().collect::<Vec<_>>();
+    project_names.sort();
+
+    let mut finished = hashbrown::HashSet::new();
+    // project -> its position in the current path
+    let mut in_path: hashbrown::HashMap<&str, usize> = hashbrown::HashMap::new();
+    let mut cycles = vec![];
+    for project_name in project_names {
+        if finished.contains(project_name.as_str()) {
+            continue;
+        }
+        let mut path = vec![project_name.as_str()];
+        in_path.insert(project_name, 0);
+        let mut stack = vec![dependencies_of(project_name)];
+        while let Some(dependencies) = stack.last_mut() {
+            let Some(dep) = dependencies.next() else {
+                stack.pop();
+                let project = path.pop().expect("the path has an entry for each iterator");
+                in_path.remove(project);
+                finished.insert(project);
+                continue;
+            };
+            if !project_graph.nodes.contains_key(dep) || finished.contains(dep.as_str()) {
+                continue;
+            }
+            if let Some(start) = in_path.get(dep.as_str()) {
+                cycles.push([&path[*start..], &[dep.as_str()]].concat());
+                continue;
+            }
+            in_path.insert(dep, path.len());
+            path.push(dep);
+            stack.push(dependencies_of(dep));
+        }
+    }
+    cycles
+}
+
 fn find_external_dependency_node_name<'a>(
     package_name: &str,
     project_graph: &'a ProjectGraph,
@@ -446,3 +821,493 @@ fn find_external_dependency_node_name<'a>(
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native::project_graph::types::{ExternalNode, Project, Target};
+    use crate::native::tasks::hashers::collect_files;
+    use crate::native::tasks::types::TaskTarget;
+    use crate::native::types::{
+        DepsOutputsInput, EnvironmentInput, FileData, FileSetInput, InputsInput, JsInputs,
+        JsonInput, RuntimeInput,
+    };
+
+    fn file_set(fileset: &str) -> JsInputs {
+        Either8::C(FileSetInput {
+            fileset: fileset.to_string(),
+        })
+    }
+
+    /// Creates a graph where every project depends on a few projects before it,
+    /// so that dependencies are shared by many projects and chains of dependencies are long
+    fn create_synthetic_workspace(project_count: usize) -> (NxJson, ProjectGraph, TaskGraph) {
+        let nx_json = NxJson {
+            named_inputs: Some(HashMap::from([
+                (
+                    "production".to_string(),
+                    vec![
+                        file_set("{projectRoot}/**/*"),
+                        file_set("!{projectRoot}/**/*.spec.ts"),
+                        Either8::B("sharedGlobals".to_string()),
+                        Either8::G(DepsOutputsInput {
+                            dependent_tasks_output_files: "**/*.d.ts".to_string(),
+                            transitive: None,
+                        }),
+                    ],
+                ),
+                (
+                    "sharedGlobals".to_string(),
+                    vec![
+                        file_set("{workspaceRoot}/babel.config.json"),
+                        Either8::E(EnvironmentInput {
+                            env: "NODE_ENV".to_string(),
+                        }),
+                    ],
+                ),
+            ])),
+        };
+
+        let project_name = |i: usize| format!("proj-{i}");
+        let mut nodes = HashMap::new();
+        let mut dependencies = HashMap::new();
+        let mut tasks = HashMap::new();
+        let mut task_dependencies = HashMap::new();
+        for i in 0..project_count {
+            let name = project_name(i);
+            nodes.insert(
+                name.clone(),
+                Project {
+                    root: format!("libs/{name}"),
+                    targets: HashMap::from([(
+                        "build".to_string(),
+                        Target {
+                            inputs: Some(vec![
+                                Either8::B("production".to_string()),
+                                Either8::B("^production".to_string()),
+                            ]),
+                            outputs: Some(vec![format!("dist/libs/{name}")]),
+                            ..Default::default()
+                        },
+                    )]),
+                    ..Default::default()
+                },
+            );
+
+            let mut deps = vec!["npm:react".to_string()];
+            if i > 0 {
+                deps.extend([i / 2, i / 3].into_iter().map(project_name));
+                if i % 50 != 0 {
+                    deps.push(project_name(i - 1));
+                }
+                deps.sort();
+                deps.dedup();
+            }
+            let task_id = format!("{name}:build");
+            task_dependencies.insert(
+                task_id.clone(),
+                deps.iter()
+                    .filter(|dep| dep.starts_with("proj-"))
+                    .map(|dep| format!("{dep}:build"))
+                    .collect(),
+            );
+            dependencies.insert(name.clone(), deps);
+            tasks.insert(
+                task_id.clone(),
+                Task {
+                    id: task_id,
+                    target: TaskTarget {
+                        project: name.clone(),
+                        target: "build".to_string(),
+                        configuration: None,
+                    },
+                    outputs: vec![format!("dist/libs/{name}")],
+                    project_root: Some(format!("libs/{name}")),
+                    overrides: None,
+                },
+            );
+        }
+        dependencies.insert(
+            "npm:react".to_string(),
+            vec!["npm:loose-envify".to_string()],
+        );
+
+        let external_nodes = HashMap::from([
+            (
+                "npm:react".to_string(),
+                ExternalNode {
+                    package_name: Some("react".to_string()),
+                    version: "18.3.1".to_string(),
+                    hash: None,
+                },
+            ),
+            (
+                "npm:loose-envify".to_string(),
+                ExternalNode {
+                    package_name: Some("loose-envify".to_string()),
+                    version: "1.4.0".to_string(),
+                    hash: None,
+                },
+            ),
+        ]);
+
+        let project_graph = ProjectGraph {
+            nodes,
+            dependencies,
+            external_nodes,
+        };
+        let task_graph = TaskGraph {
+            roots: vec![],
+            tasks,
+            dependencies: task_dependencies,
+        };
+        (nx_json, project_graph, task_graph)
+    }
+
+    #[test]
+    fn should_plan_dependency_inputs() {
+        let (nx_json, project_graph, task_graph) = create_synthetic_workspace(10);
+        let planner = HashPlanner::new(nx_json, External::new(project_graph), None);
+
+        let plans = planner
+            .get_plans_internal(vec!["proj-4:build"], task_graph)
+            .unwrap();
+
+        assert_eq!(
+            plans["proj-4:build"]
+                .iter()
+                .map(|instruction| instruction.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "workspace:[{workspaceRoot}/babel.config.json]",
+                "workspace:[{workspaceRoot}/nx.json,{workspaceRoot}/.gitignore,{workspaceRoot}/.nxignore]",
+                "env:NODE_ENV",
+                "env:NX_CLOUD_ENCRYPTION_KEY",
+                "proj-0:{projectRoot}/**/*,!{projectRoot}/**/*.spec.ts",
+                "proj-1:{projectRoot}/**/*,!{projectRoot}/**/*.spec.ts",
+                "proj-2:{projectRoot}/**/*,!{projectRoot}/**/*.spec.ts",
+                "proj-3:{projectRoot}/**/*,!{projectRoot}/**/*.spec.ts",
+                "proj-4:{projectRoot}/**/*,!{projectRoot}/**/*.spec.ts",
+                "proj-0:ProjectConfiguration",
+                "proj-1:ProjectConfiguration",
+                "proj-2:ProjectConfiguration",
+                "proj-3:ProjectConfiguration",
+                "proj-4:ProjectConfiguration",
+                "proj-0:TsConfig",
+                "proj-1:TsConfig",
+                "proj-2:TsConfig",
+                "proj-3:TsConfig",
+                "proj-4:TsConfig",
+                "**/*.d.ts:dist/libs/proj-1",
+                "**/*.d.ts:dist/libs/proj-2",
+                "**/*.d.ts:dist/libs/proj-3",
+                "npm:loose-envify",
+                "npm:react",
+                "AllExternalDependencies",
+                "proj-4:build:Options",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_plan_circular_project_dependencies_regardless_of_order() {
+        let (nx_json, mut project_graph, task_graph) = create_synthetic_workspace(3);
+        // proj-0 --> proj-2 --> proj-1 --> proj-0
+        project_graph
+            .dependencies
+            .get_mut("proj-0")
+            .unwrap()
+            .push("proj-2".to_string());
+        let planner = HashPlanner::new(nx_json, External::new(project_graph), None);
+
+        let plans = planner
+            .get_plans_internal(
+                vec!["proj-0:build", "proj-1:build", "proj-2:build"],
+                task_graph,
+            )
+            .unwrap();
+
+        for (task_id, plan) in &plans {
+            for project in ["proj-0", "proj-1", "proj-2"] {
+                assert!(
+                    plan.contains(&HashInstruction::ProjectConfiguration(project.to_string())),
+                    "{task_id} should depend on {project}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn should_error_on_circular_task_dependencies() {
+        let (mut nx_json, project_graph, mut task_graph) = create_synthetic_workspace(3);
+        nx_json.named_inputs.as_mut().unwrap().insert(
+            "production".to_string(),
+            vec![Either8::G(DepsOutputsInput {
+                dependent_tasks_output_files: "**/*.d.ts".to_string(),
+                transitive: Some(true),
+            })],
+        );
+        task_graph
+            .dependencies
+            .insert("proj-1:build".to_string(), vec!["proj-2:build".to_string()]);
+        task_graph
+            .dependencies
+            .insert("proj-2:build".to_string(), vec!["proj-1:build".to_string()]);
+        let planner = HashPlanner::new(nx_json, External::new(project_graph), None);
+
+        let error = planner
+            .get_plans_internal(vec!["proj-2:build"], task_graph)
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "The dependent tasks of 'proj-2:build' are circular: proj-2:build --> proj-1:build --> proj-2:build"
+        );
+    }
+
+    #[test]
+    fn should_reuse_archived_plans_with_the_same_fingerprint() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache_dir = Some(temp_dir.path().display().to_string());
+        let plan = |project_graph: ProjectGraph, nx_json: NxJson, task_graph: TaskGraph| {
+            let planner =
+                HashPlanner::new(nx_json, External::new(project_graph), cache_dir.clone());
+            let fingerprint = planner.fingerprint(&task_graph);
+            let plans = planner
+                .get_plans_internal(vec!["proj-4:build"], task_graph)
+                .unwrap();
+            (fingerprint, plans)
+        };
+
+        let (nx_json, project_graph, task_graph) = create_synthetic_workspace(10);
+        let (fingerprint, plans) = plan(project_graph, nx_json, task_graph);
+        let archive = read_hash_plans_archive(temp_dir.path(), &fingerprint).unwrap();
+        assert_eq!(archive.plans, plans);
+
+        let (nx_json, project_graph, task_graph) = create_synthetic_workspace(10);
+        let (reused_fingerprint, reused_plans) = plan(project_graph, nx_json, task_graph);
+        assert_eq!(reused_fingerprint, fingerprint);
+        assert_eq!(reused_plans, plans);
+
+        let (nx_json, mut project_graph, task_graph) = create_synthetic_workspace(10);
+        project_graph
+            .dependencies
+            .get_mut("proj-4")
+            .unwrap()
+            .push("proj-9".to_string());
+        let (changed_fingerprint, changed_plans) = plan(project_graph, nx_json, task_graph);
+        assert_ne!(changed_fingerprint, fingerprint);
+        assert!(changed_plans["proj-4:build"]
+            .contains(&HashInstruction::ProjectConfiguration("proj-9".to_string())));
+    }
+
+    #[test]
+    fn should_find_every_circular_project_dependency() {
+        let (_, mut project_graph, _) = create_synthetic_workspace(5);
+        assert!(find_project_cycles(&project_graph).is_empty());
+
+        project_graph
+            .dependencies
+            .get_mut("proj-1")
+            .unwrap()
+            .push("proj-4".to_string());
+        project_graph
+            .dependencies
+            .insert("proj-2".to_string(), vec!["proj-2".to_string()]);
+
+        let mut cycles = find_project_cycles(&project_graph)
+            .into_iter()
+            .map(|cycle| cycle.join(" --> "))
+            .collect::<Vec<_>>();
+        cycles.sort();
+        assert_eq!(
+            cycles,
+            vec![
+                "proj-1 --> proj-4 --> proj-1",
+                "proj-1 --> proj-4 --> proj-3 --> proj-1",
+                "proj-2 --> proj-2"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_error_on_invalid_json_inputs() {
+        for json in [
+            "{workspaceRoot}/package.json",
+            "{workspaceRoot}/package.json#name",
+            "package.json#/name",
+            "{projectRoot}package.json#/name",
+        ] {
+            let (nx_json, mut project_graph, task_graph) = create_synthetic_workspace(1);
+            project_graph
+                .nodes
+                .get_mut("proj-0")
+                .unwrap()
+                .targets
+                .get_mut("build")
+                .unwrap()
+                .inputs = Some(vec![Either8::H(JsonInput {
+                json: json.to_string(),
+            })]);
+            let planner = HashPlanner::new(nx_json, External::new(project_graph), None);
+
+            let error = planner
+                .get_plans_internal(vec!["proj-0:build"], task_graph)
+                .unwrap_err();
+
+            assert!(
+                error
+                    .to_string()
+                    .starts_with(&format!(r#""{json}" is an invalid json input."#)),
+                "{error}"
+            );
+        }
+    }
+
+    #[test]
+    fn should_exclude_files_of_inherited_named_inputs_with_negated_filesets() {
+        let (mut nx_json, mut project_graph, task_graph) = create_synthetic_workspace(1);
+        nx_json.named_inputs.as_mut().unwrap().extend([
+            ("default".to_string(), vec![file_set("{projectRoot}/**/*")]),
+            ("docs".to_string(), vec![file_set("{projectRoot}/**/*.md")]),
+        ]);
+        project_graph
+            .nodes
+            .get_mut("proj-0")
+            .unwrap()
+            .targets
+            .get_mut("build")
+            .unwrap()
+            .inputs = Some(vec![
+            Either8::B("default".to_string()),
+            Either8::B("!{projectRoot}/**/*.md".to_string()),
+            Either8::B("!docs".to_string()),
+        ]);
+        let planner = HashPlanner::new(nx_json, External::new(project_graph), None);
+
+        let plans = planner
+            .get_plans_internal(vec!["proj-0:build"], task_graph)
+            .unwrap();
+
+        let file_sets = plans["proj-0:build"]
+            .iter()
+            .find_map(|instruction| match instruction {
+                HashInstruction::ProjectFileSet(project, file_sets) if project == "proj-0" => {
+                    Some(file_sets.clone())
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            file_sets,
+            vec!["{projectRoot}/**/*", "!{projectRoot}/**/*.md"]
+        );
+        let project_files = HashMap::from([(
+            "proj-0".to_string(),
+            ["libs/proj-0/index.ts", "libs/proj-0/README.md"]
+                .map(|file| FileData {
+                    file: file.to_string(),
+                    hash: "hash".to_string(),
+                })
+                .to_vec(),
+        )]);
+        let files = collect_files("proj-0", "libs/proj-0", &file_sets, &project_files).unwrap();
+        assert_eq!(
+            files
+                .into_iter()
+                .map(|f| f.file.as_str())
+                .collect::<Vec<_>>(),
+            vec!["libs/proj-0/index.ts"]
+        );
+    }
+
+    #[test]
+    fn should_exclude_named_inputs_and_resolve_configuration_inputs() {
+        let (mut nx_json, mut project_graph, mut task_graph) = create_synthetic_workspace(1);
+        nx_json.named_inputs.as_mut().unwrap().extend([
+            (
+                "withoutGlobals".to_string(),
+                vec![
+                    Either8::B("production".to_string()),
+                    Either8::B("!sharedGlobals".to_string()),
+                ],
+            ),
+            (
+                "node".to_string(),
+                vec![Either8::D(RuntimeInput {
+                    runtime: "node -v".to_string(),
+                })],
+            ),
+        ]);
+        project_graph
+            .nodes
+            .get_mut("proj-0")
+            .unwrap()
+            .targets
+            .get_mut("build")
+            .unwrap()
+            .inputs = Some(vec![
+            Either8::B("withoutGlobals".to_string()),
+            Either8::A(InputsInput {
+                input: "node".to_string(),
+                dependencies: None,
+                projects: None,
+                configurations: Some(vec!["production".to_string()]),
+            }),
+        ]);
+        task_graph.tasks.insert(
+            "proj-0:build:production".to_string(),
+            Task {
+                id: "proj-0:build:production".to_string(),
+                target: TaskTarget {
+                    project: "proj-0".to_string(),
+                    target: "build".to_string(),
+                    configuration: Some("production".to_string()),
+                },
+                outputs: vec![],
+                project_root: Some("libs/proj-0".to_string()),
+                overrides: None,
+            },
+        );
+        let planner = HashPlanner::new(nx_json, External::new(project_graph), None);
+
+        let plans = planner
+            .get_plans_internal(vec!["proj-0:build", "proj-0:build:production"], task_graph)
+            .unwrap();
+
+        for plan in plans.values() {
+            assert!(!plan.contains(&HashInstruction::Environment("NODE_ENV".to_string())));
+            assert!(!plan.contains(&HashInstruction::WorkspaceFileSet(vec![
+                "{workspaceRoot}/babel.config.json".to_string()
+            ])));
+        }
+        let node = HashInstruction::Runtime("node -v".to_string());
+        assert!(!plans["proj-0:build"].contains(&node));
+        assert!(plans["proj-0:build:production"].contains(&node));
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with `cargo test --release -- --ignored`"]
+    fn should_plan_large_workspaces() {
+        let (nx_json, project_graph, task_graph) = create_synthetic_workspace(5000);
+        let task_ids = task_graph.tasks.keys().cloned().collect::<Vec<_>>();
+        let planner = HashPlanner::new(nx_json, External::new(project_graph), None);
+
+        let now = std::time::Instant::now();
+        let plans = planner
+            .get_plans_internal(task_ids.iter().map(|id| id.as_str()).collect(), task_graph)
+            .unwrap();
+        println!("planned 5000 tasks in {:?}", now.elapsed());
+
+        assert_eq!(plans.len(), 5000);
+        assert!(
+            plans["proj-4999:build"].contains(&HashInstruction::ProjectFileSet(
+                "proj-0".to_string(),
+                vec![
+                    "{projectRoot}/**/*".to_string(),
+                    "!{projectRoot}/**/*.spec.ts".to_string()
+                ]
+            ))
+        );
+    }
+}
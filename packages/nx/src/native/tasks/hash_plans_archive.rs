@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+use rkyv::{Archive, Deserialize, Infallible, Serialize};
+use tracing::trace;
+
+use crate::native::tasks::types::HashInstruction;
+
+const NX_HASH_PLANS_DIRECTORY: &str = "hash-plans";
+/// Archives that were used least recently are removed when there are more than this
+const MAX_HASH_PLANS_ARCHIVES: usize = 16;
+
+/// The hash plans of a task graph as they are written to disk.
+/// Each fingerprint is archived in its own file, so that running different commands
+/// does not overwrite the plans of the other commands.
+#[derive(Archive, Deserialize, Serialize, Debug, Default, PartialEq)]
+#[archive(check_bytes)]
+pub struct HashPlansArchive {
+    pub fingerprint: String,
+    pub plans: HashMap<String, Vec<HashInstruction>>,
+}
+
+pub fn read_hash_plans_archive<P: AsRef<Path>>(
+    cache_dir: P,
+    fingerprint: &str,
+) -> Option<HashPlansArchive> {
+    let now = std::time::Instant::now();
+    let archive_path = hash_plans_archive_path(cache_dir, fingerprint);
+    if !archive_path.exists() {
+        return None;
+    }
+
+    let archive = std::fs::read(&archive_path)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| {
+            let archived = rkyv::check_archived_root::<HashPlansArchive>(&bytes)
+                .map_err(|_| anyhow!("invalid archive file"))?;
+            if archived.fingerprint.as_str() != fingerprint {
+                return Err(anyhow!("hash plans were planned from different inputs"));
+            }
+            <ArchivedHashPlansArchive as Deserialize<HashPlansArchive, Infallible>>::deserialize(
+                archived,
+                &mut rkyv::Infallible,
+            )
+            .map_err(anyhow::Error::from)
+        });
+
+    match archive {
+        Ok(archive) => {
+            trace!("read hash plans archive in {:?}", now.elapsed());
+            // mark the archive as recently used, so that it is not pruned
+            let _ = std::fs::File::options()
+                .append(true)
+                .open(&archive_path)
+                .and_then(|file| file.set_modified(SystemTime::now()));
+            Some(archive)
+        }
+        Err(e) => {
+            trace!("could not read hash plans archive: {:?}", e);
+            None
+        }
+    }
+}
+
+/// Writes the archive to a temporary file first, and then renames it,
+/// so that a concurrent reader or a crash never sees a partially written archive.
+pub fn write_hash_plans_archive<P: AsRef<Path>>(cache_dir: P, archive: &HashPlansArchive) {
+    let now = std::time::Instant::now();
+    let archive_path = hash_plans_archive_path(&cache_dir, &archive.fingerprint);
+    let temp_path = archive_path.with_extension(format!("{}.tmp", std::process::id()));
+    let result = rkyv::to_bytes::<_, 2048>(archive)
+        .map_err(anyhow::Error::from)
+        .and_then(|encoded| {
+            std::fs::create_dir_all(cache_dir.as_ref().join(NX_HASH_PLANS_DIRECTORY))?;
+            std::fs::write(&temp_path, encoded)?;
+            std::fs::rename(&temp_path, &archive_path).inspect_err(|_| {
+                let _ = std::fs::remove_file(&temp_path);
+            })?;
+            prune_hash_plans_archives(cache_dir.as_ref())
+        });
+
+    match result {
+        Ok(_) => {
+            trace!("write hash plans archive in {:?}", now.elapsed());
+        }
+        Err(e) => {
+            trace!("could not write hash plans archive: {:?}", e);
+        }
+    }
+}
+
+fn hash_plans_archive_path<P: AsRef<Path>>(cache_dir: P, fingerprint: &str) -> PathBuf {
+    cache_dir
+        .as_ref()
+        .join(NX_HASH_PLANS_DIRECTORY)
+        .join(format!("{fingerprint}.nxt"))
+}
+
+/// Removes the least recently used archives, so that at most `MAX_HASH_PLANS_ARCHIVES` are kept
+fn prune_hash_plans_archives(cache_dir: &Path) -> anyhow::Result<()> {
+    let mut archives = std::fs::read_dir(cache_dir.join(NX_HASH_PLANS_DIRECTORY))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "nxt"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect::<Vec<_>>();
+    if archives.len() <= MAX_HASH_PLANS_ARCHIVES {
+        return Ok(());
+    }
+    archives.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in archives.into_iter().skip(MAX_HASH_PLANS_ARCHIVES) {
+        trace!("removing hash plans archive {:?}", path);
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_read_plans_with_the_same_fingerprint() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let archive = HashPlansArchive {
+            fingerprint: "1234".to_string(),
+            plans: HashMap::from([(
+                "proj:build".to_string(),
+                vec![
+                    HashInstruction::ProjectConfiguration("proj".to_string()),
+                    HashInstruction::TaskOutput("**/*".to_string(), vec!["dist/proj".to_string()]),
+                ],
+            )]),
+        };
+        write_hash_plans_archive(temp_dir.path(), &archive);
+
+        assert_eq!(
+            read_hash_plans_archive(temp_dir.path(), "1234"),
+            Some(archive)
+        );
+        assert_eq!(read_hash_plans_archive(temp_dir.path(), "5678"), None);
+    }
+
+    #[test]
+    fn should_keep_the_plans_of_several_fingerprints() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let archive = |fingerprint: usize| HashPlansArchive {
+            fingerprint: fingerprint.to_string(),
+            plans: HashMap::from([(
+                format!("proj-{fingerprint}:build"),
+                vec![HashInstruction::ProjectConfiguration(format!(
+                    "proj-{fingerprint}"
+                ))],
+            )]),
+        };
+        write_hash_plans_archive(temp_dir.path(), &archive(0));
+        write_hash_plans_archive(temp_dir.path(), &archive(1));
+
+        assert_eq!(
+            read_hash_plans_archive(temp_dir.path(), "0"),
+            Some(archive(0))
+        );
+        assert_eq!(
+            read_hash_plans_archive(temp_dir.path(), "1"),
+            Some(archive(1))
+        );
+
+        let archive_count = || {
+            std::fs::read_dir(temp_dir.path().join(NX_HASH_PLANS_DIRECTORY))
+                .unwrap()
+                .count()
+        };
+        for fingerprint in 2..MAX_HASH_PLANS_ARCHIVES + 4 {
+            write_hash_plans_archive(temp_dir.path(), &archive(fingerprint));
+        }
+        assert_eq!(archive_count(), MAX_HASH_PLANS_ARCHIVES);
+    }
+}
@@ -1,17 +1,21 @@
 mod hash_env;
 mod hash_external;
+mod hash_json_file;
 mod hash_project_config;
 mod hash_project_files;
 mod hash_runtime;
+mod hash_task_options;
 mod hash_task_output;
 mod hash_workspace_files;
 mod hash_tsconfig;
 
 pub use hash_env::*;
 pub use hash_external::*;
+pub use hash_json_file::*;
 pub use hash_project_config::*;
 pub use hash_project_files::*;
 pub use hash_runtime::*;
+pub use hash_task_options::*;
 pub use hash_task_output::*;
 pub use hash_workspace_files::*;
 pub use hash_tsconfig::*;
\ No newline at end of file
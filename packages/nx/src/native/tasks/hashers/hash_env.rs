@@ -1,9 +1,54 @@
-use crate::native::hasher::hash;
+use crate::native::glob::{build_glob_set, contains_glob_pattern};
+use crate::native::hasher::{hash_with_algorithm, HashAlgorithm, Hasher};
 use std::collections::HashMap;
 
-pub fn hash_env(env_name: &str, env: &HashMap<String, String>) -> String {
-    let env_value = env.get(env_name).map(|s| s.as_str()).unwrap_or("");
-    hash(env_value.as_bytes())
+use anyhow::*;
+use tracing::trace;
+
+/// Hashes an environment variable, or every variable matching a pattern such as `VITE_*`.
+/// Matching variables are hashed as `name=value\0`, sorted by name. Names cannot contain `=`
+/// and values cannot contain a nul byte, so different variables never hash to the same bytes.
+pub fn hash_env(
+    env_name: &str,
+    env: &HashMap<String, String>,
+    algorithm: HashAlgorithm,
+) -> Result<String> {
+    if !contains_glob_pattern(env_name) {
+        let env_value = env.get(env_name).map(|s| s.as_str()).unwrap_or("");
+        return Ok(hash_with_algorithm(algorithm, env_value.as_bytes()));
+    }
+
+    let matching_env = collect_env(env_name, env)?;
+    if matching_env.is_empty() {
+        return Ok(hash_with_algorithm(algorithm, b""));
+    }
+
+    let mut hasher = Hasher::with_algorithm(algorithm);
+    for (name, value) in matching_env {
+        trace!("Adding {} to hash of {}", name, env_name);
+        hasher.update(name.as_bytes());
+        hasher.update(b"=");
+        hasher.update(value.as_bytes());
+        hasher.update(b"\0");
+    }
+    Ok(hasher.digest())
+}
+
+/// Gets the environment variables that match an env input, sorted by name
+pub fn collect_env<'a>(
+    env_name: &str,
+    env: &'a HashMap<String, String>,
+) -> Result<Vec<(&'a String, &'a String)>> {
+    let mut matching_env = if contains_glob_pattern(env_name) {
+        let glob = build_glob_set(&[env_name])?;
+        env.iter()
+            .filter(|(name, _)| glob.is_match(name.as_str()))
+            .collect::<Vec<_>>()
+    } else {
+        env.get_key_value(env_name).into_iter().collect()
+    };
+    matching_env.sort();
+    Ok(matching_env)
 }
 
 #[cfg(test)]
@@ -15,7 +60,7 @@ mod test {
         let mut env = HashMap::new();
         env.insert("foo".to_string(), "bar".to_string());
         env.insert("baz".to_string(), "qux".to_string());
-        let hash = hash_env("foo", &env);
+        let hash = hash_env("foo", &env, HashAlgorithm::default()).unwrap();
 
         assert_eq!(hash, "15304296276065178466");
     }
@@ -23,8 +68,66 @@ mod test {
     #[test]
     fn should_provide_a_default_hash_if_one_does_not_exist() {
         let env = HashMap::new();
-        let hash = hash_env("foo", &env);
+        let hash = hash_env("foo", &env, HashAlgorithm::default()).unwrap();
 
         assert_eq!(hash, "3244421341483603138");
     }
+
+    #[test]
+    fn should_hash_env_matching_a_pattern() {
+        let env = HashMap::from([
+            ("VITE_API_URL".to_string(), "https://nx.dev".to_string()),
+            ("VITE_MODE".to_string(), "production".to_string()),
+            ("NODE_ENV".to_string(), "production".to_string()),
+        ]);
+
+        assert_eq!(
+            collect_env("VITE_*", &env)
+                .unwrap()
+                .into_iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["VITE_API_URL", "VITE_MODE"]
+        );
+
+        let hash = hash_env("VITE_*", &env, HashAlgorithm::default()).unwrap();
+        let mut env_without_unrelated = env.clone();
+        env_without_unrelated.remove("NODE_ENV");
+        assert_eq!(
+            hash,
+            hash_env("VITE_*", &env_without_unrelated, HashAlgorithm::default()).unwrap()
+        );
+
+        let mut env_with_changed_value = env.clone();
+        env_with_changed_value.insert("VITE_MODE".to_string(), "development".to_string());
+        assert_ne!(
+            hash,
+            hash_env("VITE_*", &env_with_changed_value, HashAlgorithm::default()).unwrap()
+        );
+
+        assert_eq!(
+            hash_env("NEXT_PUBLIC_*", &env, HashAlgorithm::default()).unwrap(),
+            hash_env("NEXT_PUBLIC_API_URL", &env, HashAlgorithm::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_hash_the_boundaries_between_names_and_values_of_a_pattern() {
+        let env = HashMap::from([("VITE_A".to_string(), "BC".to_string())]);
+        let env_with_moved_boundary = HashMap::from([("VITE_AB".to_string(), "C".to_string())]);
+        assert_ne!(
+            hash_env("VITE_*", &env, HashAlgorithm::default()).unwrap(),
+            hash_env("VITE_*", &env_with_moved_boundary, HashAlgorithm::default()).unwrap()
+        );
+
+        let env_with_two_variables = HashMap::from([
+            ("VITE_A".to_string(), "".to_string()),
+            ("VITE_B".to_string(), "".to_string()),
+        ]);
+        let env_with_one_variable = HashMap::from([("VITE_A".to_string(), "VITE_B".to_string())]);
+        assert_ne!(
+            hash_env("VITE_*", &env_with_two_variables, HashAlgorithm::default()).unwrap(),
+            hash_env("VITE_*", &env_with_one_variable, HashAlgorithm::default()).unwrap()
+        );
+    }
 }
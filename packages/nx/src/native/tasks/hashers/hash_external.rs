@@ -1,4 +1,4 @@
-use crate::native::hasher::{hash, hash_array};
+use crate::native::hasher::{hash_array_with_algorithm, hash_with_algorithm, HashAlgorithm};
 use crate::native::project_graph::types::ExternalNode;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -10,6 +10,7 @@ pub fn hash_external(
     external_name: &str,
     externals: &HashMap<String, ExternalNode>,
     cache: Arc<DashMap<String, String>>,
+    algorithm: HashAlgorithm,
 ) -> Result<String> {
     let external = externals
         .get(external_name)
@@ -20,9 +21,9 @@ pub fn hash_external(
     }
 
     let hash = if let Some(external_hash) = &external.hash {
-        hash(external_hash.as_bytes())
+        hash_with_algorithm(algorithm, external_hash.as_bytes())
     } else {
-        hash(external.version.as_bytes())
+        hash_with_algorithm(algorithm, external.version.as_bytes())
     };
 
     cache.insert(external_name.to_string(), hash.clone());
@@ -34,12 +35,15 @@ pub fn hash_all_externals<S: AsRef<str>>(
     sorted_externals: &[S],
     externals: &HashMap<String, ExternalNode>,
     cache: Arc<DashMap<String, String>>,
+    algorithm: HashAlgorithm,
 ) -> Result<String> {
     let hashes = sorted_externals
         .iter()
-        .map(|name| hash_external(name.as_ref(), externals, Arc::clone(&cache)).map(Some))
+        .map(|name| {
+            hash_external(name.as_ref(), externals, Arc::clone(&cache), algorithm).map(Some)
+        })
         .collect::<Result<Vec<Option<String>>>>()?;
-    Ok(hash_array(hashes))
+    Ok(hash_array_with_algorithm(algorithm, hashes))
 }
 
 #[cfg(test)]
@@ -73,12 +77,20 @@ mod test {
     fn test_hash_external() {
         let external_nodes = get_external_nodes_map();
         let cache: Arc<DashMap<String, String>> = Arc::new(DashMap::new());
-        let no_external_node_hash =
-            hash_external("my_external", &external_nodes, Arc::clone(&cache));
+        let no_external_node_hash = hash_external(
+            "my_external",
+            &external_nodes,
+            Arc::clone(&cache),
+            HashAlgorithm::default(),
+        );
         assert_eq!(no_external_node_hash.unwrap(), "3342527690135000204");
 
-        let external_node_hash =
-            hash_external("my_external_with_hash", &external_nodes, Arc::clone(&cache));
+        let external_node_hash = hash_external(
+            "my_external_with_hash",
+            &external_nodes,
+            Arc::clone(&cache),
+            HashAlgorithm::default(),
+        );
         assert_eq!(external_node_hash.unwrap(), "4204073044699973956");
     }
 
@@ -90,6 +102,7 @@ mod test {
             &["my_external", "my_external_with_hash"],
             &external_nodes,
             Arc::clone(&cache),
+            HashAlgorithm::default(),
         );
         assert_eq!(all_externals.unwrap(), "9354284926255893100");
     }
@@ -0,0 +1,202 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::*;
+use dashmap::DashMap;
+use serde_json::Value;
+use tracing::trace;
+
+use crate::native::hasher::{hash_with_algorithm, HashAlgorithm};
+
+/// Hashes the value at a JSON pointer of a json file, so that changes to other parts of the file
+/// do not change the hash. Missing files and values are hashed as empty.
+pub fn hash_json_file(
+    workspace_root: &str,
+    file: &str,
+    pointer: &str,
+    cache: Arc<DashMap<String, String>>,
+    algorithm: HashAlgorithm,
+) -> Result<String> {
+    let cache_key = format!("{file}#{pointer}");
+    if let Some(cached_hash) = cache.get(&cache_key) {
+        return Ok(cached_hash.clone());
+    }
+
+    let value = get_json_value(workspace_root, file, pointer)?;
+    let hash = match value {
+        Some(value) => hash_with_algorithm(algorithm, serde_json::to_string(&value)?.as_bytes()),
+        None => {
+            trace!("{} does not exist in {}", pointer, file);
+            hash_with_algorithm(algorithm, b"")
+        }
+    };
+
+    cache.insert(cache_key, hash.clone());
+    Ok(hash)
+}
+
+/// Reads the value at a JSON pointer of a json file.
+/// Comments and trailing commas are allowed, as they are in tsconfig files.
+pub fn get_json_value(workspace_root: &str, file: &str, pointer: &str) -> Result<Option<Value>> {
+    let Result::Ok(content) = std::fs::read_to_string(Path::new(workspace_root).join(file)) else {
+        trace!("could not read {}", file);
+        return Ok(None);
+    };
+    let mut json: Value = serde_json::from_str(&strip_json_comments(&content))
+        .map_err(|e| anyhow!("Could not parse {file}: {e}"))?;
+    Ok(json.pointer_mut(pointer).map(Value::take))
+}
+
+/// Removes comments and trailing commas, leaving strings untouched
+fn strip_json_comments(content: &str) -> String {
+    let chars = content.chars().collect::<Vec<_>>();
+    let mut result = String::with_capacity(content.len());
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '"' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i = (i + 1).min(chars.len());
+                result.extend(&chars[start..i]);
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i = (i + 2).min(chars.len());
+            }
+            c => {
+                result.push(c);
+                i += 1;
+            }
+        }
+    }
+    remove_trailing_commas(&result)
+}
+
+fn remove_trailing_commas(content: &str) -> String {
+    let chars = content.chars().collect::<Vec<_>>();
+    let mut result = String::with_capacity(content.len());
+    let mut in_string = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            if c == '\\' {
+                result.push(c);
+                i += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                i += 1;
+                continue;
+            }
+        }
+        if let Some(c) = chars.get(i) {
+            result.push(*c);
+        }
+        i += 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::native::hasher::hash;
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+
+    #[test]
+    fn should_only_hash_the_value_at_the_pointer() {
+        let temp = TempDir::new().unwrap();
+        temp.child("package.json")
+            .write_str(r#"{ "name": "workspace", "dependencies": { "react": "18.0.0" } }"#)
+            .unwrap();
+        let workspace_root = temp.display().to_string();
+
+        let react_hash = hash_json_file(
+            &workspace_root,
+            "package.json",
+            "/dependencies/react",
+            Arc::new(DashMap::new()),
+            HashAlgorithm::default(),
+        )
+        .unwrap();
+        assert_eq!(react_hash, hash(br#""18.0.0""#));
+
+        temp.child("package.json")
+            .write_str(r#"{ "name": "renamed", "dependencies": { "react": "18.0.0" } }"#)
+            .unwrap();
+        assert_eq!(
+            hash_json_file(
+                &workspace_root,
+                "package.json",
+                "/dependencies/react",
+                Arc::new(DashMap::new()),
+                HashAlgorithm::default(),
+            )
+            .unwrap(),
+            react_hash
+        );
+
+        assert_eq!(
+            hash_json_file(
+                &workspace_root,
+                "package.json",
+                "/dependencies/vue",
+                Arc::new(DashMap::new()),
+                HashAlgorithm::default(),
+            )
+            .unwrap(),
+            hash(b"")
+        );
+    }
+
+    #[test]
+    fn should_read_json_with_comments_and_trailing_commas() {
+        let temp = TempDir::new().unwrap();
+        temp.child("tsconfig.base.json")
+            .write_str(
+                r#"{
+                    // the paths are hashed selectively
+                    "compilerOptions": {
+                        /* "strict": false, */
+                        "strict": true,
+                        "baseUrl": "//not/a/comment",
+                    },
+                }"#,
+            )
+            .unwrap();
+
+        let value = get_json_value(
+            &temp.display().to_string(),
+            "tsconfig.base.json",
+            "/compilerOptions",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "strict": true, "baseUrl": "//not/a/comment" })
+        );
+    }
+}
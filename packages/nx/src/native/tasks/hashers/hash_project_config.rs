@@ -3,13 +3,15 @@ use std::collections::HashMap;
 use anyhow::*;
 use itertools::Itertools;
 
-use crate::native::hasher::hash;
+use crate::native::hasher::{hash_with_algorithm, HashAlgorithm};
 use crate::native::project_graph::types::Project;
+use crate::native::tasks::hashers::canonical_options;
 use crate::native::types::Input;
 
 pub fn hash_project_config(
     project_name: &str,
     projects: &HashMap<String, Project>,
+    algorithm: HashAlgorithm,
 ) -> Result<String> {
     let project = projects
         .get(project_name)
@@ -25,8 +27,8 @@ pub fn hash_project_config(
                 k,
                 v.executor.as_deref().unwrap_or_default(),
                 v.outputs.as_deref().unwrap_or_default().concat(),
-                v.options.as_deref().unwrap_or_default(),
-                v.configurations.as_deref().unwrap_or_default(),
+                canonical_options(v.options.as_deref()),
+                canonical_options(v.configurations.as_deref()),
                 v.parallelism.unwrap_or_default()
             )
         })
@@ -54,7 +56,8 @@ pub fn hash_project_config(
         })
         .unwrap_or_default();
 
-    Ok(hash(
+    Ok(hash_with_algorithm(
+        algorithm,
         &[
             project.root.as_bytes(),
             tags.as_bytes(),
@@ -141,13 +144,14 @@ mod tests {
             ),
         ]);
 
-        let nx_project_hash = hash_project_config("nx", &projects);
+        let nx_project_hash = hash_project_config("nx", &projects, HashAlgorithm::default());
         assert_eq!(nx_project_hash.unwrap(), "3244421341483603138");
 
-        let js_project_hash = hash_project_config("js", &projects).unwrap();
+        let js_project_hash =
+            hash_project_config("js", &projects, HashAlgorithm::default()).unwrap();
         assert_eq!(js_project_hash, "13565578942842640362");
 
-        let js_unsorted = hash_project_config("js-unsorted", &projects);
+        let js_unsorted = hash_project_config("js-unsorted", &projects, HashAlgorithm::default());
         assert_eq!(js_unsorted.unwrap(), js_project_hash);
     }
 
@@ -155,7 +159,7 @@ mod tests {
     fn test_hash_project_config_with_no_project() {
         let projects = HashMap::<String, Project>::new();
 
-        let result = hash_project_config("nx", &projects);
+        let result = hash_project_config("nx", &projects, HashAlgorithm::default());
 
         assert!(result.is_err());
         assert_eq!(
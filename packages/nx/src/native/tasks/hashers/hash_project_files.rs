@@ -4,6 +4,7 @@ use anyhow::*;
 use tracing::{trace, trace_span};
 
 use crate::native::glob::build_glob_set;
+use crate::native::hasher::{HashAlgorithm, Hasher};
 use crate::native::types::FileData;
 
 pub fn hash_project_files(
@@ -11,20 +12,21 @@ pub fn hash_project_files(
     project_root: &str,
     file_sets: &[String],
     project_file_map: &HashMap<String, Vec<FileData>>,
+    algorithm: HashAlgorithm,
 ) -> Result<String> {
     let _span = trace_span!("hash_project_files", project_name).entered();
     let collected_files = collect_files(project_name, project_root, file_sets, project_file_map)?;
     trace!("collected_files: {:?}", collected_files.len());
-    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+    let mut hasher = Hasher::with_algorithm(algorithm);
     for file in collected_files {
         hasher.update(file.hash.as_bytes());
         hasher.update(file.file.as_bytes());
     }
-    Ok(hasher.digest().to_string())
+    Ok(hasher.digest())
 }
 
 /// base function that should be testable (to make sure that we're getting the proper files back)
-fn collect_files<'a>(
+pub fn collect_files<'a>(
     project_name: &str,
     project_root: &str,
     file_sets: &[String],
@@ -155,7 +157,14 @@ mod tests {
                 file_data4.clone(),
             ],
         );
-        let hash_result = hash_project_files(proj_name, proj_root, file_sets, &file_map).unwrap();
+        let hash_result = hash_project_files(
+            proj_name,
+            proj_root,
+            file_sets,
+            &file_map,
+            HashAlgorithm::default(),
+        )
+        .unwrap();
         assert_eq!(
             hash_result,
             hash(&[
@@ -202,7 +211,14 @@ mod tests {
                 file_data4.clone(),
             ],
         );
-        let hash_result = hash_project_files(proj_name, proj_root, file_sets, &file_map).unwrap();
+        let hash_result = hash_project_files(
+            proj_name,
+            proj_root,
+            file_sets,
+            &file_map,
+            HashAlgorithm::default(),
+        )
+        .unwrap();
         assert_eq!(
             hash_result,
             hash(&[
@@ -1,4 +1,4 @@
-use crate::native::hasher::hash;
+use crate::native::hasher::{hash_with_algorithm, HashAlgorithm};
 use dashmap::DashMap;
 use std::collections::HashMap;
 use std::process::Command;
@@ -17,6 +17,7 @@ pub fn hash_runtime(
     command: &str,
     env: &HashMap<String, String>,
     cache: Arc<DashMap<String, String>>,
+    algorithm: HashAlgorithm,
 ) -> anyhow::Result<String> {
     let cache_key = format!("{}-{:?}", command, env);
 
@@ -40,7 +41,10 @@ pub fn hash_runtime(
 
     let std_out = std::str::from_utf8(&output.stdout)?.trim();
     let std_err = std::str::from_utf8(&output.stderr)?.trim();
-    let hash_result = hash(&[std_out.as_bytes(), std_err.as_bytes()].concat());
+    let hash_result = hash_with_algorithm(
+        algorithm,
+        &[std_out.as_bytes(), std_err.as_bytes()].concat(),
+    );
 
     cache.insert(cache_key, hash_result.clone());
 
@@ -81,7 +85,14 @@ mod tests {
         let env: HashMap<String, String> = HashMap::new();
         let cache = Arc::new(DashMap::new());
 
-        let result = hash_runtime(workspace_root, command, &env, Arc::clone(&cache)).unwrap();
+        let result = hash_runtime(
+            workspace_root,
+            command,
+            &env,
+            Arc::clone(&cache),
+            HashAlgorithm::default(),
+        )
+        .unwrap();
         assert_eq!(result, "10571312846059850300");
     }
 }
@@ -0,0 +1,129 @@
+use anyhow::*;
+use serde_json::{Map, Value};
+
+use crate::native::project_graph::types::Target;
+
+/// Merges the options of a target with the options of the configuration and the overrides of a task,
+/// the same way that they are merged before they are passed to the executor
+pub fn resolve_task_options(
+    target: &Target,
+    configuration: Option<&str>,
+    overrides: Option<&Value>,
+) -> Result<Value> {
+    let mut options = Map::new();
+    if let Some(Value::Object(target_options)) = parse_options(target.options.as_deref())? {
+        options.extend(target_options);
+    }
+    if let Some(configuration) = configuration {
+        if let Some(Value::Object(mut configurations)) =
+            parse_options(target.configurations.as_deref())?
+        {
+            if let Some(Value::Object(configuration_options)) = configurations.remove(configuration)
+            {
+                options.extend(configuration_options);
+            }
+        }
+    }
+    if let Some(Value::Object(overrides)) = overrides {
+        options.extend(
+            overrides
+                .iter()
+                .filter(|(key, _)| key.as_str() != "__overrides_unparsed__")
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+    }
+    Ok(Value::Object(options))
+}
+
+fn parse_options(options: Option<&str>) -> Result<Option<Value>> {
+    options
+        .map(|options| {
+            serde_json::from_str(options).map_err(|e| anyhow!("Could not parse options: {e}"))
+        })
+        .transpose()
+}
+
+/// Canonicalizes options that are serialized as JSON, options that are not valid JSON are kept as they are
+pub fn canonical_options(options: Option<&str>) -> String {
+    match parse_options(options) {
+        Result::Ok(Some(value)) => canonical_json(&value),
+        _ => options.unwrap_or_default().to_string(),
+    }
+}
+
+/// Serializes a JSON value with sorted keys and without whitespace,
+/// so that values that only differ in key order have the same serialization
+pub fn canonical_json(value: &Value) -> String {
+    let mut json = String::new();
+    write_canonical_json(value, &mut json);
+    json
+}
+
+fn write_canonical_json(value: &Value, json: &mut String) {
+    match value {
+        Value::Object(object) => {
+            let mut entries = object.iter().collect::<Vec<_>>();
+            entries.sort_by_key(|(key, _)| *key);
+            json.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                json.push_str(&Value::String(key.clone()).to_string());
+                json.push(':');
+                write_canonical_json(value, json);
+            }
+            json.push('}');
+        }
+        Value::Array(array) => {
+            json.push('[');
+            for (i, value) in array.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_canonical_json(value, json);
+            }
+            json.push(']');
+        }
+        value => json.push_str(&value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_resolve_options_from_configuration_and_overrides() {
+        let target = Target {
+            options: Some(r#"{"main": "src/main.ts", "sourceMap": true, "minify": false}"#.into()),
+            configurations: Some(
+                r#"{"production": {"sourceMap": false, "minify": true}, "development": {}}"#.into(),
+            ),
+            ..Default::default()
+        };
+        let overrides = json!({ "minify": false, "__overrides_unparsed__": ["--minify=false"] });
+
+        let options = resolve_task_options(&target, Some("production"), Some(&overrides)).unwrap();
+
+        assert_eq!(
+            canonical_json(&options),
+            r#"{"main":"src/main.ts","minify":false,"sourceMap":false}"#
+        );
+    }
+
+    #[test]
+    fn should_serialize_equal_values_the_same() {
+        let value: Value =
+            serde_json::from_str(r#"{ "b": [1, { "d": null, "c": "\"" }], "a": {} }"#).unwrap();
+        let reordered: Value =
+            serde_json::from_str(r#"{"a":{},"b":[1,{"c":"\"","d":null}]}"#).unwrap();
+
+        assert_eq!(
+            canonical_json(&value),
+            r#"{"a":{},"b":[1,{"c":"\"","d":null}]}"#
+        );
+        assert_eq!(canonical_json(&value), canonical_json(&reordered));
+    }
+}
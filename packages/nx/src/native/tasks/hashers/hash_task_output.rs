@@ -1,20 +1,112 @@
 use std::path::Path;
 use crate::native::cache::expand_outputs::get_files_for_outputs;
 use crate::native::glob::build_glob_set;
-use crate::native::hasher::{hash_array, hash_file};
+use crate::native::hasher::{
+    hash_array_with_algorithm, hash_file_path_with_algorithm, HashAlgorithm,
+};
+use crate::native::types::FileData;
 use anyhow::*;
 use rayon::prelude::*;
 use tracing::trace;
 
-pub fn hash_task_output(workspace_root: &str, glob: &str, outputs: &[String]) -> Result<String> {
+pub fn hash_task_output(
+    workspace_root: &str,
+    glob: &str,
+    outputs: &[String],
+    algorithm: HashAlgorithm,
+) -> Result<String> {
+    let output_files = collect_task_output_files(workspace_root, glob, outputs)?;
+    let hashes = output_files
+        .into_par_iter()
+        .filter_map(|file| {
+            hash_file_path_with_algorithm(algorithm, Path::new(workspace_root).join(file))
+        })
+        .collect::<Vec<_>>();
+    Ok(hash_array_with_algorithm(
+        algorithm,
+        hashes.into_iter().map(Some).collect(),
+    ))
+}
+
+/// Gets the existing files of the task outputs that are matched by the glob
+pub fn collect_task_output_files(
+    workspace_root: &str,
+    glob: &str,
+    outputs: &[String],
+) -> Result<Vec<String>> {
     let now = std::time::Instant::now();
     let output_files = get_files_for_outputs(workspace_root.to_string(), outputs.to_vec())?;
     trace!("get_files_for_outputs: {:?}", now.elapsed());
     let glob = build_glob_set(&[glob])?;
-    let hashes = output_files
-        .into_par_iter()
+    Ok(output_files
+        .into_iter()
         .filter(|file| glob.is_match(file))
-        .filter_map(|file| hash_file(Path::new(workspace_root).join(file).to_str().expect("path contains invalid utf-8").to_owned()))
+        .collect())
+}
+
+/// Hashes task outputs from file hashes that were recorded when the outputs were cached.
+/// `output_files` should be sorted by path so the hash matches `hash_task_output`
+pub fn hash_recorded_task_output(
+    glob: &str,
+    output_files: &[&FileData],
+    algorithm: HashAlgorithm,
+) -> Result<String> {
+    let glob = build_glob_set(&[glob])?;
+    let hashes = output_files
+        .iter()
+        .filter(|file| glob.is_match(&file.file))
+        .map(|file| Some(file.hash.clone()))
         .collect::<Vec<_>>();
-    Ok(hash_array(hashes.into_iter().map(Some).collect()))
+    Ok(hash_array_with_algorithm(algorithm, hashes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native::hasher::hash_file_path;
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+
+    #[test]
+    fn should_hash_recorded_outputs_the_same_as_outputs_on_disk() {
+        let temp = TempDir::new().unwrap();
+        temp.child("dist/libs/lib1/index.js")
+            .write_str("content")
+            .unwrap();
+        temp.child("dist/libs/lib1/index.d.ts")
+            .write_str("content2")
+            .unwrap();
+        temp.child("dist/libs/lib1/package.json")
+            .write_str("{}")
+            .unwrap();
+
+        let recorded_files = ["index.d.ts", "index.js", "package.json"]
+            .iter()
+            .map(|file| {
+                let file = format!("dist/libs/lib1/{}", file);
+                FileData {
+                    hash: hash_file_path(temp.join(&file)).unwrap(),
+                    file,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let workspace_root = temp.display().to_string();
+        let outputs = vec!["dist/libs/lib1".to_string()];
+        assert_eq!(
+            hash_recorded_task_output(
+                "**/*.{js,ts}",
+                &recorded_files.iter().collect::<Vec<_>>(),
+                HashAlgorithm::default()
+            )
+            .unwrap(),
+            hash_task_output(
+                &workspace_root,
+                "**/*.{js,ts}",
+                &outputs,
+                HashAlgorithm::default()
+            )
+            .unwrap()
+        );
+    }
 }
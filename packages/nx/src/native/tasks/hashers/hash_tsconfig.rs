@@ -1,19 +1,279 @@
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
 
 use anyhow::*;
+use dashmap::DashMap;
+use serde_json::{Map, Value};
+use tracing::trace;
 
-use crate::native::hasher::hash;
+use crate::native::hasher::{hash_with_algorithm, HashAlgorithm};
 use crate::native::project_graph::utils::find_project_for_path;
+use crate::native::tasks::hashers::{canonical_json, get_json_value};
+
+/// The hash of the tsconfig files of a project
+#[derive(Debug, Clone)]
+pub struct TsConfigHash {
+    pub hash: String,
+    /// The tsconfig files that were read for the hash, including the files that do not exist,
+    /// so that the hash can be dropped when any of them changes
+    pub files: Vec<String>,
+}
+
+/// Hashes the compiler options that a project is compiled with. These are the options of the project's
+/// tsconfig.json and the tsconfig files that it extends, and of the tsconfig files in the project that it references.
+/// Paths that point to other projects are not included.
+/// Projects without a tsconfig.json are hashed with the root tsconfig instead.
+#[allow(clippy::too_many_arguments)]
+pub fn hash_project_tsconfig(
+    workspace_root: &str,
+    project_name: &str,
+    project_root: &str,
+    ts_config: &[u8],
+    ts_config_paths: &HashMap<String, Vec<String>>,
+    project_root_mappings: &HashMap<String, String>,
+    cache: Arc<DashMap<String, TsConfigHash>>,
+    algorithm: HashAlgorithm,
+) -> Result<String> {
+    if let Some(cached_hash) = cache.get(project_name) {
+        return Ok(cached_hash.hash.clone());
+    }
+
+    let project_tsconfig = join_path(project_root, "tsconfig.json");
+    let mut configs = Map::new();
+    let mut external_references = vec![];
+    let mut read_files = vec![];
+    collect_project_tsconfigs(
+        workspace_root,
+        project_root,
+        &project_tsconfig,
+        &mut configs,
+        &mut external_references,
+        &mut read_files,
+    )?;
+
+    let hash = if configs.is_empty() {
+        trace!("{project_tsconfig} does not exist, hashing the root tsconfig");
+        hash_tsconfig_selectively(
+            project_name,
+            ts_config,
+            ts_config_paths,
+            project_root_mappings,
+            algorithm,
+        )?
+    } else {
+        for config in configs.values_mut() {
+            let Value::Object(compiler_options) = config else {
+                continue;
+            };
+            if let Some(Value::Object(paths)) = compiler_options.remove("paths") {
+                let paths = paths
+                    .into_iter()
+                    .map(|(key, files)| {
+                        let files = files
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|file| file.as_str().map(String::from))
+                            .collect();
+                        (key, files)
+                    })
+                    .collect();
+                compiler_options.insert(
+                    "paths".to_string(),
+                    Value::String(remove_other_project_paths(
+                        project_name,
+                        project_root_mappings,
+                        &paths,
+                    )),
+                );
+            }
+        }
+        external_references.sort();
+        external_references.dedup();
+        let tsconfig = canonical_json(&Value::Object(configs));
+        hash_with_algorithm(
+            algorithm,
+            &[
+                tsconfig.as_bytes(),
+                external_references.join(";").as_bytes(),
+            ]
+            .concat(),
+        )
+    };
+
+    read_files.sort();
+    read_files.dedup();
+    cache.insert(
+        project_name.to_string(),
+        TsConfigHash {
+            hash: hash.clone(),
+            files: read_files,
+        },
+    );
+    Ok(hash)
+}
+
+/// Collects the effective compiler options of a tsconfig file and of the tsconfig files in the project that it references.
+/// References to tsconfig files outside of the project are only collected as paths.
+fn collect_project_tsconfigs(
+    workspace_root: &str,
+    project_root: &str,
+    file: &str,
+    configs: &mut Map<String, Value>,
+    external_references: &mut Vec<String>,
+    read_files: &mut Vec<String>,
+) -> Result<()> {
+    if configs.contains_key(file) {
+        return Ok(());
+    }
+    let Some(tsconfig) = read_tsconfig(workspace_root, file, &mut vec![], read_files)? else {
+        return Ok(());
+    };
+    configs.insert(file.to_string(), Value::Object(tsconfig.compiler_options));
+
+    for reference in tsconfig.references {
+        if is_in_project(&reference, project_root) {
+            collect_project_tsconfigs(
+                workspace_root,
+                project_root,
+                &reference,
+                configs,
+                external_references,
+                read_files,
+            )?;
+        } else {
+            external_references.push(reference);
+        }
+    }
+    Ok(())
+}
+
+/// The compiler options of a tsconfig file, merged with the compiler options of the files that it extends,
+/// and the tsconfig files that it references
+struct TsConfig {
+    compiler_options: Map<String, Value>,
+    references: Vec<String>,
+}
+
+fn read_tsconfig(
+    workspace_root: &str,
+    file: &str,
+    extends_chain: &mut Vec<String>,
+    read_files: &mut Vec<String>,
+) -> Result<Option<TsConfig>> {
+    if extends_chain.iter().any(|extended| extended == file) {
+        bail!(
+            "Circular extends in tsconfig files: {} -> {file}",
+            extends_chain.join(" -> ")
+        );
+    }
+    read_files.push(file.to_string());
+    let Some(tsconfig) = get_json_value(workspace_root, file, "")? else {
+        return Ok(None);
+    };
+    let directory = parent_directory(file);
+
+    let mut compiler_options = Map::new();
+    let extends = match tsconfig.get("extends") {
+        Some(Value::String(extends)) => vec![extends.as_str()],
+        Some(Value::Array(extends)) => extends.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+    extends_chain.push(file.to_string());
+    for extends in extends {
+        let extended_file = resolve_extends(workspace_root, directory, extends);
+        match read_tsconfig(workspace_root, &extended_file, extends_chain, read_files)? {
+            Some(extended) => compiler_options.extend(extended.compiler_options),
+            None => trace!("could not find {extends} extended by {file}"),
+        }
+    }
+    extends_chain.pop();
+
+    if let Some(Value::Object(options)) = tsconfig.get("compilerOptions") {
+        compiler_options.extend(options.clone());
+    }
+
+    let references = tsconfig
+        .get("references")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|reference| reference.get("path").and_then(Value::as_str))
+        .map(|path| {
+            let path = join_path(directory, path);
+            if path.ends_with(".json") {
+                path
+            } else {
+                join_path(&path, "tsconfig.json")
+            }
+        })
+        .collect();
+
+    Ok(Some(TsConfig {
+        compiler_options,
+        references,
+    }))
+}
+
+/// Resolves the file that a tsconfig extends, which is either relative to the tsconfig or in node_modules
+fn resolve_extends(workspace_root: &str, directory: &str, extends: &str) -> String {
+    let file = if extends.starts_with("./") || extends.starts_with("../") {
+        join_path(directory, extends)
+    } else {
+        let package = join_path("node_modules", extends);
+        if Path::new(workspace_root).join(&package).is_dir() {
+            return join_path(&package, "tsconfig.json");
+        }
+        package
+    };
+    if file.ends_with(".json") || Path::new(workspace_root).join(&file).is_file() {
+        file
+    } else {
+        format!("{file}.json")
+    }
+}
+
+/// Joins paths that are relative to the workspace root, resolving `.` and `..`
+fn join_path(directory: &str, path: &str) -> String {
+    let mut segments: Vec<&str> = vec![];
+    for segment in directory.split('/').chain(path.split('/')) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+fn parent_directory(file: &str) -> &str {
+    file.rsplit_once('/').map_or("", |(directory, _)| directory)
+}
+
+fn is_in_project(file: &str, project_root: &str) -> bool {
+    project_root.is_empty()
+        || project_root == "."
+        || file
+            .strip_prefix(project_root)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
 
 pub fn hash_tsconfig_selectively(
     project_name: &str,
     ts_config: &[u8],
     ts_config_paths: &HashMap<String, Vec<String>>,
     project_root_mappings: &HashMap<String, String>,
+    algorithm: HashAlgorithm,
 ) -> Result<String> {
     let project_path =
         remove_other_project_paths(project_name, project_root_mappings, ts_config_paths);
-    Ok(hash(&[project_path.as_bytes(), ts_config].concat()))
+    Ok(hash_with_algorithm(
+        algorithm,
+        &[project_path.as_bytes(), ts_config].concat(),
+    ))
 }
 
 fn remove_other_project_paths(
@@ -108,6 +368,7 @@ mod test {
             tsconfig.as_bytes(),
             &paths,
             &project_root_mappings,
+            HashAlgorithm::default(),
         )
         .unwrap();
         assert_eq!(result, "6431119472521503644");
@@ -116,11 +377,137 @@ mod test {
             tsconfig.as_bytes(),
             &paths,
             &project_root_mappings,
+            HashAlgorithm::default(),
         )
         .unwrap();
         assert_eq!(result, "13103308914505796317");
     }
 
+    #[test]
+    fn should_hash_the_extends_chain_and_references_of_project_tsconfigs() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace_root = temp_dir.path().to_str().unwrap();
+        let project_root_mappings = create_test_project_root_mappings();
+        let write = |file: &str, content: &str| {
+            let path = temp_dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        let write_base = |target: &str, project2_path: &str| {
+            write(
+                "tsconfig.base.json",
+                &format!(
+                    r#"{{
+                        "compilerOptions": {{
+                            "target": "{target}",
+                            "paths": {{
+                                "@test/project1": ["path1/index.ts"],
+                                "@test/project2": ["{project2_path}"]
+                            }}
+                        }}
+                    }}"#
+                ),
+            );
+        };
+        let cache = Arc::new(DashMap::new());
+        let hash_project1 = || {
+            cache.clear();
+            hash_project_tsconfig(
+                workspace_root,
+                "project1",
+                "path1",
+                b"{}",
+                &HashMap::new(),
+                &project_root_mappings,
+                Arc::clone(&cache),
+                HashAlgorithm::default(),
+            )
+            .unwrap()
+        };
+
+        write_base("ES2021", "packages/path2/index.ts");
+        write(
+            "path1/tsconfig.json",
+            r#"{
+                // solution tsconfig
+                "extends": "../tsconfig.base.json",
+                "files": [],
+                "references": [{ "path": "./tsconfig.lib.json" }, { "path": "../packages/path2" }],
+            }"#,
+        );
+        write(
+            "path1/tsconfig.lib.json",
+            r#"{ "extends": "./tsconfig.json", "compilerOptions": { "outDir": "../dist/path1" } }"#,
+        );
+        let hash = hash_project1();
+        assert_eq!(
+            cache.get("project1").unwrap().files,
+            vec![
+                "path1/tsconfig.json",
+                "path1/tsconfig.lib.json",
+                "tsconfig.base.json"
+            ]
+        );
+
+        write_base("ES2021", "packages/path2/src/index.ts");
+        assert_eq!(hash_project1(), hash);
+
+        write_base("ES2022", "packages/path2/index.ts");
+        let changed_target = hash_project1();
+        assert_ne!(changed_target, hash);
+
+        write(
+            "path1/tsconfig.lib.json",
+            r#"{ "extends": "./tsconfig.json", "compilerOptions": { "outDir": "../dist/path1-lib" } }"#,
+        );
+        assert_ne!(hash_project1(), changed_target);
+    }
+
+    #[test]
+    fn should_hash_the_root_tsconfig_for_projects_without_a_tsconfig() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let project_root_mappings = create_test_project_root_mappings();
+        let paths = HashMap::from([("@test/project1".into(), vec!["path1/index.ts".into()])]);
+
+        let result = hash_project_tsconfig(
+            temp_dir.path().to_str().unwrap(),
+            "project1",
+            "path1",
+            b"{}",
+            &paths,
+            &project_root_mappings,
+            Arc::new(DashMap::new()),
+            HashAlgorithm::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            hash_tsconfig_selectively(
+                "project1",
+                b"{}",
+                &paths,
+                &project_root_mappings,
+                HashAlgorithm::default()
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_join_workspace_paths() {
+        assert_eq!(
+            join_path("libs/a", "../../tsconfig.base.json"),
+            "tsconfig.base.json"
+        );
+        assert_eq!(
+            join_path("libs/a", "./tsconfig.lib.json"),
+            "libs/a/tsconfig.lib.json"
+        );
+        assert_eq!(join_path("", "tsconfig.json"), "tsconfig.json");
+        assert_eq!(join_path(".", "tsconfig.json"), "tsconfig.json");
+    }
+
     fn create_test_project_root_mappings() -> HashMap<String, String> {
         create_project_root_mappings(&HashMap::from([
             (
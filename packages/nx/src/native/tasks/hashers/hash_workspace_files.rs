@@ -5,38 +5,21 @@ use dashmap::DashMap;
 use tracing::{debug, debug_span, trace, warn};
 
 use crate::native::types::FileData;
-use crate::native::{glob::build_glob_set, hasher::hash};
+use crate::native::{
+    glob::build_glob_set,
+    hasher::{hash_with_algorithm, HashAlgorithm, Hasher},
+};
 
 pub fn hash_workspace_files(
     workspace_file_sets: &[String],
     all_workspace_files: &[FileData],
     cache: Arc<DashMap<String, String>>,
+    algorithm: HashAlgorithm,
 ) -> Result<String> {
-    let globs: Vec<String> = workspace_file_sets
-        .iter()
-        .inspect(|&x| trace!("Workspace file set: {}", x))
-        .filter_map(|x| {
-            let is_negative = x.starts_with("!");
-            let x = if is_negative { &x[1..] } else { x };
-            let fileset: Option<&str> = x.strip_prefix("{workspaceRoot}/");
-            if let Some(fileset) = fileset {
-                if is_negative {
-                    Some(format!("!{}", fileset))
-                } else {
-                    Some(fileset.to_string())
-                }
-            } else {
-                warn!(
-                    "{x} does not start with {}. This will throw an error in Nx 20.",
-                    "{workspaceRoot}/"
-                );
-                None
-            }
-        })
-        .collect();
+    let globs = get_workspace_globs(workspace_file_sets);
 
     if globs.is_empty() {
-        return Ok(hash(b""));
+        return Ok(hash_with_algorithm(algorithm, b""));
     }
 
     let cache_key = globs.join(",");
@@ -46,7 +29,7 @@ pub fn hash_workspace_files(
 
     let glob = build_glob_set(&globs)?;
 
-    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+    let mut hasher = Hasher::with_algorithm(algorithm);
     debug_span!("Hashing workspace fileset", cache_key).in_scope(|| {
         for file in all_workspace_files
             .iter()
@@ -56,7 +39,7 @@ pub fn hash_workspace_files(
             hasher.update(file.file.clone().as_bytes());
             hasher.update(file.hash.clone().as_bytes());
         }
-        let hashed_value = hasher.digest().to_string();
+        let hashed_value = hasher.digest();
         debug!("Hash Value: {:?}", hashed_value);
 
         cache.insert(cache_key.to_string(), hashed_value.clone());
@@ -64,6 +47,50 @@ pub fn hash_workspace_files(
     })
 }
 
+/// Gets the workspace files that are matched by the given file sets, in the order they are hashed
+pub fn collect_workspace_files<'a>(
+    workspace_file_sets: &[String],
+    all_workspace_files: &'a [FileData],
+) -> Result<Vec<&'a FileData>> {
+    let globs = get_workspace_globs(workspace_file_sets);
+
+    if globs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let glob = build_glob_set(&globs)?;
+    Ok(all_workspace_files
+        .iter()
+        .filter(|file| glob.is_match(&file.file))
+        .collect())
+}
+
+/// Gets the globs of the `{workspaceRoot}` file sets, relative to the workspace root
+pub fn get_workspace_globs(workspace_file_sets: &[String]) -> Vec<String> {
+    workspace_file_sets
+        .iter()
+        .inspect(|&x| trace!("Workspace file set: {}", x))
+        .filter_map(|x| {
+            let is_negative = x.starts_with("!");
+            let x = if is_negative { &x[1..] } else { x };
+            let fileset: Option<&str> = x.strip_prefix("{workspaceRoot}/");
+            if let Some(fileset) = fileset {
+                if is_negative {
+                    Some(format!("!{}", fileset))
+                } else {
+                    Some(fileset.to_string())
+                }
+            } else {
+                warn!(
+                    "{x} does not start with {}. This will throw an error in Nx 20.",
+                    "{workspaceRoot}/"
+                );
+                None
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use crate::native::hasher::hash;
@@ -78,6 +105,7 @@ mod test {
             &["packages/{package}".to_string()],
             &[],
             Arc::new(DashMap::new()),
+            HashAlgorithm::default(),
         )
         .unwrap();
         assert_eq!(result, hash(b""));
@@ -110,11 +138,48 @@ mod test {
                 project_file.clone(),
             ],
             Arc::new(DashMap::new()),
+            HashAlgorithm::default(),
         )
         .unwrap();
         assert_eq!(result, "15841935230129999746");
     }
 
+    #[test]
+    fn test_collect_workspace_files() {
+        let gitignore_file = FileData {
+            file: ".gitignore".into(),
+            hash: "123".into(),
+        };
+        let package_json_file = FileData {
+            file: "package.json".into(),
+            hash: "789".into(),
+        };
+        let project_file = FileData {
+            file: "packages/project/project.json".into(),
+            hash: "abc".into(),
+        };
+        let all_workspace_files = [
+            gitignore_file.clone(),
+            package_json_file.clone(),
+            project_file.clone(),
+        ];
+
+        let result = collect_workspace_files(
+            &[
+                "{workspaceRoot}/**/*.json".to_string(),
+                "!{workspaceRoot}/packages/**".to_string(),
+            ],
+            &all_workspace_files,
+        )
+        .unwrap();
+        assert_eq!(result, vec![&package_json_file]);
+
+        let result =
+            collect_workspace_files(&["packages/{package}".to_string()], &all_workspace_files)
+                .unwrap();
+        assert!(result.is_empty());
+    }
+
     #[test]
     fn test_hash_workspace_files_is_deterministic() {
         let gitignore_file = FileData {
@@ -144,6 +209,7 @@ mod test {
                     project_file.clone(),
                 ],
                 Arc::new(DashMap::new()),
+                HashAlgorithm::default(),
             )
             .unwrap();
             assert_eq!(result, "13759877301064854697");
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use napi::bindgen_prelude::{Buffer, External};
+use parking_lot::RwLock;
+use tracing::trace;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::native::db::connection::NxDbConnection;
+use crate::native::project_graph::types::ProjectGraph;
+use crate::native::tasks::task_hasher::{HashDetails, HasherOptions, ResolvedInputs, TaskHasher};
+use crate::native::tasks::types::HashInstruction;
+use crate::native::types::NapiDashMap;
+use crate::native::workspace::context::WorkspaceContext;
+use crate::native::workspace::types::ProjectFiles;
+
+/// A long-lived task hasher which keeps its files up to date as they change.
+/// Only the hashes of the inputs that depend on the changed files are recomputed,
+/// so that tasks can be rehashed without rehashing the whole workspace.
+#[napi]
+pub struct HashingService {
+    hasher: RwLock<TaskHasher>,
+}
+
+#[napi]
+impl HashingService {
+    /// The service starts with the files of the workspace context, and is kept up to date with `updateFiles`.
+    /// Tasks are hashed with the hash algorithm of the workspace context,
+    /// once the project graph is set with `updateProjectGraph`.
+    #[napi(constructor)]
+    pub fn new(context: &WorkspaceContext, options: Option<HasherOptions>) -> Self {
+        let options = HasherOptions {
+            selectively_hash_ts_config: options.is_some_and(|o| o.selectively_hash_ts_config),
+            hash_algorithm: Some(context.hash_algorithm()),
+        };
+        let project_graph = ProjectGraph {
+            nodes: HashMap::new(),
+            dependencies: HashMap::new(),
+            external_nodes: HashMap::new(),
+        };
+        let hasher = TaskHasher::with_owned_files(
+            context.workspace_root.clone(),
+            External::new(project_graph),
+            ProjectFiles::new(),
+            context.all_file_data(),
+            vec![],
+            HashMap::new(),
+            Some(options),
+        );
+
+        Self {
+            hasher: RwLock::new(hasher),
+        }
+    }
+
+    /// Replaces the project graph, the files of the projects and the root tsconfig after the project graph
+    /// was recomputed. Only the hashes that depend on what changed are recomputed.
+    #[napi]
+    pub fn update_project_graph(
+        &self,
+        project_graph: External<ProjectGraph>,
+        project_files: External<ProjectFiles>,
+        ts_config: Buffer,
+        ts_config_paths: HashMap<String, Vec<String>>,
+    ) -> anyhow::Result<()> {
+        self.hasher.write().update_project_graph(
+            project_graph,
+            (*project_files).clone(),
+            ts_config.to_vec(),
+            ts_config_paths,
+        )
+    }
+
+    /// Updates the files with the file hashes that the workspace context was updated with,
+    /// so that the changed files are not hashed again
+    #[napi]
+    pub fn update_files(
+        &self,
+        updated_files: HashMap<String, String>,
+        deleted_files: Vec<&str>,
+    ) -> anyhow::Result<()> {
+        trace!(
+            "updating {} files and deleting {} files",
+            updated_files.len(),
+            deleted_files.len()
+        );
+        self.hasher
+            .write()
+            .update_files(&updated_files, &deleted_files)
+    }
+
+    /// Replaces the root tsconfig after it changed
+    #[napi]
+    pub fn update_root_ts_config(
+        &self,
+        ts_config: Buffer,
+        ts_config_paths: HashMap<String, Vec<String>>,
+    ) {
+        self.hasher
+            .write()
+            .update_root_ts_config(ts_config.to_vec(), ts_config_paths);
+    }
+
+    #[napi]
+    pub fn hash_plans(
+        &self,
+        hash_plans: External<HashMap<String, Vec<HashInstruction>>>,
+        js_env: HashMap<String, String>,
+    ) -> anyhow::Result<NapiDashMap<String, HashDetails>> {
+        self.hasher.read().hash_plans(hash_plans, js_env)
+    }
+
+    #[napi]
+    pub fn resolve_inputs(
+        &self,
+        task_id: String,
+        hash_plans: External<HashMap<String, Vec<HashInstruction>>>,
+        js_env: HashMap<String, String>,
+    ) -> anyhow::Result<ResolvedInputs> {
+        self.hasher
+            .read()
+            .resolve_inputs(task_id, hash_plans, js_env)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[napi]
+    pub fn use_recorded_task_outputs(&self, db: External<NxDbConnection>) {
+        self.hasher.write().use_recorded_task_outputs(db);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native::project_graph::types::Project;
+    use crate::native::types::FileData;
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+
+    fn hash_of(service: &HashingService, instruction: HashInstruction) -> String {
+        service
+            .hash_plans(
+                External::new(HashMap::from([(
+                    "app:build".to_string(),
+                    vec![instruction],
+                )])),
+                HashMap::new(),
+            )
+            .unwrap()
+            .remove("app:build")
+            .unwrap()
+            .1
+            .value
+    }
+
+    #[test]
+    fn should_only_rehash_the_inputs_of_changed_files() {
+        let temp = TempDir::new().unwrap();
+        temp.child("apps/app/main.ts").write_str("app").unwrap();
+        temp.child("libs/lib/index.ts").write_str("lib").unwrap();
+        temp.child("package.json").write_str("{}").unwrap();
+
+        let workspace_root = temp.display().to_string();
+        let context = WorkspaceContext::new(
+            workspace_root,
+            temp.child(".nx/workspace-data").display().to_string(),
+            None,
+        );
+        let project_graph = ProjectGraph {
+            nodes: HashMap::from([
+                (
+                    "app".to_string(),
+                    Project {
+                        root: "apps/app".into(),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "lib".to_string(),
+                    Project {
+                        root: "libs/lib".into(),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            dependencies: HashMap::new(),
+            external_nodes: HashMap::new(),
+        };
+        let files = context.all_file_data();
+        let project_files: ProjectFiles = ["app", "lib"]
+            .into_iter()
+            .map(|project| {
+                let root = project_graph.nodes[project].root.clone();
+                let files = files
+                    .iter()
+                    .filter(|f| f.file.starts_with(&root))
+                    .cloned()
+                    .collect::<Vec<FileData>>();
+                (project.to_string(), files)
+            })
+            .collect();
+
+        let service = HashingService::new(&context, None);
+        service
+            .hasher
+            .write()
+            .update_project_graph(
+                External::new(project_graph),
+                project_files,
+                vec![],
+                HashMap::new(),
+            )
+            .unwrap();
+
+        let app_files =
+            HashInstruction::ProjectFileSet("app".into(), vec!["{projectRoot}/**/*".into()]);
+        let lib_files =
+            HashInstruction::ProjectFileSet("lib".into(), vec!["{projectRoot}/**/*".into()]);
+        let package_json =
+            HashInstruction::WorkspaceFileSet(vec!["{workspaceRoot}/package.json".into()]);
+        let app_hash = hash_of(&service, app_files.clone());
+        let lib_hash = hash_of(&service, lib_files.clone());
+        let package_json_hash = hash_of(&service, package_json.clone());
+
+        temp.child("libs/lib/index.ts")
+            .write_str("changed")
+            .unwrap();
+        let updated_files = context.incremental_update(vec!["libs/lib/index.ts"], vec![]);
+        service.update_files(updated_files, vec![]).unwrap();
+
+        assert_eq!(hash_of(&service, app_files.clone()), app_hash);
+        assert_ne!(hash_of(&service, lib_files.clone()), lib_hash);
+        assert_eq!(hash_of(&service, package_json.clone()), package_json_hash);
+
+        let lib_hash = hash_of(&service, lib_files.clone());
+        std::fs::remove_dir_all(temp.child("apps/app")).unwrap();
+        context.incremental_update(vec![], vec!["apps/app"]);
+        service
+            .update_files(HashMap::new(), vec!["apps/app"])
+            .unwrap();
+
+        assert_ne!(hash_of(&service, app_files), app_hash);
+        assert_eq!(hash_of(&service, lib_files), lib_hash);
+        assert!(!context
+            .all_file_data()
+            .iter()
+            .any(|f| f.file.starts_with("apps/app")));
+    }
+}
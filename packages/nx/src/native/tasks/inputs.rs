@@ -1,6 +1,7 @@
 use crate::native::project_graph::types::{Project, ProjectGraph};
 use crate::native::tasks::types::Task;
-use crate::native::types::{Input, NxJson};
+use crate::native::types::{Input, InputsInput, JsInputs, NxJson};
+use napi::bindgen_prelude::Either8;
 use std::collections::HashMap;
 
 #[derive(Debug)]
@@ -33,11 +34,12 @@ pub(super) fn get_inputs<'a>(
             task.target.target
         ))?;
 
-    let named_inputs = get_named_inputs(nx_json, project_node);
+    let configuration = task.target.configuration.as_deref();
+    let named_inputs = get_named_inputs(nx_json, project_node, configuration);
     let inputs: Option<Vec<Input>> = target_data
         .inputs
         .as_ref()
-        .map(|i| i.iter().map(|v| v.into()).collect());
+        .map(|i| resolve_configuration_inputs(i, configuration));
 
     split_inputs_into_self_and_deps(inputs, named_inputs)
 }
@@ -46,12 +48,13 @@ pub(super) fn get_inputs_for_dependency<'a>(
     project: &'a Project,
     nx_json: &'a NxJson,
     named_input: &'a Input,
+    configuration: Option<&str>,
 ) -> anyhow::Result<Option<SplitInputs<'a>>> {
     let Input::Inputs { input, .. } = named_input else {
         return Ok(None);
     };
 
-    let inputs = get_named_inputs(nx_json, project);
+    let inputs = get_named_inputs(nx_json, project, configuration);
     let (self_inputs, deps_outputs): (Vec<Input>, Vec<Input>) = expand_named_input(input, &inputs)?
         .into_iter()
         .partition(|i| !(matches!(i, Input::DepsOutputs { .. })));
@@ -104,6 +107,7 @@ fn split_inputs_into_self_and_deps<'a>(
                 | Input::FileSet(_)
                 | Input::Runtime(_)
                 | Input::Environment(_)
+                | Input::Json(_)
                 | Input::DepsOutputs { .. }
                 | Input::ExternalDependency(_) => {
                     acc.1.push(input);
@@ -136,6 +140,7 @@ pub(super) fn expand_single_project_inputs<'a>(
     named_inputs: &HashMap<&str, Vec<Input<'a>>>,
 ) -> anyhow::Result<Vec<Input<'a>>> {
     let mut expanded = vec![];
+    let mut excluded = vec![];
 
     for i in inputs {
         match i {
@@ -144,7 +149,12 @@ pub(super) fn expand_single_project_inputs<'a>(
                     anyhow::bail!("namedInputs definitions cannot start with ^");
                 }
 
-                if named_inputs.get(s).is_some() {
+                if let Some(excluded_input) = s
+                    .strip_prefix('!')
+                    .filter(|input| named_inputs.contains_key(input))
+                {
+                    excluded.extend(expand_named_input(excluded_input, named_inputs)?);
+                } else if named_inputs.get(s).is_some() {
                     expanded.extend(expand_named_input(s, named_inputs)?);
                 } else {
                     validate_file_set(s)?;
@@ -161,6 +171,10 @@ pub(super) fn expand_single_project_inputs<'a>(
             }
             Input::Runtime(runtime) => expanded.push(Input::Runtime(runtime)),
             Input::Environment(env) => expanded.push(Input::Environment(env)),
+            Input::Json(json) => {
+                split_json_input(json)?;
+                expanded.push(Input::Json(json));
+            }
             Input::ExternalDependency(external) => {
                 expanded.push(Input::ExternalDependency(external))
             }
@@ -182,6 +196,13 @@ pub(super) fn expand_single_project_inputs<'a>(
         }
     }
 
+    // `!namedInput` removes the inputs of a named input, wherever they are in the list.
+    // Negated filesets are kept as they are: they exclude their files from every fileset of the list,
+    // so `["default", "!{projectRoot}/**/*.md"]` is `default` without the markdown files
+    if !excluded.is_empty() {
+        expanded.retain(|input| !excluded.contains(input));
+    }
+
     Ok(expanded)
 }
 
@@ -206,6 +227,26 @@ If "{file_set}" is a named input, make sure it is defined in nx.json.
     }
 }
 
+/// Splits a json input such as `{workspaceRoot}/package.json#/dependencies/react`
+/// into the file and the JSON pointer
+pub(super) fn split_json_input(json: &str) -> anyhow::Result<(&str, &str)> {
+    match json.split_once('#') {
+        Some((file, pointer))
+            if (file.starts_with("{workspaceRoot}/") || file.starts_with("{projectRoot}/"))
+                && (pointer.is_empty() || pointer.starts_with('/')) =>
+        {
+            Ok((file, pointer))
+        }
+        _ => anyhow::bail!(
+            r#""{json}" is an invalid json input.
+Json inputs have to be a file in {workspaceRoot} or {projectRoot} followed by a JSON pointer.
+For instance: "{workspaceRoot}/package.json#/dependencies/react"."#,
+            workspaceRoot = "{workspaceRoot}",
+            projectRoot = "{projectRoot}",
+        ),
+    }
+}
+
 pub(super) fn expand_named_input<'a>(
     input: &str,
     named_inputs: &HashMap<&str, Vec<Input<'a>>>,
@@ -220,6 +261,7 @@ pub(super) fn expand_named_input<'a>(
 pub(super) fn get_named_inputs<'a>(
     nx_json: &'a NxJson,
     project: &'a Project,
+    configuration: Option<&str>,
 ) -> HashMap<&'a str, Vec<Input<'a>>> {
     let mut collected_named_inputs: HashMap<&str, Vec<Input>> = HashMap::new();
 
@@ -228,9 +270,31 @@ pub(super) fn get_named_inputs<'a>(
     let iterable_structs = [&nx_json.named_inputs, &project.named_inputs];
     for named_inputs in iterable_structs.into_iter().flatten() {
         for (key, val) in named_inputs.iter() {
-            collected_named_inputs.insert(key.as_ref(), val.iter().map(|v| v.into()).collect());
+            collected_named_inputs.insert(
+                key.as_ref(),
+                resolve_configuration_inputs(val, configuration),
+            );
         }
     }
 
     collected_named_inputs
 }
+
+/// Drops the inputs that are only used with other configurations than the configuration of the task
+fn resolve_configuration_inputs<'a>(
+    inputs: &'a [JsInputs],
+    configuration: Option<&str>,
+) -> Vec<Input<'a>> {
+    inputs
+        .iter()
+        .filter(|input| match input {
+            Either8::A(InputsInput {
+                configurations: Some(configurations),
+                ..
+            }) => configuration
+                .is_some_and(|configuration| configurations.iter().any(|c| c == configuration)),
+            _ => true,
+        })
+        .map(|input| input.into())
+        .collect()
+}
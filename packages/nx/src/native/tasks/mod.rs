@@ -1,12 +1,17 @@
 mod dep_outputs;
 mod hash_planner;
+mod hash_plans_archive;
 pub mod hashers;
 mod inputs;
+pub mod task_graph;
 pub mod task_hasher;
+pub mod task_scheduler;
 pub mod types;
 mod utils;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub mod details;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod hashing_service;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod task_history;
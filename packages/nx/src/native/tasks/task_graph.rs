@@ -0,0 +1,1071 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, bail};
+use napi::bindgen_prelude::External;
+use napi::Either;
+use serde_json::{json, Value};
+use tracing::{trace, warn};
+
+use crate::native::glob::build_glob_set;
+use crate::native::project_graph::types::{
+    JsTargetDependency, Project, ProjectGraph, Target, TargetDependencyConfig,
+};
+use crate::native::tasks::dep_outputs::TaskGraphCycleError;
+use crate::native::tasks::hashers::resolve_task_options;
+use crate::native::tasks::types::{Task, TaskGraph, TaskTarget};
+use crate::native::utils::find_matching_projects;
+
+const DUMMY_TASK_TARGET: &str = "__nx_dummy_task__";
+const GLOB_CHARACTERS: [char; 7] = ['*', '|', '{', '}', '(', ')', '['];
+
+#[napi(object)]
+#[derive(Default)]
+pub struct TaskGraphOptions {
+    pub configuration: Option<String>,
+    pub overrides: Option<serde_json::Value>,
+    /// Only creates the tasks of the projects and targets, without the tasks that they depend on
+    pub exclude_task_dependencies: Option<bool>,
+    /// Creates the task graph even if the tasks depend on each other circularly
+    pub ignore_cycles: Option<bool>,
+}
+
+/// Creates the tasks for the targets of the projects, and the tasks that they depend on through `dependsOn`.
+/// `target_dependencies` are the `dependsOn` of the target defaults, for targets that do not define their own.
+#[napi]
+pub fn create_task_graph(
+    project_graph: External<ProjectGraph>,
+    target_dependencies: HashMap<String, Vec<JsTargetDependency>>,
+    project_names: Vec<String>,
+    targets: Vec<String>,
+    options: Option<TaskGraphOptions>,
+) -> anyhow::Result<TaskGraph> {
+    build_task_graph(
+        &project_graph,
+        &target_dependencies,
+        &project_names,
+        &targets,
+        &options.unwrap_or_default(),
+    )
+}
+
+fn build_task_graph(
+    project_graph: &ProjectGraph,
+    target_dependencies: &HashMap<String, Vec<JsTargetDependency>>,
+    project_names: &[String],
+    targets: &[String],
+    options: &TaskGraphOptions,
+) -> anyhow::Result<TaskGraph> {
+    let mut process_tasks = ProcessTasks::new(
+        project_graph,
+        target_dependencies,
+        options.configuration.as_deref(),
+        options.overrides.as_ref(),
+    );
+    let roots = process_tasks.process_tasks(
+        project_names,
+        targets,
+        options.exclude_task_dependencies.unwrap_or(false),
+    )?;
+
+    if !options.ignore_cycles.unwrap_or(false) {
+        if let Some(path) = find_cycle(&process_tasks.dependencies) {
+            return Err(TaskGraphCycleError { path }.into());
+        }
+    }
+
+    Ok(TaskGraph {
+        roots,
+        tasks: process_tasks.tasks,
+        dependencies: process_tasks.dependencies,
+    })
+}
+
+/// A `dependsOn` entry, with its projects resolved and its target expanded
+struct DependencyConfig {
+    target: String,
+    projects: Option<Vec<String>>,
+    dependencies: bool,
+    forward_params: bool,
+}
+
+/// The task that dependencies are processed for.
+/// Dummy tasks stand in for the targets that dependencies of a project do not have.
+struct ProcessedTask<'t> {
+    id: &'t str,
+    project: &'t str,
+    target: &'t str,
+}
+
+struct ProcessTasks<'a> {
+    project_graph: &'a ProjectGraph,
+    target_dependencies: &'a HashMap<String, Vec<JsTargetDependency>>,
+    all_target_names: Vec<&'a str>,
+    configuration: Option<&'a str>,
+    overrides: Option<&'a Value>,
+    seen: HashSet<String>,
+    tasks: HashMap<String, Task>,
+    dependencies: HashMap<String, Vec<String>>,
+}
+
+impl<'a> ProcessTasks<'a> {
+    fn new(
+        project_graph: &'a ProjectGraph,
+        target_dependencies: &'a HashMap<String, Vec<JsTargetDependency>>,
+        configuration: Option<&'a str>,
+        overrides: Option<&'a Value>,
+    ) -> Self {
+        let mut all_target_names = project_graph
+            .nodes
+            .values()
+            .flat_map(|project| project.targets.keys().map(String::as_str))
+            .collect::<Vec<_>>();
+        all_target_names.sort();
+        all_target_names.dedup();
+
+        Self {
+            project_graph,
+            target_dependencies,
+            all_target_names,
+            configuration,
+            overrides,
+            seen: HashSet::new(),
+            tasks: HashMap::new(),
+            dependencies: HashMap::new(),
+        }
+    }
+
+    fn process_tasks(
+        &mut self,
+        project_names: &[String],
+        targets: &[String],
+        exclude_task_dependencies: bool,
+    ) -> anyhow::Result<Vec<String>> {
+        for project_name in project_names {
+            let project = self.project(project_name)?;
+            for target in targets {
+                if targets.len() == 1 || project.targets.contains_key(target) {
+                    let configuration = resolve_configuration(project, target, self.configuration);
+                    let id = get_id(project_name, target, configuration.as_deref());
+                    let task = self.create_task(
+                        &id,
+                        project_name,
+                        target,
+                        configuration,
+                        self.overrides.cloned(),
+                    )?;
+                    self.tasks.insert(id.clone(), task);
+                    self.dependencies.insert(id, vec![]);
+                }
+            }
+        }
+
+        let initial_tasks = self.tasks.keys().cloned().collect::<HashSet<_>>();
+        let mut initial_task_ids = initial_tasks.iter().collect::<Vec<_>>();
+        initial_task_ids.sort();
+        for id in initial_task_ids {
+            let task = &self.tasks[id];
+            let (project, target) = (task.target.project.clone(), task.target.target.clone());
+            self.process_task(
+                &ProcessedTask {
+                    id,
+                    project: &project,
+                    target: &target,
+                },
+                &project,
+            )?;
+        }
+
+        if exclude_task_dependencies {
+            self.tasks.retain(|id, _| initial_tasks.contains(id));
+            self.dependencies.retain(|id, _| initial_tasks.contains(id));
+            for dependencies in self.dependencies.values_mut() {
+                dependencies.retain(|id| initial_tasks.contains(id));
+            }
+        }
+
+        filter_dummy_tasks(&mut self.dependencies);
+
+        for (id, dependencies) in self.dependencies.iter_mut() {
+            let mut seen = HashSet::new();
+            dependencies.retain(|dependency| dependency != id && seen.insert(dependency.clone()));
+        }
+
+        let mut roots = self
+            .dependencies
+            .iter()
+            .filter(|(_, dependencies)| dependencies.is_empty())
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        roots.sort();
+        Ok(roots)
+    }
+
+    fn process_task(
+        &mut self,
+        task: &ProcessedTask,
+        project_used_to_derive_dependencies: &str,
+    ) -> anyhow::Result<()> {
+        if !self
+            .seen
+            .insert(format!("{}-{project_used_to_derive_dependencies}", task.id))
+        {
+            return Ok(());
+        }
+
+        for dependency_config in self.get_dependency_configs(task.project, task.target)? {
+            let overrides = if dependency_config.forward_params {
+                self.overrides.cloned()
+            } else {
+                Some(json!({ "__overrides_unparsed__": [] }))
+            };
+
+            if let Some(projects) = &dependency_config.projects {
+                if projects.is_empty() {
+                    warn!(
+                        "`dependsOn` is misconfigured for {}:{}, the project patterns do not match any projects",
+                        task.project, task.target
+                    );
+                }
+                for project_name in projects {
+                    self.process_tasks_for_single_project(
+                        task,
+                        project_name,
+                        &dependency_config.target,
+                        &overrides,
+                    )?;
+                }
+            } else if dependency_config.dependencies {
+                self.process_tasks_for_dependencies(
+                    task,
+                    project_used_to_derive_dependencies,
+                    &dependency_config.target,
+                    &overrides,
+                )?;
+            } else {
+                self.process_tasks_for_single_project(
+                    task,
+                    task.project,
+                    &dependency_config.target,
+                    &overrides,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn process_tasks_for_single_project(
+        &mut self,
+        task: &ProcessedTask,
+        project_name: &str,
+        target: &str,
+        overrides: &Option<Value>,
+    ) -> anyhow::Result<()> {
+        let project = self.project(project_name)?;
+        if project.targets.contains_key(target) {
+            self.add_dependency(task, project_name, project, target, overrides)?;
+        }
+        Ok(())
+    }
+
+    fn process_tasks_for_dependencies(
+        &mut self,
+        task: &ProcessedTask,
+        project_used_to_derive_dependencies: &str,
+        target: &str,
+        overrides: &Option<Value>,
+    ) -> anyhow::Result<()> {
+        let project_graph = self.project_graph;
+        let Some(dependencies) = project_graph
+            .dependencies
+            .get(project_used_to_derive_dependencies)
+        else {
+            return Ok(());
+        };
+
+        for dependency in dependencies {
+            // external nodes do not have tasks
+            let Some(dependency_project) = project_graph.nodes.get(dependency) else {
+                continue;
+            };
+
+            if dependency_project.targets.contains_key(target) {
+                self.add_dependency(task, dependency, dependency_project, target, overrides)?;
+            } else {
+                // the dummy task continues through the dependencies of projects that do not have the target
+                let dummy_id = get_id(
+                    dependency,
+                    &format!("{}__{target}{DUMMY_TASK_TARGET}", task.project),
+                    None,
+                );
+                self.push_dependency(task.id, &dummy_id);
+                self.dependencies.entry(dummy_id.clone()).or_default();
+                self.process_task(
+                    &ProcessedTask {
+                        id: &dummy_id,
+                        project: task.project,
+                        target: task.target,
+                    },
+                    dependency,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_dependency(
+        &mut self,
+        task: &ProcessedTask,
+        project_name: &str,
+        project: &Project,
+        target: &str,
+        overrides: &Option<Value>,
+    ) -> anyhow::Result<()> {
+        let configuration = resolve_configuration(project, target, self.configuration);
+        let dependency_id = get_id(project_name, target, configuration.as_deref());
+        if task.id != dependency_id {
+            self.push_dependency(task.id, &dependency_id);
+        }
+
+        if !self.tasks.contains_key(&dependency_id) {
+            let dependency_task = self.create_task(
+                &dependency_id,
+                project_name,
+                target,
+                configuration,
+                overrides.clone(),
+            )?;
+            self.tasks.insert(dependency_id.clone(), dependency_task);
+            self.dependencies.insert(dependency_id.clone(), vec![]);
+            self.process_task(
+                &ProcessedTask {
+                    id: &dependency_id,
+                    project: project_name,
+                    target,
+                },
+                project_name,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn push_dependency(&mut self, task_id: &str, dependency_id: &str) {
+        self.dependencies
+            .entry(task_id.to_string())
+            .or_default()
+            .push(dependency_id.to_string());
+    }
+
+    fn get_dependency_configs(
+        &self,
+        project_name: &str,
+        target: &str,
+    ) -> anyhow::Result<Vec<DependencyConfig>> {
+        let project = self.project(project_name)?;
+        let Some(depends_on) = project
+            .targets
+            .get(target)
+            .and_then(|target| target.depends_on.as_ref())
+            .or_else(|| self.target_dependencies.get(target))
+        else {
+            return Ok(vec![]);
+        };
+
+        let mut dependency_configs = vec![];
+        for definition in depends_on {
+            let dependency_config = self.normalize_dependency_config(definition, project_name)?;
+            if !dependency_config.target.contains(GLOB_CHARACTERS) {
+                dependency_configs.push(dependency_config);
+                continue;
+            }
+
+            let glob = build_glob_set(&[dependency_config.target.as_str()])?;
+            dependency_configs.extend(
+                self.all_target_names
+                    .iter()
+                    .filter(|target_name| glob.is_match(target_name))
+                    .map(|target_name| DependencyConfig {
+                        target: target_name.to_string(),
+                        projects: dependency_config.projects.clone(),
+                        dependencies: dependency_config.dependencies,
+                        forward_params: dependency_config.forward_params,
+                    }),
+            );
+        }
+        Ok(dependency_configs)
+    }
+
+    fn normalize_dependency_config(
+        &self,
+        definition: &JsTargetDependency,
+        current_project: &str,
+    ) -> anyhow::Result<DependencyConfig> {
+        let (target, patterns, dependencies, forward_params) = match definition {
+            Either::A(target) => {
+                if let Some(target) = target.strip_prefix('^') {
+                    (target.to_string(), None, true, false)
+                } else {
+                    match target.split_once(':') {
+                        Some((project, project_target))
+                            if self.project_graph.nodes.contains_key(project) =>
+                        {
+                            (
+                                project_target.to_string(),
+                                Some(vec![project.to_string()]),
+                                false,
+                                false,
+                            )
+                        }
+                        _ => (target.to_string(), None, false, false),
+                    }
+                }
+            }
+            Either::B(TargetDependencyConfig {
+                projects,
+                dependencies,
+                target,
+                params,
+            }) => {
+                let forward_params = params.as_deref() == Some("forward");
+                let dependencies = dependencies.unwrap_or(false);
+                match projects {
+                    // `self` and `dependencies` are kept for lerna
+                    Some(Either::A(projects)) if projects == "self" => {
+                        (target.clone(), None, dependencies, forward_params)
+                    }
+                    Some(Either::A(projects)) if projects == "dependencies" => {
+                        (target.clone(), None, true, forward_params)
+                    }
+                    Some(Either::A(project)) => (
+                        target.clone(),
+                        Some(vec![project.clone()]),
+                        dependencies,
+                        forward_params,
+                    ),
+                    Some(Either::B(projects)) => (
+                        target.clone(),
+                        Some(projects.clone()),
+                        dependencies,
+                        forward_params,
+                    ),
+                    None => (target.clone(), None, dependencies, forward_params),
+                }
+            }
+        };
+
+        let projects = match patterns {
+            Some(patterns) => {
+                let patterns = patterns.iter().map(String::as_str).collect::<Vec<_>>();
+                Some(
+                    find_matching_projects(&patterns, self.project_graph)?
+                        .into_iter()
+                        .map(String::from)
+                        .collect(),
+                )
+            }
+            None if !dependencies => Some(vec![current_project.to_string()]),
+            None => None,
+        };
+
+        Ok(DependencyConfig {
+            target,
+            projects,
+            dependencies,
+            forward_params,
+        })
+    }
+
+    fn create_task(
+        &self,
+        id: &str,
+        project_name: &str,
+        target_name: &str,
+        configuration: Option<String>,
+        overrides: Option<Value>,
+    ) -> anyhow::Result<Task> {
+        let project = self.project(project_name)?;
+        let target = project.targets.get(target_name).ok_or_else(|| {
+            anyhow!("Cannot find configuration for task {project_name}:{target_name}")
+        })?;
+        if target.executor.is_none() {
+            bail!("Target \"{project_name}:{target_name}\" does not have an executor configured");
+        }
+
+        let overrides = overrides
+            .map(|overrides| interpolate_overrides(overrides, project_name, &project.root))
+            .transpose()?;
+        let outputs = get_outputs(
+            project_name,
+            project,
+            target_name,
+            target,
+            configuration.as_deref(),
+            overrides.as_ref(),
+        )?;
+        trace!("created task {id}");
+
+        Ok(Task {
+            id: id.to_string(),
+            target: TaskTarget {
+                project: project_name.to_string(),
+                target: target_name.to_string(),
+                configuration,
+            },
+            outputs,
+            project_root: Some(project.root.clone()),
+            overrides,
+        })
+    }
+
+    fn project(&self, project_name: &str) -> anyhow::Result<&'a Project> {
+        self.project_graph
+            .nodes
+            .get(project_name)
+            .ok_or_else(|| anyhow!("Project {project_name} not found in the project graph"))
+    }
+}
+
+fn get_id(project: &str, target: &str, configuration: Option<&str>) -> String {
+    match configuration {
+        Some(configuration) if !configuration.is_empty() => {
+            format!("{project}:{target}:{configuration}")
+        }
+        _ => format!("{project}:{target}"),
+    }
+}
+
+/// Uses the configuration if the target has it, and the default configuration of the target otherwise
+fn resolve_configuration(
+    project: &Project,
+    target: &str,
+    configuration: Option<&str>,
+) -> Option<String> {
+    let target = project.targets.get(target)?;
+    let default_configuration = target.default_configuration.clone();
+    let Some(configuration) = configuration.or(default_configuration.as_deref()) else {
+        return default_configuration;
+    };
+
+    let has_configuration = target
+        .configurations
+        .as_deref()
+        .and_then(|configurations| serde_json::from_str::<Value>(configurations).ok())
+        .is_some_and(|configurations| configurations.get(configuration).is_some());
+    if has_configuration {
+        Some(configuration.to_string())
+    } else {
+        default_configuration
+    }
+}
+
+/// Removes the dummy tasks, and makes the tasks depend on the tasks that the dummy tasks lead to instead
+fn filter_dummy_tasks(dependencies: &mut HashMap<String, Vec<String>>) {
+    let cycles = find_cycles(dependencies);
+    let task_ids = dependencies
+        .keys()
+        .filter(|id| !id.ends_with(DUMMY_TASK_TARGET))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    for id in task_ids {
+        let mut normalized_dependencies = vec![];
+        let mut seen = HashSet::from([id.clone()]);
+        for dependency in &dependencies[&id] {
+            collect_non_dummy_dependencies(
+                dependency,
+                dependencies,
+                &cycles,
+                &mut seen,
+                &mut normalized_dependencies,
+            );
+        }
+        dependencies.insert(id, normalized_dependencies);
+    }
+
+    dependencies.retain(|id, _| !id.ends_with(DUMMY_TASK_TARGET));
+}
+
+fn collect_non_dummy_dependencies(
+    task_id: &str,
+    dependencies: &HashMap<String, Vec<String>>,
+    cycles: &HashSet<String>,
+    seen: &mut HashSet<String>,
+    non_dummy_dependencies: &mut Vec<String>,
+) {
+    if !seen.insert(task_id.to_string()) {
+        return;
+    }
+
+    if !task_id.ends_with(DUMMY_TASK_TARGET) {
+        non_dummy_dependencies.push(task_id.to_string());
+    } else if !cycles.contains(task_id) {
+        for dependency in dependencies.get(task_id).into_iter().flatten() {
+            collect_non_dummy_dependencies(
+                dependency,
+                dependencies,
+                cycles,
+                seen,
+                non_dummy_dependencies,
+            );
+        }
+    }
+}
+
+/// Finds the first cycle in the dependencies, which starts and ends with the same task
+fn find_cycle(dependencies: &HashMap<String, Vec<String>>) -> Option<Vec<String>> {
+    let mut visited = HashSet::new();
+    let mut ids = dependencies.keys().collect::<Vec<_>>();
+    ids.sort();
+    ids.into_iter().find_map(|id| {
+        let mut path = vec![id.as_str()];
+        find_cycle_from(dependencies, id, &mut visited, &mut path).map(|cycle| {
+            let start = cycle.iter().position(|id| id == cycle.last().unwrap());
+            cycle[start.unwrap_or(0)..].to_vec()
+        })
+    })
+}
+
+/// Finds the tasks on the paths to the cycles in the dependencies
+fn find_cycles(dependencies: &HashMap<String, Vec<String>>) -> HashSet<String> {
+    let mut visited = HashSet::new();
+    let mut ids = dependencies.keys().collect::<Vec<_>>();
+    ids.sort();
+    ids.into_iter()
+        .filter_map(|id| {
+            let mut path = vec![id.as_str()];
+            find_cycle_from(dependencies, id, &mut visited, &mut path)
+        })
+        .flatten()
+        .collect()
+}
+
+fn find_cycle_from<'d>(
+    dependencies: &'d HashMap<String, Vec<String>>,
+    id: &'d str,
+    visited: &mut HashSet<&'d str>,
+    path: &mut Vec<&'d str>,
+) -> Option<Vec<String>> {
+    if !visited.insert(id) {
+        return None;
+    }
+
+    for dependency in dependencies.get(id).into_iter().flatten() {
+        if path.contains(&dependency.as_str()) {
+            let mut cycle = path.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+            cycle.push(dependency.to_string());
+            return Some(cycle);
+        }
+        path.push(dependency);
+        let cycle = find_cycle_from(dependencies, dependency, visited, path);
+        path.pop();
+        if cycle.is_some() {
+            return cycle;
+        }
+    }
+    None
+}
+
+/// Gets the outputs that are cached for a task, the same way as `getOutputsForTargetAndConfiguration`
+fn get_outputs(
+    project_name: &str,
+    project: &Project,
+    target_name: &str,
+    target: &Target,
+    configuration: Option<&str>,
+    overrides: Option<&Value>,
+) -> anyhow::Result<Vec<String>> {
+    let options = resolve_task_options(target, configuration, overrides)?;
+
+    if let Some(outputs) = &target.outputs {
+        let mut result: Vec<String> = vec![];
+        for output in outputs {
+            let output = interpolate(output, project_name, &project.root, Some(&options))?;
+            if !output.is_empty() && !is_unresolved(&output) && !result.contains(&output) {
+                result.push(output);
+            }
+        }
+        return Ok(result);
+    }
+
+    // outputs used to be read from the outputPath option
+    Ok(match options.get("outputPath") {
+        Some(Value::String(output_path)) => vec![output_path.clone()],
+        Some(Value::Array(output_paths)) => output_paths
+            .iter()
+            .filter_map(|output_path| output_path.as_str().map(String::from))
+            .collect(),
+        _ if target_name == "build" || target_name == "prepare" => {
+            let root = &project.root;
+            vec![
+                format!("dist/{root}"),
+                format!("{root}/dist"),
+                format!("{root}/build"),
+                format!("{root}/public"),
+            ]
+        }
+        _ => vec![],
+    })
+}
+
+fn is_unresolved(output: &str) -> bool {
+    output.contains("{projectRoot}")
+        || output.contains("{workspaceRoot}")
+        || output
+            .find("{options")
+            .is_some_and(|start| output[start..].contains('}'))
+}
+
+fn interpolate_overrides(
+    overrides: Value,
+    project_name: &str,
+    project_root: &str,
+) -> anyhow::Result<Value> {
+    let Value::Object(overrides) = overrides else {
+        return Ok(overrides);
+    };
+    overrides
+        .into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::String(value) => {
+                    Value::String(interpolate(&value, project_name, project_root, None)?)
+                }
+                value => value,
+            };
+            Ok((name, value))
+        })
+        .collect::<anyhow::Result<_>>()
+        .map(Value::Object)
+}
+
+/// Replaces `{projectRoot}`, `{projectName}` and `{options.*}` in a path, the same way as `interpolate` in JS
+fn interpolate(
+    template: &str,
+    project_name: &str,
+    project_root: &str,
+    options: Option<&Value>,
+) -> anyhow::Result<String> {
+    let has_replacements = template
+        .find('{')
+        .is_some_and(|start| template[start + 1..].contains('}'));
+    if template.starts_with('/') || !has_replacements {
+        return Ok(template.to_string());
+    }
+
+    if template[1..].contains("{workspaceRoot}") {
+        bail!(
+            "Output '{template}' is invalid. {{workspaceRoot}} can only be used at the beginning of the expression."
+        );
+    }
+    if project_root == "." && template[1..].contains("{projectRoot}") {
+        bail!(
+            "Output '{template}' is invalid. When {{projectRoot}} is '.', it can only be used at the beginning of the expression."
+        );
+    }
+
+    let parts = template
+        .split('/')
+        .map(|part| interpolate_part(part, project_name, project_root, options))
+        .collect::<Vec<_>>();
+    Ok(normalize_path(&parts.join("/")).replacen("{workspaceRoot}/", "", 1))
+}
+
+fn interpolate_part(
+    part: &str,
+    project_name: &str,
+    project_root: &str,
+    options: Option<&Value>,
+) -> String {
+    let part = if project_root == "." {
+        part.replacen("{projectRoot}", "", 1)
+    } else {
+        part.to_string()
+    };
+
+    let mut result = String::with_capacity(part.len());
+    let mut rest = part.as_str();
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start + 1..].find('}') else {
+            break;
+        };
+        let end = start + 1 + length;
+        let path = rest[start + 1..end].trim();
+        result.push_str(&rest[..start]);
+        match lookup(path, project_name, project_root, options) {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Looks up a value that can be interpolated, values that are not set or falsy are not replaced
+fn lookup(
+    path: &str,
+    project_name: &str,
+    project_root: &str,
+    options: Option<&Value>,
+) -> Option<String> {
+    let value = match path.split('.').collect::<Vec<_>>().as_slice() {
+        ["projectRoot"] | ["project", "root"] => Value::String(project_root.to_string()),
+        ["projectName"] | ["project", "name"] => Value::String(project_name.to_string()),
+        ["options", keys @ ..] => {
+            let mut value = options?;
+            for key in keys {
+                value = value.get(key)?;
+            }
+            value.clone()
+        }
+        _ => return None,
+    };
+
+    match value {
+        Value::String(value) if !value.is_empty() => Some(value),
+        Value::Number(value) if value.as_f64() != Some(0.0) => Some(value.to_string()),
+        Value::Bool(true) => Some("true".to_string()),
+        _ => None,
+    }
+}
+
+/// Joins and normalizes a posix path, like `path.join` in JS
+fn normalize_path(path: &str) -> String {
+    let mut segments: Vec<&str> = vec![];
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." if segments.last().is_some_and(|last| *last != "..") => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    let normalized = segments.join("/");
+    if path.starts_with('/') {
+        format!("/{normalized}")
+    } else if normalized.is_empty() {
+        ".".to_string()
+    } else {
+        normalized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(depends_on: Vec<JsTargetDependency>) -> Target {
+        Target {
+            executor: Some("nx:run-commands".into()),
+            depends_on: Some(depends_on),
+            ..Default::default()
+        }
+    }
+
+    fn create_project_graph(projects: Vec<(&str, Vec<(&str, Target)>)>) -> ProjectGraph {
+        ProjectGraph {
+            nodes: projects
+                .into_iter()
+                .map(|(name, targets)| {
+                    (
+                        name.to_string(),
+                        Project {
+                            root: format!("libs/{name}"),
+                            targets: targets
+                                .into_iter()
+                                .map(|(name, target)| (name.to_string(), target))
+                                .collect(),
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            dependencies: HashMap::from([
+                ("app".into(), vec!["utils".into()]),
+                ("utils".into(), vec!["core".into(), "npm:react".into()]),
+                ("core".into(), vec![]),
+            ]),
+            external_nodes: HashMap::new(),
+        }
+    }
+
+    fn sorted_dependencies(task_graph: &TaskGraph, id: &str) -> Vec<String> {
+        let mut dependencies = task_graph.dependencies[id].clone();
+        dependencies.sort();
+        dependencies
+    }
+
+    #[test]
+    fn should_create_tasks_for_the_dependencies_of_projects() {
+        let project_graph = create_project_graph(vec![
+            (
+                "app",
+                vec![
+                    ("build", target(vec![Either::A("^build".into())])),
+                    ("lint", target(vec![])),
+                ],
+            ),
+            // utils does not have a build target, so app depends on the build of core through it
+            ("utils", vec![("lint", target(vec![]))]),
+            (
+                "core",
+                vec![("build", target(vec![Either::A("^build".into())]))],
+            ),
+        ]);
+
+        let task_graph = build_task_graph(
+            &project_graph,
+            &HashMap::new(),
+            &["app".into()],
+            &["build".into()],
+            &Default::default(),
+        )
+        .unwrap();
+
+        let mut task_ids = task_graph.tasks.keys().collect::<Vec<_>>();
+        task_ids.sort();
+        assert_eq!(task_ids, vec!["app:build", "core:build"]);
+        assert_eq!(
+            sorted_dependencies(&task_graph, "app:build"),
+            vec!["core:build"]
+        );
+        assert_eq!(task_graph.roots, vec!["core:build"]);
+        assert_eq!(
+            task_graph.tasks["core:build"].outputs,
+            vec![
+                "dist/libs/core",
+                "libs/core/dist",
+                "libs/core/build",
+                "libs/core/public"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_expand_target_defaults_and_project_target_dependencies() {
+        let project_graph = create_project_graph(vec![
+            (
+                "app",
+                vec![
+                    (
+                        "test",
+                        Target {
+                            executor: Some("nx:run-commands".into()),
+                            options: Some(r#"{"coverage":"coverage"}"#.into()),
+                            outputs: Some(vec![
+                                "{workspaceRoot}/{options.coverage}/{projectRoot}".into()
+                            ]),
+                            ..Default::default()
+                        },
+                    ),
+                    ("build", target(vec![])),
+                ],
+            ),
+            (
+                "utils",
+                vec![(
+                    "build",
+                    Target {
+                        executor: Some("nx:run-commands".into()),
+                        configurations: Some(r#"{"production":{}}"#.into()),
+                        default_configuration: Some("production".into()),
+                        ..Default::default()
+                    },
+                )],
+            ),
+            ("core", vec![]),
+        ]);
+        let target_dependencies = HashMap::from([(
+            "test".to_string(),
+            vec![
+                Either::A("build".into()),
+                Either::B(TargetDependencyConfig {
+                    projects: Some(Either::A("utils".into())),
+                    target: "build".into(),
+                    ..Default::default()
+                }),
+            ],
+        )]);
+
+        let task_graph = build_task_graph(
+            &project_graph,
+            &target_dependencies,
+            &["app".into()],
+            &["test".into()],
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            sorted_dependencies(&task_graph, "app:test"),
+            vec!["app:build", "utils:build:production"]
+        );
+        assert_eq!(
+            task_graph.tasks["app:test"].outputs,
+            vec!["coverage/libs/app"]
+        );
+        assert_eq!(
+            task_graph.tasks["utils:build:production"]
+                .target
+                .configuration
+                .as_deref(),
+            Some("production")
+        );
+    }
+
+    #[test]
+    fn should_error_on_circular_task_dependencies() {
+        let project_graph = create_project_graph(vec![
+            (
+                "app",
+                vec![
+                    ("build", target(vec![Either::A("lint".into())])),
+                    ("lint", target(vec![Either::A("build".into())])),
+                ],
+            ),
+            ("utils", vec![]),
+            ("core", vec![]),
+        ]);
+        let create = |ignore_cycles| {
+            build_task_graph(
+                &project_graph,
+                &HashMap::new(),
+                &["app".into()],
+                &["build".into()],
+                &TaskGraphOptions {
+                    ignore_cycles: Some(ignore_cycles),
+                    ..Default::default()
+                },
+            )
+        };
+
+        let error = create(false)
+            .err()
+            .unwrap()
+            .downcast::<TaskGraphCycleError>()
+            .unwrap();
+        assert_eq!(error.path, vec!["app:build", "app:lint", "app:build"]);
+        assert!(create(true).unwrap().roots.is_empty());
+    }
+
+    #[test]
+    fn should_interpolate_outputs() {
+        let options = json!({ "outputPath": "dist/app", "empty": "" });
+        let interpolate = |template| interpolate(template, "app", "apps/app", Some(&options));
+
+        assert_eq!(
+            interpolate("{workspaceRoot}/{options.outputPath}").unwrap(),
+            "dist/app"
+        );
+        assert_eq!(
+            interpolate("{projectRoot}/../{projectName}/coverage").unwrap(),
+            "apps/app/coverage"
+        );
+        assert_eq!(
+            interpolate("{workspaceRoot}/{options.empty}").unwrap(),
+            "{options.empty}"
+        );
+        assert!(interpolate("dist/{workspaceRoot}").is_err());
+    }
+}
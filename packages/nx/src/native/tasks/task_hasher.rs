@@ -1,9 +1,22 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
 use std::sync::Arc;
 
+#[cfg(not(target_arch = "wasm32"))]
 use crate::native::{
-    hasher::hash,
-    project_graph::{types::ProjectGraph, utils::create_project_root_mappings},
+    cache::task_output_files::{
+        get_recorded_task_output_files, get_task_output_files_hash_algorithm,
+    },
+    db::connection::NxDbConnection,
+    utils::NxMutex,
+};
+use crate::native::{
+    glob::build_glob_set,
+    hasher::{hash_with_algorithm, HashAlgorithm, Hasher},
+    project_graph::{
+        types::ProjectGraph,
+        utils::{create_project_root_mappings, find_project_for_path},
+    },
     tasks::types::HashInstruction,
     types::NapiDashMap,
 };
@@ -13,17 +26,24 @@ use crate::native::{
 };
 use crate::native::{
     tasks::hashers::{
-        hash_all_externals, hash_external, hash_project_config, hash_project_files,
-        hash_task_output, hash_tsconfig_selectively,
+        collect_env, collect_files, collect_task_output_files, collect_workspace_files,
+        get_json_value, get_workspace_globs, hash_all_externals, hash_external, hash_json_file,
+        hash_project_config, hash_project_files, hash_project_tsconfig, hash_recorded_task_output,
+        hash_task_output, TsConfigHash,
     },
     types::FileData,
     workspace::types::ProjectFiles,
 };
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use dashmap::DashMap;
 use napi::bindgen_prelude::{Buffer, External};
 use rayon::prelude::*;
-use tracing::{debug, trace, trace_span};
+use tracing::{debug, trace, trace_span, warn};
+
+/// Bump this when the way that tasks are hashed changes, so that the change of every task hash is deliberate.
+/// Version 2 hashes the resolved options of tasks as canonical JSON, which does not match hashes of version 1
+/// or the remote caches populated with them.
+const TASK_HASH_VERSION: &str = "2";
 
 #[napi(object)]
 #[derive(Debug)]
@@ -32,23 +52,63 @@ pub struct HashDetails {
     pub details: HashMap<String, String>,
 }
 
+/// The concrete inputs of a task, as they would be hashed
+#[napi(object)]
+#[derive(Debug, Default)]
+pub struct ResolvedInputs {
+    /// The files matched by each project fileset, workspace fileset and dependent task output instruction
+    pub files: HashMap<String, Vec<String>>,
+    /// The environment variables that are set and match the env inputs
+    pub environment: Vec<String>,
+    pub runtime: Vec<String>,
+    pub externals: Vec<String>,
+    /// Filesets that did not match any files
+    pub warnings: Vec<String>,
+}
+
 #[napi(object)]
 pub struct HasherOptions {
     pub selectively_hash_ts_config: bool,
+    /// The algorithm of the workspace file hashes, which task hashes are also computed with
+    pub hash_algorithm: Option<HashAlgorithm>,
+}
+
+/// Files that are either shared with JS, or owned by the hasher so that they can be updated when they change
+enum HasherFiles<T: 'static> {
+    Shared(External<T>),
+    Owned(T),
+}
+
+impl<T: 'static> Deref for HasherFiles<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            HasherFiles::Shared(files) => files,
+            HasherFiles::Owned(files) => files,
+        }
+    }
 }
 
 #[napi]
 pub struct TaskHasher {
     workspace_root: String,
     project_graph: External<ProjectGraph>,
-    project_file_map: External<HashMap<String, Vec<FileData>>>,
-    all_workspace_files: External<Vec<FileData>>,
+    project_file_map: HasherFiles<ProjectFiles>,
+    all_workspace_files: HasherFiles<Vec<FileData>>,
     ts_config: Vec<u8>,
     ts_config_paths: HashMap<String, Vec<String>>,
     options: Option<HasherOptions>,
+    hash_algorithm: HashAlgorithm,
     workspace_files_cache: Arc<DashMap<String, String>>,
     external_cache: Arc<DashMap<String, String>>,
     runtime_cache: Arc<DashMap<String, String>>,
+    json_files_cache: Arc<DashMap<String, String>>,
+    tsconfig_cache: Arc<DashMap<String, TsConfigHash>>,
+    /// Hashes of the instructions that only depend on the files and the project graph
+    instruction_cache: DashMap<HashInstruction, String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    task_output_files_db: Option<NxMutex<External<NxDbConnection>>>,
 }
 #[napi]
 impl TaskHasher {
@@ -62,18 +122,15 @@ impl TaskHasher {
         ts_config_paths: HashMap<String, Vec<String>>,
         options: Option<HasherOptions>,
     ) -> Self {
-        Self {
+        Self::with_files(
             workspace_root,
             project_graph,
-            project_file_map,
-            all_workspace_files,
-            ts_config: ts_config.to_vec(),
+            HasherFiles::Shared(project_file_map),
+            HasherFiles::Shared(all_workspace_files),
+            ts_config.to_vec(),
             ts_config_paths,
             options,
-            workspace_files_cache: Arc::new(DashMap::new()),
-            external_cache: Arc::new(DashMap::new()),
-            runtime_cache: Arc::new(DashMap::new()),
-        }
+        )
     }
 
     #[napi]
@@ -87,7 +144,7 @@ impl TaskHasher {
         trace!("all workspace files: {}", self.all_workspace_files.len());
         trace!("project_file_map: {}", self.project_file_map.len());
 
-        let ts_config_hash = hash(&self.ts_config);
+        let ts_config_hash = hash_with_algorithm(self.hash_algorithm, &self.ts_config);
         let project_root_mappings = create_project_root_mappings(&self.project_graph.nodes);
 
         let mut sorted_externals = self.project_graph.external_nodes.keys().collect::<Vec<_>>();
@@ -99,6 +156,8 @@ impl TaskHasher {
             .map(|o| o.selectively_hash_ts_config)
             .unwrap_or(false);
 
+        let recorded_task_outputs = self.get_recorded_task_outputs(&hash_plans);
+
         let hash_time = std::time::Instant::now();
 
         let hashes: NapiDashMap<String, HashDetails> = NapiDashMap::new();
@@ -121,6 +180,7 @@ impl TaskHasher {
                         project_root_mappings: &project_root_mappings,
                         sorted_externals: &sorted_externals,
                         selectively_hash_tsconfig,
+                        recorded_task_outputs: &recorded_task_outputs,
                     },
                 )?;
 
@@ -139,13 +199,14 @@ impl TaskHasher {
             let (hash_id, hash_details) = h.pair_mut();
             let mut keys = hash_details.details.keys().collect::<Vec<_>>();
             keys.par_sort();
-            let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+            let mut hasher = Hasher::with_algorithm(self.hash_algorithm);
+            hasher.update(TASK_HASH_VERSION.as_bytes());
             trace_span!("Assembling hash", hash_id).in_scope(|| {
                 for key in keys {
                     trace!("Adding {} ({}) to hash", hash_details.details[key], key);
                     hasher.update(hash_details.details[key].as_bytes());
                 }
-                let hash = hasher.digest().to_string();
+                let hash = hasher.digest();
                 trace!("Hash Value: {}", hash);
                 hash_details.value = hash;
             });
@@ -155,6 +216,119 @@ impl TaskHasher {
         Ok(hashes)
     }
 
+    /// Expands the hash plan of a task into the files, environment variables and externals it depends on,
+    /// without hashing them
+    #[napi]
+    pub fn resolve_inputs(
+        &self,
+        task_id: String,
+        hash_plans: External<HashMap<String, Vec<HashInstruction>>>,
+        js_env: HashMap<String, String>,
+    ) -> anyhow::Result<ResolvedInputs> {
+        let instructions = hash_plans
+            .get(&task_id)
+            .ok_or_else(|| anyhow!("Task with id '{task_id}' not found"))?;
+
+        let mut resolved_inputs = ResolvedInputs::default();
+        for instruction in instructions {
+            match instruction {
+                HashInstruction::ProjectFileSet(project_name, file_sets) => {
+                    let project = self
+                        .project_graph
+                        .nodes
+                        .get(project_name)
+                        .ok_or_else(|| anyhow!("project {} not found", project_name))?;
+                    let files = collect_files(
+                        project_name,
+                        &project.root,
+                        file_sets,
+                        &self.project_file_map,
+                    )?;
+                    for file_set in file_sets.iter().filter(|f| !f.starts_with('!')) {
+                        let matched_files = collect_files(
+                            project_name,
+                            &project.root,
+                            std::slice::from_ref(file_set),
+                            &self.project_file_map,
+                        )?;
+                        if matched_files.is_empty() {
+                            resolved_inputs.warnings.push(format!(
+                                "{file_set} in {project_name} does not match any files"
+                            ));
+                        }
+                    }
+                    resolved_inputs.files.insert(
+                        instruction.to_string(),
+                        files.into_iter().map(|f| f.file.clone()).collect(),
+                    );
+                }
+                HashInstruction::WorkspaceFileSet(file_sets) => {
+                    let files = collect_workspace_files(file_sets, &self.all_workspace_files)?;
+                    for file_set in file_sets.iter().filter(|f| !f.starts_with('!')) {
+                        let matched_files = collect_workspace_files(
+                            std::slice::from_ref(file_set),
+                            &self.all_workspace_files,
+                        )?;
+                        if matched_files.is_empty() {
+                            resolved_inputs
+                                .warnings
+                                .push(format!("{file_set} does not match any files"));
+                        }
+                    }
+                    resolved_inputs.files.insert(
+                        instruction.to_string(),
+                        files.into_iter().map(|f| f.file.clone()).collect(),
+                    );
+                }
+                HashInstruction::TaskOutput(glob, outputs) => {
+                    let files = collect_task_output_files(&self.workspace_root, glob, outputs)?;
+                    resolved_inputs.files.insert(instruction.to_string(), files);
+                }
+                HashInstruction::JsonFile(file, pointer) => {
+                    if get_json_value(&self.workspace_root, file, pointer)?.is_none() {
+                        resolved_inputs
+                            .warnings
+                            .push(format!("{file}#{pointer} does not exist"));
+                    }
+                    resolved_inputs
+                        .files
+                        .insert(instruction.to_string(), vec![file.clone()]);
+                }
+                HashInstruction::Environment(env) => {
+                    resolved_inputs.environment.extend(
+                        collect_env(env, &js_env)?
+                            .into_iter()
+                            .map(|(name, _)| name.clone()),
+                    );
+                }
+                HashInstruction::Runtime(runtime) => {
+                    resolved_inputs.runtime.push(runtime.clone());
+                }
+                HashInstruction::External(external) => {
+                    resolved_inputs.externals.push(external.clone());
+                }
+                HashInstruction::AllExternalDependencies => {
+                    resolved_inputs
+                        .externals
+                        .extend(self.project_graph.external_nodes.keys().cloned());
+                }
+                HashInstruction::ProjectConfiguration(_)
+                | HashInstruction::TsConfiguration(_)
+                | HashInstruction::TaskOptions(..) => {}
+            }
+        }
+
+        for warning in &resolved_inputs.warnings {
+            warn!("{task_id}: {warning}");
+        }
+
+        resolved_inputs.environment.sort();
+        resolved_inputs.environment.dedup();
+        resolved_inputs.externals.sort();
+        resolved_inputs.externals.dedup();
+        Ok(resolved_inputs)
+    }
+
     fn hash_instruction(
         &self,
         task_id: &str,
@@ -165,8 +339,22 @@ impl TaskHasher {
             project_root_mappings,
             sorted_externals,
             selectively_hash_tsconfig,
+            recorded_task_outputs,
         }: HashInstructionArgs,
     ) -> anyhow::Result<(String, String)> {
+        let cacheable = matches!(
+            instruction,
+            HashInstruction::WorkspaceFileSet(_)
+                | HashInstruction::JsonFile(..)
+                | HashInstruction::ProjectFileSet(..)
+                | HashInstruction::TsConfiguration(_)
+        );
+        if cacheable {
+            if let Some(cached_hash) = self.instruction_cache.get(instruction) {
+                return Ok((instruction.to_string(), cached_hash.clone()));
+            }
+        }
+
         let now = std::time::Instant::now();
         let span = trace_span!("hashing", task_id).entered();
         let hash = match instruction {
@@ -175,6 +363,7 @@ impl TaskHasher {
                     workspace_file_set,
                     &self.all_workspace_files,
                     Arc::clone(&self.workspace_files_cache),
+                    self.hash_algorithm,
                 );
                 trace!(parent: &span, "hash_workspace_files: {:?}", now.elapsed());
                 hashed_workspace_files?
@@ -185,15 +374,27 @@ impl TaskHasher {
                     runtime,
                     js_env,
                     Arc::clone(&self.runtime_cache),
+                    self.hash_algorithm,
                 )?;
                 trace!(parent: &span, "hash_runtime: {:?}", now.elapsed());
                 hashed_runtime
             }
             HashInstruction::Environment(env) => {
-                let hashed_env = hash_env(env, js_env);
+                let hashed_env = hash_env(env, js_env, self.hash_algorithm)?;
                 trace!(parent: &span, "hash_env: {:?}", now.elapsed());
                 hashed_env
             }
+            HashInstruction::JsonFile(file, pointer) => {
+                let hashed_json_file = hash_json_file(
+                    &self.workspace_root,
+                    file,
+                    pointer,
+                    Arc::clone(&self.json_files_cache),
+                    self.hash_algorithm,
+                )?;
+                trace!(parent: &span, "hash_json_file: {:?}", now.elapsed());
+                hashed_json_file
+            }
             HashInstruction::ProjectFileSet(project_name, file_sets) => {
                 let project = self
                     .project_graph
@@ -205,13 +406,17 @@ impl TaskHasher {
                     &project.root,
                     file_sets,
                     &self.project_file_map,
+                    self.hash_algorithm,
                 )?;
                 trace!(parent: &span, "hash_project_files: {:?}", now.elapsed());
                 hashed_project_files
             }
             HashInstruction::ProjectConfiguration(project_name) => {
-                let hashed_project_config =
-                    hash_project_config(project_name, &self.project_graph.nodes)?;
+                let hashed_project_config = hash_project_config(
+                    project_name,
+                    &self.project_graph.nodes,
+                    self.hash_algorithm,
+                )?;
                 trace!(parent: &span, "hash_project_config: {:?}", now.elapsed());
                 hashed_project_config
             }
@@ -219,11 +424,15 @@ impl TaskHasher {
                 let ts_config_hash = if !selectively_hash_tsconfig {
                     ts_config_hash.to_string()
                 } else {
-                    hash_tsconfig_selectively(
+                    hash_project_tsconfig(
+                        &self.workspace_root,
                         project_name,
+                        &self.project_graph.nodes[project_name].root,
                         &self.ts_config,
                         &self.ts_config_paths,
                         project_root_mappings,
+                        Arc::clone(&self.tsconfig_cache),
+                        self.hash_algorithm,
                     )?
                 };
 
@@ -233,7 +442,10 @@ impl TaskHasher {
                     .get("typescript")
                     .and_then(|pkg| pkg.hash.as_deref())
                     .map(|pkg_hash| {
-                        hash(&[pkg_hash.as_bytes(), ts_config_hash.as_bytes()].concat())
+                        hash_with_algorithm(
+                            self.hash_algorithm,
+                            &[pkg_hash.as_bytes(), ts_config_hash.as_bytes()].concat(),
+                        )
                     })
                     // the unwrap_or is for the case where typescript is not installed
                     .unwrap_or(ts_config_hash);
@@ -242,7 +454,21 @@ impl TaskHasher {
                 ts_hash
             }
             HashInstruction::TaskOutput(glob, outputs) => {
-                let hashed_task_output = hash_task_output(&self.workspace_root, glob, outputs)?;
+                let recorded_output_files = outputs
+                    .iter()
+                    .map(|output| recorded_task_outputs.get(output))
+                    .collect::<Option<Vec<_>>>();
+                let hashed_task_output = if let Some(recorded_output_files) = recorded_output_files
+                {
+                    let mut output_files = recorded_output_files
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>();
+                    output_files.sort();
+                    hash_recorded_task_output(glob, &output_files, self.hash_algorithm)?
+                } else {
+                    hash_task_output(&self.workspace_root, glob, outputs, self.hash_algorithm)?
+                };
                 trace!(parent: &span, "hash_task_output: {:?}", now.elapsed());
                 hashed_task_output
             }
@@ -251,6 +477,7 @@ impl TaskHasher {
                     external,
                     &self.project_graph.external_nodes,
                     Arc::clone(&self.external_cache),
+                    self.hash_algorithm,
                 )?;
                 trace!(parent: &span, "hash_external: {:?}", now.elapsed());
                 hashed_external
@@ -260,19 +487,590 @@ impl TaskHasher {
                     sorted_externals,
                     &self.project_graph.external_nodes,
                     Arc::clone(&self.external_cache),
+                    self.hash_algorithm,
                 )?;
                 trace!(parent: &span, "hash_all_externals: {:?}", now.elapsed());
                 hashed_all_externals
             }
+            HashInstruction::TaskOptions(_, options) => {
+                let hashed_options = hash_with_algorithm(self.hash_algorithm, options.as_bytes());
+                trace!(parent: &span, "hash_task_options: {:?}", now.elapsed());
+                hashed_options
+            }
         };
+        if cacheable {
+            self.instruction_cache
+                .insert(instruction.clone(), hash.clone());
+        }
         Ok((instruction.to_string(), hash))
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+#[napi]
+impl TaskHasher {
+    /// Hash dependent task outputs with the file hashes that the cache recorded in the db,
+    /// instead of walking and reading the outputs from disk
+    #[napi]
+    pub fn use_recorded_task_outputs(&mut self, db: External<NxDbConnection>) {
+        self.task_output_files_db = Some(NxMutex::new(db));
+    }
+}
+
+impl TaskHasher {
+    fn with_files(
+        workspace_root: String,
+        project_graph: External<ProjectGraph>,
+        project_file_map: HasherFiles<ProjectFiles>,
+        all_workspace_files: HasherFiles<Vec<FileData>>,
+        ts_config: Vec<u8>,
+        ts_config_paths: HashMap<String, Vec<String>>,
+        options: Option<HasherOptions>,
+    ) -> Self {
+        let hash_algorithm = options
+            .as_ref()
+            .and_then(|o| o.hash_algorithm)
+            .unwrap_or_default();
+        Self {
+            workspace_root,
+            project_graph,
+            project_file_map,
+            all_workspace_files,
+            ts_config,
+            ts_config_paths,
+            options,
+            hash_algorithm,
+            workspace_files_cache: Arc::new(DashMap::new()),
+            external_cache: Arc::new(DashMap::new()),
+            runtime_cache: Arc::new(DashMap::new()),
+            json_files_cache: Arc::new(DashMap::new()),
+            tsconfig_cache: Arc::new(DashMap::new()),
+            instruction_cache: DashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            task_output_files_db: None,
+        }
+    }
+
+    /// Creates a hasher that owns its files, so that they can be kept up to date with [`TaskHasher::update_files`]
+    pub(crate) fn with_owned_files(
+        workspace_root: String,
+        project_graph: External<ProjectGraph>,
+        project_file_map: ProjectFiles,
+        all_workspace_files: Vec<FileData>,
+        ts_config: Vec<u8>,
+        ts_config_paths: HashMap<String, Vec<String>>,
+        options: Option<HasherOptions>,
+    ) -> Self {
+        Self::with_files(
+            workspace_root,
+            project_graph,
+            HasherFiles::Owned(project_file_map),
+            HasherFiles::Owned(all_workspace_files),
+            ts_config,
+            ts_config_paths,
+            options,
+        )
+    }
+
+    /// Applies changed files to the files of the hasher, and drops the cached hashes that depend on them.
+    /// `updated_files` are the hashes of the files that the workspace context was updated with,
+    /// and deleted directories are expanded into the files that were in them.
+    pub(crate) fn update_files(
+        &mut self,
+        updated_files: &HashMap<String, String>,
+        deleted_files_and_directories: &[&str],
+    ) -> anyhow::Result<()> {
+        let (HasherFiles::Owned(project_file_map), HasherFiles::Owned(workspace_files)) =
+            (&mut self.project_file_map, &mut self.all_workspace_files)
+        else {
+            bail!("The files of a hasher that are shared with JS cannot be updated");
+        };
+
+        let (deleted_files, remaining_files): (Vec<_>, Vec<_>) =
+            std::mem::take(workspace_files).into_iter().partition(|f| {
+                deleted_files_and_directories
+                    .iter()
+                    .any(|deleted| is_within(&f.file, deleted))
+            });
+        *workspace_files = remaining_files;
+        let deleted_files = deleted_files
+            .into_iter()
+            .map(|f| f.file)
+            .collect::<Vec<_>>();
+        for (file, hash) in updated_files {
+            upsert_file(workspace_files, file, hash);
+        }
+
+        let project_root_mappings = create_project_root_mappings(&self.project_graph.nodes);
+        let mut changed_projects = HashSet::new();
+        for (file, hash) in updated_files {
+            let Some(project) = find_project_for_path(file, &project_root_mappings) else {
+                continue;
+            };
+            let Some(project_files) = project_file_map.get_mut(project) else {
+                continue;
+            };
+            upsert_file(project_files, file, hash);
+            changed_projects.insert(project.to_string());
+        }
+        for file in &deleted_files {
+            let Some(project) = find_project_for_path(file, &project_root_mappings) else {
+                continue;
+            };
+            if let Some(project_files) = project_file_map.get_mut(project) {
+                project_files.retain(|f| &f.file != file);
+                changed_projects.insert(project.to_string());
+            }
+        }
+
+        let changed_files = updated_files
+            .keys()
+            .chain(&deleted_files)
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        trace!(
+            "invalidating hashes of {} changed files in {:?}",
+            changed_files.len(),
+            changed_projects
+        );
+        // the tsconfig hashes are dropped when any file of their extends and references chain changed
+        let mut changed_tsconfig_projects = HashSet::new();
+        self.tsconfig_cache.retain(|project, tsconfig_hash| {
+            let changed = tsconfig_hash
+                .files
+                .iter()
+                .any(|file| changed_files.contains(&file.as_str()));
+            if changed {
+                changed_tsconfig_projects.insert(project.clone());
+            }
+            !changed
+        });
+        invalidate_instruction_hashes(
+            &self.instruction_cache,
+            &changed_files,
+            &changed_projects,
+            &changed_tsconfig_projects,
+        );
+
+        // the invalidated instructions are rehashed from the files, not from the previous results
+        self.workspace_files_cache.clear();
+        self.json_files_cache.clear();
+        Ok(())
+    }
+
+    /// Replaces the project graph, the files of the projects and the root tsconfig after the project graph
+    /// was recomputed, and drops the cached hashes that depend on what changed
+    pub(crate) fn update_project_graph(
+        &mut self,
+        project_graph: External<ProjectGraph>,
+        project_files: ProjectFiles,
+        ts_config: Vec<u8>,
+        ts_config_paths: HashMap<String, Vec<String>>,
+    ) -> anyhow::Result<()> {
+        let HasherFiles::Owned(project_file_map) = &mut self.project_file_map else {
+            bail!("The files of a hasher that are shared with JS cannot be updated");
+        };
+
+        let roots_changed = create_project_root_mappings(&self.project_graph.nodes)
+            != create_project_root_mappings(&project_graph.nodes);
+        let typescript_hash = |project_graph: &ProjectGraph| {
+            project_graph
+                .external_nodes
+                .get("typescript")
+                .and_then(|pkg| pkg.hash.clone())
+        };
+        let ts_config_changed = self.ts_config != ts_config
+            || self.ts_config_paths != ts_config_paths
+            || typescript_hash(&self.project_graph) != typescript_hash(&project_graph);
+        let changed_projects = project_files
+            .iter()
+            .filter(|(project, files)| {
+                project_file_map
+                    .get(*project)
+                    .is_none_or(|previous_files| !same_file_hashes(previous_files, files))
+            })
+            .map(|(project, _)| project)
+            .chain(
+                project_file_map
+                    .keys()
+                    .filter(|project| !project_files.contains_key(*project)),
+            )
+            .cloned()
+            .collect::<HashSet<_>>();
+        trace!(
+            "updating the project graph, roots changed: {}, tsconfig changed: {}, changed projects: {:?}",
+            roots_changed,
+            ts_config_changed,
+            changed_projects
+        );
+
+        *project_file_map = project_files;
+        self.project_graph = project_graph;
+        self.ts_config = ts_config;
+        self.ts_config_paths = ts_config_paths;
+
+        self.external_cache.clear();
+        if roots_changed || ts_config_changed {
+            self.tsconfig_cache.clear();
+        }
+        self.instruction_cache
+            .retain(|instruction, _| match instruction {
+                HashInstruction::ProjectFileSet(project_name, _) => {
+                    !roots_changed && !changed_projects.contains(project_name)
+                }
+                HashInstruction::TsConfiguration(_) => !roots_changed && !ts_config_changed,
+                _ => true,
+            });
+        Ok(())
+    }
+
+    /// Replaces the root tsconfig, which is read by JS, and drops the tsconfig hashes
+    pub(crate) fn update_root_ts_config(
+        &mut self,
+        ts_config: Vec<u8>,
+        ts_config_paths: HashMap<String, Vec<String>>,
+    ) {
+        self.ts_config = ts_config;
+        self.ts_config_paths = ts_config_paths;
+        self.tsconfig_cache.clear();
+        self.instruction_cache
+            .retain(|instruction, _| !matches!(instruction, HashInstruction::TsConfiguration(_)));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn get_recorded_task_outputs(
+        &self,
+        hash_plans: &HashMap<String, Vec<HashInstruction>>,
+    ) -> HashMap<String, Vec<FileData>> {
+        let Some(db) = &self.task_output_files_db else {
+            return HashMap::new();
+        };
+        let Ok(db) = db.lock() else {
+            return HashMap::new();
+        };
+        // the recorded hashes can only be reused when the cache hashed them with the same algorithm
+        let recorded_hash_algorithm = get_task_output_files_hash_algorithm(&db).ok().flatten();
+        if recorded_hash_algorithm.as_deref() != Some(self.hash_algorithm.as_ref()) {
+            trace!(
+                "recorded task output files were hashed with {:?}, not {}",
+                recorded_hash_algorithm,
+                self.hash_algorithm.as_ref()
+            );
+            return HashMap::new();
+        }
+
+        let workspace_root = std::path::Path::new(&self.workspace_root);
+        let mut recorded_task_outputs = HashMap::new();
+        let mut checked_outputs = std::collections::HashSet::new();
+        for output in hash_plans
+            .values()
+            .flatten()
+            .flat_map(|instruction| match instruction {
+                HashInstruction::TaskOutput(_, outputs) => outputs.as_slice(),
+                _ => &[],
+            })
+        {
+            if !checked_outputs.insert(output) {
+                continue;
+            }
+            match get_recorded_task_output_files(&db, workspace_root, output) {
+                Ok(Some(files)) => {
+                    recorded_task_outputs.insert(output.to_string(), files);
+                }
+                Ok(None) => trace!("no recorded files for {}, reading from disk", output),
+                Err(e) => trace!("could not get recorded files for {}: {:?}", output, e),
+            }
+        }
+        recorded_task_outputs
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn get_recorded_task_outputs(
+        &self,
+        _hash_plans: &HashMap<String, Vec<HashInstruction>>,
+    ) -> HashMap<String, Vec<FileData>> {
+        HashMap::new()
+    }
+}
+
 struct HashInstructionArgs<'a> {
     js_env: &'a HashMap<String, String>,
     ts_config_hash: &'a str,
     project_root_mappings: &'a ProjectRootMappings,
     sorted_externals: &'a [&'a String],
     selectively_hash_tsconfig: bool,
+    recorded_task_outputs: &'a HashMap<String, Vec<FileData>>,
+}
+
+/// Drops the cached hashes of the instructions that depend on the changed files:
+/// the filesets of the changed projects, the workspace filesets that match a changed file,
+/// the json files that changed, and the tsconfig hashes of the projects whose tsconfig files changed
+fn invalidate_instruction_hashes(
+    instruction_cache: &DashMap<HashInstruction, String>,
+    changed_files: &[&str],
+    changed_projects: &HashSet<String>,
+    changed_tsconfig_projects: &HashSet<String>,
+) {
+    if changed_files.is_empty() {
+        return;
+    }
+
+    instruction_cache.retain(|instruction, _| match instruction {
+        HashInstruction::ProjectFileSet(project_name, _) => {
+            !changed_projects.contains(project_name)
+        }
+        HashInstruction::WorkspaceFileSet(file_sets) => {
+            match build_glob_set(&get_workspace_globs(file_sets)) {
+                Ok(glob) => !changed_files.iter().any(|file| glob.is_match(file)),
+                Err(_) => false,
+            }
+        }
+        HashInstruction::JsonFile(file, _) => !changed_files.contains(&file.as_str()),
+        HashInstruction::TsConfiguration(project_name) => {
+            !changed_tsconfig_projects.contains(project_name)
+        }
+        _ => true,
+    });
+}
+
+/// Updates the hash of a file in files sorted by path, or inserts the file where it belongs
+fn upsert_file(files: &mut Vec<FileData>, file: &str, hash: &str) {
+    match files.binary_search_by(|f| f.file.as_str().cmp(file)) {
+        Ok(i) => hash.clone_into(&mut files[i].hash),
+        Err(i) => files.insert(
+            i,
+            FileData {
+                file: file.to_string(),
+                hash: hash.to_string(),
+            },
+        ),
+    }
+}
+
+fn same_file_hashes(files: &[FileData], other_files: &[FileData]) -> bool {
+    files.len() == other_files.len()
+        && files
+            .iter()
+            .zip(other_files)
+            .all(|(file, other_file)| file.file == other_file.file && file.hash == other_file.hash)
+}
+
+fn is_within(file: &str, path: &str) -> bool {
+    file.strip_prefix(path)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native::project_graph::types::Project;
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+
+    fn file_data(file: &str) -> FileData {
+        FileData {
+            file: file.to_string(),
+            hash: "hash".to_string(),
+        }
+    }
+
+    fn create_hasher(workspace_root: &TempDir) -> TaskHasher {
+        let project_graph = ProjectGraph {
+            nodes: HashMap::from([(
+                "lib1".to_string(),
+                Project {
+                    root: "libs/lib1".to_string(),
+                    named_inputs: None,
+                    tags: None,
+                    targets: HashMap::new(),
+                },
+            )]),
+            dependencies: HashMap::new(),
+            external_nodes: HashMap::new(),
+        };
+        let project_files = vec![
+            file_data("libs/lib1/index.ts"),
+            file_data("libs/lib1/index.spec.ts"),
+            file_data("libs/lib1/README.md"),
+        ];
+        TaskHasher::with_owned_files(
+            workspace_root.display().to_string(),
+            External::new(project_graph),
+            HashMap::from([("lib1".to_string(), project_files.clone())]),
+            [project_files, vec![file_data("babel.config.json")]].concat(),
+            vec![],
+            HashMap::new(),
+            None,
+        )
+    }
+
+    fn resolve_inputs(
+        hasher: &TaskHasher,
+        instructions: Vec<HashInstruction>,
+        js_env: HashMap<String, String>,
+    ) -> ResolvedInputs {
+        hasher
+            .resolve_inputs(
+                "lib1:build".to_string(),
+                External::new(HashMap::from([("lib1:build".to_string(), instructions)])),
+                js_env,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn should_resolve_the_files_of_filesets() {
+        let temp = TempDir::new().unwrap();
+        let hasher = create_hasher(&temp);
+        let project_file_set = HashInstruction::ProjectFileSet(
+            "lib1".to_string(),
+            vec![
+                "{projectRoot}/**/*.ts".to_string(),
+                "!{projectRoot}/**/*.spec.ts".to_string(),
+            ],
+        );
+        let workspace_file_set = HashInstruction::WorkspaceFileSet(vec![
+            "{workspaceRoot}/babel.config.json".to_string(),
+            "{workspaceRoot}/jest.config.ts".to_string(),
+        ]);
+
+        let resolved_inputs = resolve_inputs(
+            &hasher,
+            vec![project_file_set.clone(), workspace_file_set.clone()],
+            HashMap::new(),
+        );
+
+        assert_eq!(
+            resolved_inputs.files[&project_file_set.to_string()],
+            vec!["libs/lib1/index.ts"]
+        );
+        assert_eq!(
+            resolved_inputs.files[&workspace_file_set.to_string()],
+            vec!["babel.config.json"]
+        );
+        assert_eq!(
+            resolved_inputs.warnings,
+            vec!["{workspaceRoot}/jest.config.ts does not match any files"]
+        );
+    }
+
+    #[test]
+    fn should_resolve_the_environment_variables_that_are_set() {
+        let temp = TempDir::new().unwrap();
+        let hasher = create_hasher(&temp);
+
+        let resolved_inputs = resolve_inputs(
+            &hasher,
+            vec![
+                HashInstruction::Environment("VITE_*".to_string()),
+                HashInstruction::Environment("NODE_ENV".to_string()),
+                HashInstruction::Environment("CI".to_string()),
+            ],
+            HashMap::from([
+                ("VITE_B".to_string(), "b".to_string()),
+                ("VITE_A".to_string(), "a".to_string()),
+                ("NODE_ENV".to_string(), "production".to_string()),
+                ("HOME".to_string(), "/home".to_string()),
+            ]),
+        );
+
+        assert_eq!(
+            resolved_inputs.environment,
+            vec!["NODE_ENV", "VITE_A", "VITE_B"]
+        );
+    }
+
+    #[test]
+    fn should_resolve_runtime_inputs_without_running_them() {
+        let temp = TempDir::new().unwrap();
+        let hasher = create_hasher(&temp);
+
+        let resolved_inputs = resolve_inputs(
+            &hasher,
+            vec![
+                HashInstruction::Runtime("node -v".to_string()),
+                HashInstruction::Runtime("exit 1".to_string()),
+            ],
+            HashMap::new(),
+        );
+
+        assert_eq!(resolved_inputs.runtime, vec!["node -v", "exit 1"]);
+    }
+
+    #[test]
+    fn should_resolve_the_files_of_dependent_task_outputs() {
+        let temp = TempDir::new().unwrap();
+        temp.child("dist/libs/lib2/index.js")
+            .write_str("content")
+            .unwrap();
+        temp.child("dist/libs/lib2/index.d.ts")
+            .write_str("content")
+            .unwrap();
+        let hasher = create_hasher(&temp);
+        let task_output = HashInstruction::TaskOutput(
+            "**/*.d.ts".to_string(),
+            vec!["dist/libs/lib2".to_string()],
+        );
+
+        let resolved_inputs = resolve_inputs(&hasher, vec![task_output.clone()], HashMap::new());
+
+        assert_eq!(
+            resolved_inputs.files[&task_output.to_string()],
+            vec!["dist/libs/lib2/index.d.ts"]
+        );
+    }
+
+    #[test]
+    fn should_rehash_tsconfigs_when_a_file_of_their_extends_chain_changes() {
+        let temp = TempDir::new().unwrap();
+        temp.child("libs/lib1/tsconfig.json")
+            .write_str(r#"{ "extends": "../../config/ts-base.json" }"#)
+            .unwrap();
+        temp.child("config/ts-base.json")
+            .write_str(r#"{ "compilerOptions": { "target": "ES2021" } }"#)
+            .unwrap();
+        let mut hasher = create_hasher(&temp);
+        hasher.options = Some(HasherOptions {
+            selectively_hash_ts_config: true,
+            hash_algorithm: None,
+        });
+        let hash_tsconfig = |hasher: &TaskHasher| {
+            hasher
+                .hash_plans(
+                    External::new(HashMap::from([(
+                        "lib1:build".to_string(),
+                        vec![HashInstruction::TsConfiguration("lib1".to_string())],
+                    )])),
+                    HashMap::new(),
+                )
+                .unwrap()
+                .remove("lib1:build")
+                .unwrap()
+                .1
+                .value
+        };
+        let hash = hash_tsconfig(&hasher);
+
+        temp.child("libs/lib1/README.md")
+            .write_str("changed")
+            .unwrap();
+        hasher
+            .update_files(
+                &HashMap::from([("libs/lib1/README.md".to_string(), "changed".to_string())]),
+                &[],
+            )
+            .unwrap();
+        assert_eq!(hash_tsconfig(&hasher), hash);
+
+        temp.child("config/ts-base.json")
+            .write_str(r#"{ "compilerOptions": { "target": "ES2022" } }"#)
+            .unwrap();
+        hasher
+            .update_files(
+                &HashMap::from([("config/ts-base.json".to_string(), "changed".to_string())]),
+                &[],
+            )
+            .unwrap();
+        assert_ne!(hash_tsconfig(&hasher), hash);
+    }
 }
@@ -0,0 +1,422 @@
+use std::collections::{HashMap, HashSet};
+
+use napi::bindgen_prelude::External;
+use tracing::trace;
+
+use crate::native::project_graph::types::ProjectGraph;
+use crate::native::tasks::types::TaskGraph;
+
+/// The parts of the task graph that do not change while the tasks run
+struct SchedulePlan {
+    parallel: usize,
+    dependents: HashMap<String, Vec<String>>,
+    durations: HashMap<String, f64>,
+    /// The longest estimated time from the start of a task until every task that depends on it has finished
+    priorities: HashMap<String, f64>,
+    /// Tasks of targets with `parallelism: false`, which run alone
+    non_parallel_tasks: HashSet<String>,
+}
+
+#[derive(Clone)]
+struct ScheduleState {
+    /// The number of unfinished dependencies of each task that has not started
+    pending: HashMap<String, usize>,
+    running: HashSet<String>,
+}
+
+impl SchedulePlan {
+    fn new(
+        task_graph: &TaskGraph,
+        project_graph: &ProjectGraph,
+        estimated_timings: &HashMap<String, f64>,
+        parallel: usize,
+    ) -> Self {
+        let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+        for (task_id, dependencies) in &task_graph.dependencies {
+            for dependency in dependencies {
+                dependents
+                    .entry(dependency.clone())
+                    .or_default()
+                    .push(task_id.clone());
+            }
+        }
+
+        // tasks that never ran before are expected to take as long as the average task
+        let known_timings = task_graph
+            .tasks
+            .keys()
+            .filter_map(|id| estimated_timings.get(id))
+            .collect::<Vec<_>>();
+        let default_duration = if known_timings.is_empty() {
+            1.0
+        } else {
+            known_timings.iter().copied().sum::<f64>() / known_timings.len() as f64
+        };
+        let durations = task_graph
+            .tasks
+            .keys()
+            .map(|id| {
+                let duration = estimated_timings
+                    .get(id)
+                    .copied()
+                    .unwrap_or(default_duration);
+                (id.clone(), duration)
+            })
+            .collect::<HashMap<_, _>>();
+
+        let non_parallel_tasks = task_graph
+            .tasks
+            .values()
+            .filter(|task| {
+                project_graph
+                    .nodes
+                    .get(&task.target.project)
+                    .and_then(|project| project.targets.get(&task.target.target))
+                    .is_some_and(|target| target.parallelism == Some(false))
+            })
+            .map(|task| task.id.clone())
+            .collect();
+
+        let mut priorities = HashMap::new();
+        let mut visiting = HashSet::new();
+        for task_id in task_graph.tasks.keys() {
+            get_priority(
+                task_id,
+                &dependents,
+                &durations,
+                &mut priorities,
+                &mut visiting,
+            );
+        }
+
+        Self {
+            parallel: parallel.max(1),
+            dependents,
+            durations,
+            priorities,
+            non_parallel_tasks,
+        }
+    }
+
+    /// Starts the tasks that can run now, the ones on the longest remaining paths first
+    fn next_tasks(&self, state: &mut ScheduleState) -> Vec<String> {
+        if state
+            .running
+            .iter()
+            .any(|id| self.non_parallel_tasks.contains(id))
+        {
+            return vec![];
+        }
+
+        let mut ready = state
+            .pending
+            .iter()
+            .filter(|(_, dependencies)| **dependencies == 0)
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        ready.sort_by(|a, b| {
+            self.priorities[b]
+                .total_cmp(&self.priorities[a])
+                .then_with(|| a.cmp(b))
+        });
+
+        let mut scheduled = vec![];
+        for id in ready {
+            if state.running.len() >= self.parallel {
+                break;
+            }
+            if self.non_parallel_tasks.contains(&id) {
+                // waits for the running tasks to finish, instead of letting shorter paths start first
+                if state.running.is_empty() {
+                    state.pending.remove(&id);
+                    state.running.insert(id.clone());
+                    scheduled.push(id);
+                }
+                break;
+            }
+            state.pending.remove(&id);
+            state.running.insert(id.clone());
+            scheduled.push(id);
+        }
+        scheduled
+    }
+
+    fn complete_task(&self, state: &mut ScheduleState, task_id: &str) {
+        if !state.running.remove(task_id) {
+            return;
+        }
+        for dependent in self.dependents.get(task_id).into_iter().flatten() {
+            if let Some(dependencies) = state.pending.get_mut(dependent) {
+                *dependencies = dependencies.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Simulates running the tasks with their estimated durations
+    fn predict_wall_time(&self, state: &ScheduleState) -> f64 {
+        let mut state = state.clone();
+        let mut time = 0.0;
+        let mut running: Vec<(f64, String)> = vec![];
+        loop {
+            for id in self.next_tasks(&mut state) {
+                running.push((time + self.durations[&id], id));
+            }
+
+            let Some(next) = running
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)))
+                .map(|(i, _)| i)
+            else {
+                break;
+            };
+            let (finished_at, id) = running.swap_remove(next);
+            time = finished_at;
+            self.complete_task(&mut state, &id);
+        }
+        time
+    }
+}
+
+fn get_priority(
+    task_id: &str,
+    dependents: &HashMap<String, Vec<String>>,
+    durations: &HashMap<String, f64>,
+    priorities: &mut HashMap<String, f64>,
+    visiting: &mut HashSet<String>,
+) -> f64 {
+    if let Some(priority) = priorities.get(task_id) {
+        return *priority;
+    }
+    // circular dependencies never become runnable, so they do not add to the path
+    if !visiting.insert(task_id.to_string()) {
+        return 0.0;
+    }
+
+    let longest_dependent_path = dependents
+        .get(task_id)
+        .into_iter()
+        .flatten()
+        .map(|dependent| get_priority(dependent, dependents, durations, priorities, visiting))
+        .fold(0.0, f64::max);
+    let priority = durations.get(task_id).copied().unwrap_or_default() + longest_dependent_path;
+
+    visiting.remove(task_id);
+    priorities.insert(task_id.to_string(), priority);
+    priority
+}
+
+/// Schedules the tasks of a task graph, starting the tasks on the longest paths through the graph first,
+/// so that long chains of tasks do not start late.
+/// Durations are the estimated task timings of the task history, by task id.
+#[napi]
+pub struct TaskScheduler {
+    plan: SchedulePlan,
+    state: ScheduleState,
+    predicted_wall_time: f64,
+}
+
+#[napi]
+impl TaskScheduler {
+    #[napi(constructor)]
+    pub fn new(
+        task_graph: TaskGraph,
+        project_graph: External<ProjectGraph>,
+        estimated_timings: HashMap<String, f64>,
+        parallel: u32,
+    ) -> Self {
+        Self::with_project_graph(
+            &task_graph,
+            &project_graph,
+            &estimated_timings,
+            parallel as usize,
+        )
+    }
+
+    /// Gets the tasks that can start now, and marks them as running.
+    /// Returns nothing when the running tasks need to finish first.
+    #[napi]
+    pub fn next_tasks(&mut self) -> Vec<String> {
+        let tasks = self.plan.next_tasks(&mut self.state);
+        trace!("scheduled {:?}", tasks);
+        tasks
+    }
+
+    #[napi]
+    pub fn complete_tasks(&mut self, task_ids: Vec<String>) {
+        for task_id in task_ids {
+            self.plan.complete_task(&mut self.state, &task_id);
+        }
+    }
+
+    /// Whether every task has been scheduled and completed
+    #[napi]
+    pub fn is_complete(&self) -> bool {
+        self.state.running.is_empty() && self.state.pending.is_empty()
+    }
+
+    /// The estimated time to run every task of the task graph, in the unit of the estimated timings
+    #[napi]
+    pub fn get_predicted_wall_time(&self) -> f64 {
+        self.predicted_wall_time
+    }
+}
+
+impl TaskScheduler {
+    fn with_project_graph(
+        task_graph: &TaskGraph,
+        project_graph: &ProjectGraph,
+        estimated_timings: &HashMap<String, f64>,
+        parallel: usize,
+    ) -> Self {
+        let plan = SchedulePlan::new(task_graph, project_graph, estimated_timings, parallel);
+        let state = ScheduleState {
+            pending: task_graph
+                .tasks
+                .keys()
+                .map(|id| {
+                    let dependencies = task_graph.dependencies.get(id).map_or(0, Vec::len);
+                    (id.clone(), dependencies)
+                })
+                .collect(),
+            running: HashSet::new(),
+        };
+        let predicted_wall_time = plan.predict_wall_time(&state);
+
+        Self {
+            plan,
+            state,
+            predicted_wall_time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native::project_graph::types::{Project, Target};
+    use crate::native::tasks::types::{Task, TaskTarget};
+
+    fn create_task_graph(dependencies: &[(&str, &[&str])]) -> TaskGraph {
+        TaskGraph {
+            roots: vec![],
+            tasks: dependencies
+                .iter()
+                .map(|(id, _)| {
+                    let (project, target) = id.split_once(':').unwrap();
+                    (
+                        id.to_string(),
+                        Task {
+                            id: id.to_string(),
+                            target: TaskTarget {
+                                project: project.to_string(),
+                                target: target.to_string(),
+                                configuration: None,
+                            },
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            dependencies: dependencies
+                .iter()
+                .map(|(id, deps)| (id.to_string(), deps.iter().map(|d| d.to_string()).collect()))
+                .collect(),
+        }
+    }
+
+    fn create_project_graph(non_parallel: &[&str]) -> ProjectGraph {
+        let project = |name: &str| Project {
+            root: name.into(),
+            targets: ["build", "test", "e2e"]
+                .into_iter()
+                .map(|target| {
+                    (
+                        target.to_string(),
+                        Target {
+                            parallelism: Some(!non_parallel.contains(&target)),
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        };
+        ProjectGraph {
+            nodes: ["a", "b", "c", "d"]
+                .into_iter()
+                .map(|name| (name.to_string(), project(name)))
+                .collect(),
+            dependencies: HashMap::new(),
+            external_nodes: HashMap::new(),
+        }
+    }
+
+    fn timings(timings: &[(&str, f64)]) -> HashMap<String, f64> {
+        timings
+            .iter()
+            .map(|(id, timing)| (id.to_string(), *timing))
+            .collect()
+    }
+
+    #[test]
+    fn should_start_the_tasks_on_the_critical_path_first() {
+        // a:test is quick, but a:e2e which depends on it takes longer than anything else
+        let task_graph = create_task_graph(&[
+            ("a:test", &[]),
+            ("a:e2e", &["a:test"]),
+            ("b:build", &[]),
+            ("c:build", &[]),
+        ]);
+        let mut scheduler = TaskScheduler::with_project_graph(
+            &task_graph,
+            &create_project_graph(&[]),
+            &timings(&[
+                ("a:test", 1.0),
+                ("a:e2e", 10.0),
+                ("b:build", 5.0),
+                ("c:build", 3.0),
+            ]),
+            2,
+        );
+
+        assert_eq!(scheduler.get_predicted_wall_time(), 11.0);
+        assert_eq!(scheduler.next_tasks(), vec!["a:test", "b:build"]);
+        assert!(scheduler.next_tasks().is_empty());
+
+        scheduler.complete_tasks(vec!["a:test".into()]);
+        assert_eq!(scheduler.next_tasks(), vec!["a:e2e"]);
+
+        scheduler.complete_tasks(vec!["b:build".into()]);
+        assert_eq!(scheduler.next_tasks(), vec!["c:build"]);
+
+        scheduler.complete_tasks(vec!["a:e2e".into(), "c:build".into()]);
+        assert!(scheduler.is_complete());
+    }
+
+    #[test]
+    fn should_run_tasks_without_parallelism_alone() {
+        let task_graph = create_task_graph(&[
+            ("a:build", &[]),
+            ("b:build", &[]),
+            ("c:e2e", &[]),
+            ("d:e2e", &[]),
+        ]);
+        let mut scheduler = TaskScheduler::with_project_graph(
+            &task_graph,
+            &create_project_graph(&["e2e"]),
+            &timings(&[("c:e2e", 4.0), ("d:e2e", 2.0)]),
+            3,
+        );
+
+        // builds have no timings, so they are expected to take the average of 3
+        assert_eq!(scheduler.get_predicted_wall_time(), 9.0);
+        assert_eq!(scheduler.next_tasks(), vec!["c:e2e"]);
+        assert!(scheduler.next_tasks().is_empty());
+
+        scheduler.complete_tasks(vec!["c:e2e".into()]);
+        assert_eq!(scheduler.next_tasks(), vec!["a:build", "b:build"]);
+        scheduler.complete_tasks(vec!["a:build".into(), "b:build".into()]);
+        assert_eq!(scheduler.next_tasks(), vec!["d:e2e"]);
+    }
+}
@@ -13,6 +13,8 @@ pub struct Task {
     pub target: TaskTarget,
     pub outputs: Vec<String>,
     pub project_root: Option<String>,
+    /// Options passed on the command line, which override the options of the target
+    pub overrides: Option<serde_json::Value>,
 }
 
 #[napi(object)]
@@ -30,17 +32,32 @@ pub struct TaskGraph {
     pub dependencies: HashMap<String, Vec<String>>,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+#[archive(check_bytes)]
 pub enum HashInstruction {
     WorkspaceFileSet(Vec<String>),
     Runtime(String),
     Environment(String),
+    JsonFile(String, String),
     ProjectFileSet(String, Vec<String>),
     ProjectConfiguration(String),
     TsConfiguration(String),
     TaskOutput(String, Vec<String>),
     External(String),
     AllExternalDependencies,
+    /// The task id and its resolved options, serialized as canonical JSON
+    TaskOptions(String, String),
 }
 
 impl ToNapiValue for HashInstruction {
@@ -77,6 +94,7 @@ impl fmt::Display for HashInstruction {
                     format!("workspace:[{}]", file_set.join(",")),
                 HashInstruction::Runtime(runtime) => format!("runtime:{}", runtime),
                 HashInstruction::Environment(env) => format!("env:{}", env),
+                HashInstruction::JsonFile(file, pointer) => format!("json:{file}#{pointer}"),
                 HashInstruction::TaskOutput(task_output, dep_outputs) => {
                     let dep_outputs = dep_outputs.join(",");
                     format!("{task_output}:{dep_outputs}")
@@ -88,6 +106,7 @@ impl fmt::Display for HashInstruction {
                 HashInstruction::TsConfiguration(project_name) => {
                     format!("{project_name}:TsConfig")
                 }
+                HashInstruction::TaskOptions(task_id, _) => format!("{task_id}:Options"),
             }
         )
     }
@@ -24,6 +24,8 @@ export function transformProjectGraphForRust(
         options: JSON.stringify(targetConfig.options),
         configurations: JSON.stringify(targetConfig.configurations),
         parallelism: targetConfig.parallelism,
+        defaultConfiguration: targetConfig.defaultConfiguration,
+        dependsOn: targetConfig.dependsOn,
       };
     }
     nodes[projectName] = {
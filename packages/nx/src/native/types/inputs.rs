@@ -1,4 +1,4 @@
-use napi::bindgen_prelude::Either7;
+use napi::bindgen_prelude::Either8;
 use napi::Either;
 
 #[napi(object)]
@@ -6,6 +6,8 @@ pub struct InputsInput {
     pub input: String,
     pub dependencies: Option<bool>,
     pub projects: Option<Either<String, Vec<String>>>,
+    /// When set, the input is only used by tasks that run with one of these configurations
+    pub configurations: Option<Vec<String>>,
 }
 
 #[napi(object)]
@@ -23,6 +25,13 @@ pub struct EnvironmentInput {
     pub env: String,
 }
 
+#[napi(object)]
+pub struct JsonInput {
+    /// A json file and a JSON pointer to the value in it that is hashed,
+    /// e.g. `{workspaceRoot}/package.json#/dependencies/react`
+    pub json: String,
+}
+
 #[napi(object)]
 pub struct ExternalDependenciesInput {
     pub external_dependencies: Vec<String>,
@@ -34,7 +43,7 @@ pub struct DepsOutputsInput {
     pub transitive: Option<bool>,
 }
 
-pub(crate) type JsInputs = Either7<
+pub(crate) type JsInputs = Either8<
     InputsInput,
     String,
     FileSetInput,
@@ -42,12 +51,13 @@ pub(crate) type JsInputs = Either7<
     EnvironmentInput,
     ExternalDependenciesInput,
     DepsOutputsInput,
+    JsonInput,
 >;
 
 impl<'a> From<&'a JsInputs> for Input<'a> {
     fn from(value: &'a JsInputs) -> Self {
         match value {
-            Either7::A(inputs) => {
+            Either8::A(inputs) => {
                 if let Some(projects) = &inputs.projects {
                     Input::Projects {
                         input: &inputs.input,
@@ -63,7 +73,7 @@ impl<'a> From<&'a JsInputs> for Input<'a> {
                     }
                 }
             }
-            Either7::B(string) => {
+            Either8::B(string) => {
                 if let Some(input) = string.strip_prefix('^') {
                     Input::Inputs {
                         input,
@@ -73,21 +83,22 @@ impl<'a> From<&'a JsInputs> for Input<'a> {
                     Input::String(string)
                 }
             }
-            Either7::C(file_set) => Input::FileSet(&file_set.fileset),
-            Either7::D(runtime) => Input::Runtime(&runtime.runtime),
-            Either7::E(environment) => Input::Environment(&environment.env),
-            Either7::F(external_dependencies) => {
+            Either8::C(file_set) => Input::FileSet(&file_set.fileset),
+            Either8::D(runtime) => Input::Runtime(&runtime.runtime),
+            Either8::E(environment) => Input::Environment(&environment.env),
+            Either8::F(external_dependencies) => {
                 Input::ExternalDependency(&external_dependencies.external_dependencies)
             }
-            Either7::G(deps_outputs) => Input::DepsOutputs {
+            Either8::G(deps_outputs) => Input::DepsOutputs {
                 transitive: deps_outputs.transitive.unwrap_or(false),
                 dependent_tasks_output_files: &deps_outputs.dependent_tasks_output_files,
             },
+            Either8::H(json) => Input::Json(&json.json),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub(crate) enum Input<'a> {
     Inputs {
         input: &'a str,
@@ -97,6 +108,7 @@ pub(crate) enum Input<'a> {
     FileSet(&'a str),
     Runtime(&'a str),
     Environment(&'a str),
+    Json(&'a str),
     ExternalDependency(&'a [String]),
     DepsOutputs {
         dependent_tasks_output_files: &'a str,
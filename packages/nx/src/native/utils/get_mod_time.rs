@@ -23,3 +23,30 @@ pub fn get_mod_time(metadata: &Metadata) -> i64 {
     use std::os::wasi::fs::MetadataExt;
     metadata.mtim() as i64
 }
+
+/// The inode and status change time of a file, which change even when its mod time is restored
+#[cfg(target_os = "macos")]
+pub fn get_inode_and_change_time(metadata: &Metadata) -> (u64, i64) {
+    use std::os::macos::fs::MetadataExt;
+    (metadata.st_ino(), metadata.st_ctime())
+}
+
+/// Windows does not have inodes or status change times
+#[cfg(target_os = "windows")]
+pub fn get_inode_and_change_time(_metadata: &Metadata) -> (u64, i64) {
+    (0, 0)
+}
+
+/// The inode and status change time of a file, which change even when its mod time is restored
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+pub fn get_inode_and_change_time(metadata: &Metadata) -> (u64, i64) {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.ino(), metadata.ctime())
+}
+
+/// The inode and status change time of a file, which change even when its mod time is restored
+#[cfg(target_os = "wasi")]
+pub fn get_inode_and_change_time(metadata: &Metadata) -> (u64, i64) {
+    use std::os::wasi::fs::MetadataExt;
+    (metadata.ino(), metadata.ctim() as i64)
+}
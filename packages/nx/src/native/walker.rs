@@ -1,3 +1,4 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::WalkBuilder;
 use std::fmt::Debug;
 use std::path::{Path, PathBuf};
@@ -5,20 +6,181 @@ use std::path::{Path, PathBuf};
 use crate::native::glob::build_glob_set;
 
 use crate::native::logger::enable_logger;
-use crate::native::utils::{get_mod_time, Normalize};
+use crate::native::utils::{get_inode_and_change_time, get_mod_time, Normalize};
+use tracing::warn;
 use walkdir::WalkDir;
 
-#[derive(PartialEq, Debug, Ord, PartialOrd, Eq, Clone)]
+#[derive(PartialEq, Debug, Default, Ord, PartialOrd, Eq, Clone)]
 pub struct NxFile {
     pub full_path: String,
     pub normalized_path: String,
     pub mod_time: i64,
+    pub size: u64,
+    pub inode: u64,
+    pub change_time: i64,
+    /// Where the file links to, for symlinks that are recorded instead of followed
+    pub link_target: Option<String>,
 }
 
-/// Walks the directory in a single thread and does not ignore any files
+/// How the walker treats symlinks
+#[napi(string_enum)]
+#[derive(Debug, Default, PartialEq)]
+pub enum SymlinkPolicy {
+    /// Symlinks to files are hashed by the content of the file, symlinks to directories are skipped
+    #[allow(non_camel_case_types)]
+    #[default]
+    content,
+    /// Symlinks to files and directories within the directory are followed, symlinks that loop are skipped
+    #[allow(non_camel_case_types)]
+    follow,
+    /// Symlinks are not followed, and are hashed by the path that they link to
+    #[allow(non_camel_case_types)]
+    record,
+}
+
+/// Ignored by the walker and the watcher even without ignore files, in the format of .gitignore files
+pub const DEFAULT_IGNORE_GLOBS: &[&str] = &[
+    "node_modules",
+    ".git",
+    "**/.nx/cache",
+    "**/.nx/workspace-data",
+    "**/.yarn/cache",
+    "vitest.config.ts.timestamp*.mjs",
+    "vite.config.ts.timestamp*.mjs",
+];
+
+/// The ignore files that are read by default, where `.nxignore` can include files that `.gitignore` ignores
+pub const DEFAULT_IGNORE_FILES: &[&str] = &[".gitignore", ".nxignore"];
+
+/// Which files are ignored. The workspace context and the watcher take the same options, so that they agree on which files exist.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreOptions {
+    /// Names of the ignore files that apply to their directory and below, `.gitignore` and `.nxignore` by default.
+    /// Later files take precedence over earlier ones, and `.gitignore` files always take the least precedence.
+    pub ignore_files: Option<Vec<String>>,
+    /// Globs that are ignored besides `node_modules`, `.git`, `.nx/cache`, `.nx/workspace-data` and `.yarn/cache`,
+    /// in the format of .gitignore files
+    pub ignore_globs: Option<Vec<String>>,
+}
+
+impl IgnoreOptions {
+    pub fn ignore_files(&self) -> Vec<String> {
+        self.ignore_files.clone().unwrap_or_else(|| {
+            DEFAULT_IGNORE_FILES
+                .iter()
+                .map(|file| file.to_string())
+                .collect()
+        })
+    }
+
+    /// The default globs followed by the configured ones, so that the configured ones can include ignored files
+    pub fn ignore_globs(&self) -> Vec<String> {
+        DEFAULT_IGNORE_GLOBS
+            .iter()
+            .map(|glob| glob.to_string())
+            .chain(self.ignore_globs.iter().flatten().cloned())
+            .collect()
+    }
+
+    /// Matches the ignore globs like a .gitignore file in the directory
+    pub fn build_glob_matcher(&self, directory: &Path) -> anyhow::Result<Gitignore> {
+        let mut builder = GitignoreBuilder::new(directory);
+        for glob in self.ignore_globs() {
+            builder.add_line(None, &glob)?;
+        }
+        Ok(builder.build()?)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WalkOptions {
+    pub symlinks: SymlinkPolicy,
+    /// Whether to walk git submodules and nested repositories
+    pub include_submodules: bool,
+    /// Whether to walk nested workspaces, which are directories with their own nx.json
+    pub include_nested_workspaces: bool,
+    pub ignores: IgnoreOptions,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            symlinks: SymlinkPolicy::default(),
+            include_submodules: true,
+            include_nested_workspaces: true,
+            ignores: IgnoreOptions::default(),
+        }
+    }
+}
+
+/// Whether the directory is the root of a workspace, because it has its own nx.json
+pub fn is_workspace_root(directory: &Path) -> bool {
+    directory.join("nx.json").is_file()
+}
+
+impl NxFile {
+    fn new(full_path: &Path, normalized_path: String, metadata: &std::fs::Metadata) -> Self {
+        let (inode, change_time) = get_inode_and_change_time(metadata);
+        NxFile {
+            full_path: String::from(full_path.to_string_lossy()),
+            normalized_path,
+            mod_time: get_mod_time(metadata),
+            size: metadata.len(),
+            inode,
+            change_time,
+            link_target: None,
+        }
+    }
+}
+
+fn to_nx_file(
+    dir_entry: &ignore::DirEntry,
+    directory: &Path,
+    options: &WalkOptions,
+) -> Option<NxFile> {
+    if dir_entry.file_type().is_some_and(|d| d.is_dir()) {
+        return None;
+    }
+
+    let file_path = dir_entry.path().strip_prefix(directory).ok()?;
+    // the stats of symlinks that are hashed by their content have to be the stats of the content,
+    // so that the hashes of unchanged files are not reused when the file they link to changes
+    let metadata = if options.symlinks == SymlinkPolicy::content && dir_entry.path_is_symlink() {
+        let metadata = std::fs::metadata(dir_entry.path()).ok()?;
+        if metadata.is_dir() {
+            return None;
+        }
+        metadata
+    } else {
+        dir_entry.metadata().ok()?
+    };
+    let mut file = NxFile::new(
+        dir_entry.path(),
+        file_path.to_normalized_string(),
+        &metadata,
+    );
+    if options.symlinks == SymlinkPolicy::record && dir_entry.path_is_symlink() {
+        let link_target = std::fs::read_link(dir_entry.path()).ok()?;
+        file.link_target = Some(link_target.to_normalized_string());
+    }
+    Some(file)
+}
+
+/// Walk the directory and ignore files from .gitignore and .nxignore
+pub fn nx_walker<P>(directory: P, use_ignores: bool) -> impl Iterator<Item = NxFile>
+where
+    P: AsRef<Path>,
+{
+    nx_walker_with_options(directory, use_ignores, WalkOptions::default())
+}
+
+/// Walks the directory in a single thread and does not read any ignore files
 /// Should only be used for small directories, and not traversing the whole workspace
 ///
-/// The `ignores` argument is used to filter entries. This is important to make sure that any ignore globs are applied on the `filter_entry` function
+/// The default ignore globs are applied in the `filter_entry` function, in the format of .gitignore files in the directory,
+/// so that ignored directories are not walked. The `ignores` argument is applied there too, as globs that can match
+/// anywhere in the path of an entry.
 pub fn nx_walker_sync<'a, P>(
     directory: P,
     ignores: Option<&[String]>,
@@ -28,26 +190,31 @@ where
 {
     let base_dir: PathBuf = directory.as_ref().into();
 
-    let mut base_ignores: Vec<String> = vec![
-        "**/node_modules".into(),
-        "**/.git".into(),
-        "**/.nx/cache".into(),
-        "**/.nx/workspace-data".into(),
-        "**/.yarn/cache".into(),
-    ];
-
-    if let Some(additional_ignores) = ignores {
-        base_ignores.extend(additional_ignores.iter().map(|s| format!("**/{}", s)));
-    };
-
-    let ignore_glob_set = build_glob_set(&base_ignores).expect("Should be valid globs");
+    let default_ignores = IgnoreOptions::default()
+        .build_glob_matcher(&base_dir)
+        .expect("The default ignore globs always build");
+    let ignore_glob_set = ignores
+        .filter(|ignores| !ignores.is_empty())
+        .map(|ignores| {
+            let ignores = ignores
+                .iter()
+                .map(|s| format!("**/{}", s))
+                .collect::<Vec<_>>();
+            build_glob_set(&ignores).expect("Should be valid globs")
+        });
 
     // Use WalkDir instead of ignore::WalkBuilder because it's faster
     WalkDir::new(&base_dir)
         .into_iter()
         .filter_entry(move |entry| {
-            let path = entry.path().to_string_lossy();
-            !ignore_glob_set.is_match(path.as_ref())
+            let ignored_by_default = entry.depth() > 0
+                && default_ignores
+                    .matched(entry.path(), entry.file_type().is_dir())
+                    .is_ignore();
+            let ignored = ignore_glob_set.as_ref().is_some_and(|glob_set| {
+                glob_set.is_match(entry.path().to_string_lossy().as_ref())
+            });
+            !ignored_by_default && !ignored
         })
         .filter_map(move |entry| {
             entry
@@ -61,14 +228,18 @@ where
         })
 }
 
-/// Walk the directory and ignore files from .gitignore and .nxignore
+/// Walk the directory with the symlink and submodule options, and ignore files from .gitignore and .nxignore
 #[cfg(target_arch = "wasm32")]
-pub fn nx_walker<P>(directory: P, use_ignores: bool) -> impl Iterator<Item = NxFile>
+pub fn nx_walker_with_options<P>(
+    directory: P,
+    use_ignores: bool,
+    options: WalkOptions,
+) -> impl Iterator<Item = NxFile>
 where
     P: AsRef<Path>,
 {
     let directory: PathBuf = directory.as_ref().into();
-    let walker = create_walker(&directory, use_ignores);
+    let walker = create_walker(&directory, use_ignores, &options);
 
     let entries = walker.build();
 
@@ -77,29 +248,17 @@ where
             return None;
         };
 
-        if dir_entry.file_type().is_some_and(|d| d.is_dir()) {
-            return None;
-        }
-
-        let Ok(file_path) = dir_entry.path().strip_prefix(&directory) else {
-            return None;
-        };
-
-        let Ok(metadata) = dir_entry.metadata() else {
-            return None;
-        };
-
-        Some(NxFile {
-            full_path: String::from(dir_entry.path().to_string_lossy()),
-            normalized_path: file_path.to_normalized_string(),
-            mod_time: get_mod_time(&metadata),
-        })
+        to_nx_file(&dir_entry, &directory, &options)
     })
 }
 
-/// Walk the directory and ignore files from .gitignore and .nxignore
+/// Walk the directory with the symlink and submodule options, and ignore files from .gitignore and .nxignore
 #[cfg(not(target_arch = "wasm32"))]
-pub fn nx_walker<P>(directory: P, use_ignores: bool) -> impl Iterator<Item = NxFile>
+pub fn nx_walker_with_options<P>(
+    directory: P,
+    use_ignores: bool,
+    options: WalkOptions,
+) -> impl Iterator<Item = NxFile>
 where
     P: AsRef<Path>,
 {
@@ -111,11 +270,12 @@ where
     enable_logger();
 
     let directory = directory.as_ref();
-    let mut walker = create_walker(directory, use_ignores);
+    let mut walker = create_walker(directory, use_ignores, &options);
 
     let cpus = available_parallelism().map_or(2, |n| n.get()) - 1;
 
     let (sender, receiver) = unbounded();
+    let options = &options;
 
     trace!(?directory, "walking");
 
@@ -129,25 +289,10 @@ where
                 return Continue;
             };
 
-            if dir_entry.file_type().is_some_and(|d| d.is_dir()) {
-                return Continue;
+            if let Some(file) = to_nx_file(&dir_entry, directory, options) {
+                tx.send(file).ok();
             }
 
-            let Ok(file_path) = dir_entry.path().strip_prefix(directory) else {
-                return Continue;
-            };
-
-            let Ok(metadata) = dir_entry.metadata() else {
-                return Continue;
-            };
-
-            tx.send(NxFile {
-                full_path: String::from(dir_entry.path().to_string_lossy()),
-                normalized_path: file_path.to_normalized_string(),
-                mod_time: get_mod_time(&metadata),
-            })
-            .ok();
-
             Continue
         })
     });
@@ -158,39 +303,165 @@ where
     receiver_thread.join().unwrap()
 }
 
-fn create_walker<P>(directory: P, use_ignores: bool) -> WalkBuilder
+fn create_walker<P>(directory: P, use_ignores: bool, options: &WalkOptions) -> WalkBuilder
 where
     P: AsRef<Path>,
 {
     let directory: PathBuf = directory.as_ref().into();
 
-    let ignore_glob_set = build_glob_set(&[
-        "**/node_modules",
-        "**/.git",
-        "**/.nx/cache",
-        "**/.nx/workspace-data",
-        "**/.yarn/cache",
-    ])
-    .expect("These static ignores always build");
+    let ignore_globs = options
+        .ignores
+        .build_glob_matcher(&directory)
+        .unwrap_or_else(|e| {
+            warn!("invalid ignore globs, only the default globs are ignored: {e:?}");
+            IgnoreOptions::default()
+                .build_glob_matcher(&directory)
+                .expect("The default ignore globs always build")
+        });
 
     let mut walker = WalkBuilder::new(&directory);
     walker.require_git(false);
     walker.hidden(false);
-    walker.git_ignore(use_ignores);
+    let ignore_files = options.ignores.ignore_files();
+    walker.git_ignore(use_ignores && ignore_files.iter().any(|file| file == ".gitignore"));
     if use_ignores {
-        walker.add_custom_ignore_filename(".nxignore");
+        for ignore_file in ignore_files.iter().filter(|file| *file != ".gitignore") {
+            walker.add_custom_ignore_filename(ignore_file);
+        }
     }
 
+    let follow_symlinks = options.symlinks == SymlinkPolicy::follow;
+    walker.follow_links(follow_symlinks);
+    let canonical_directory = directory.canonicalize().ok();
+    let include_submodules = options.include_submodules;
+    let include_nested_workspaces = options.include_nested_workspaces;
+
     // We should make sure to always ignore node_modules and the .git folder
     walker.filter_entry(move |entry| {
-        let path = entry.path().to_string_lossy();
-        !ignore_glob_set.is_match(path.as_ref())
+        let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+        if entry.depth() > 0 && ignore_globs.matched(entry.path(), is_dir).is_ignore() {
+            return false;
+        }
+
+        // symlinks out of the directory are not followed, so that the walk stays within the workspace
+        if follow_symlinks
+            && entry.path_is_symlink()
+            && !entry.path().canonicalize().is_ok_and(|target| {
+                canonical_directory
+                    .as_ref()
+                    .is_some_and(|directory| target.starts_with(directory))
+            })
+        {
+            return false;
+        }
+
+        if entry.depth() == 0 || !is_dir {
+            return true;
+        }
+        (include_submodules || !entry.path().join(".git").exists())
+            && (include_nested_workspaces || !is_workspace_root(entry.path()))
     });
     walker
 }
 
+/// Finds the nested workspaces within the directory, without walking into them.
+/// Nested workspaces within nested workspaces are left for the nested workspace to find.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn find_nested_workspaces<P>(
+    directory: P,
+    use_ignores: bool,
+    options: WalkOptions,
+) -> Vec<String>
+where
+    P: AsRef<Path>,
+{
+    use std::sync::Mutex;
+    use std::thread::available_parallelism;
+
+    let directory = directory.as_ref();
+    let options = WalkOptions {
+        include_nested_workspaces: true,
+        ..options
+    };
+    let mut walker = create_walker(directory, use_ignores, &options);
+    let cpus = available_parallelism().map_or(2, |n| n.get()) - 1;
+
+    let nested_workspaces = Mutex::new(vec![]);
+    walker.threads(cpus).build_parallel().run(|| {
+        Box::new(|entry| {
+            use ignore::WalkState::*;
+
+            let Ok(dir_entry) = entry else {
+                return Continue;
+            };
+            if dir_entry.depth() == 0
+                || !dir_entry.file_type().is_some_and(|t| t.is_dir())
+                || !is_workspace_root(dir_entry.path())
+            {
+                return Continue;
+            }
+            if let Ok(nested_workspace) = dir_entry.path().strip_prefix(directory) {
+                nested_workspaces
+                    .lock()
+                    .expect("Should be able to lock nested workspaces")
+                    .push(nested_workspace.to_normalized_string());
+            }
+            Skip
+        })
+    });
+
+    let mut nested_workspaces = nested_workspaces
+        .into_inner()
+        .expect("Should be able to take nested workspaces");
+    nested_workspaces.sort();
+    nested_workspaces
+}
+
+/// Finds the nested workspaces within the directory, without walking into them.
+/// Nested workspaces within nested workspaces are left for the nested workspace to find.
+#[cfg(target_arch = "wasm32")]
+pub fn find_nested_workspaces<P>(
+    directory: P,
+    use_ignores: bool,
+    options: WalkOptions,
+) -> Vec<String>
+where
+    P: AsRef<Path>,
+{
+    let directory = directory.as_ref();
+    let options = WalkOptions {
+        include_nested_workspaces: true,
+        ..options
+    };
+    // the single threaded walker cannot skip directories, so the workspaces within nested workspaces are left out afterwards
+    let mut nested_workspaces = create_walker(directory, use_ignores, &options)
+        .build()
+        .filter_map(|entry| {
+            let dir_entry = entry.ok()?;
+            if dir_entry.depth() == 0
+                || !dir_entry.file_type().is_some_and(|t| t.is_dir())
+                || !is_workspace_root(dir_entry.path())
+            {
+                return None;
+            }
+            dir_entry
+                .path()
+                .strip_prefix(directory)
+                .ok()
+                .map(|nested_workspace| nested_workspace.to_path_buf())
+        })
+        .collect::<Vec<_>>();
+    nested_workspaces.sort();
+    nested_workspaces.dedup_by(|nested, outer| nested.starts_with(outer));
+    nested_workspaces
+        .into_iter()
+        .map(|nested_workspace| nested_workspace.to_normalized_string())
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
     use std::{assert_eq, vec};
 
     use assert_fs::prelude::*;
@@ -315,4 +586,250 @@ nested/child-two/
             )
         );
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn follows_symlinks_within_the_directory() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = setup_fs();
+        let outside = TempDir::new().unwrap();
+        outside.child("outside.txt").write_str("data").unwrap();
+        symlink(temp_dir.join("baz"), temp_dir.join("linked")).unwrap();
+        symlink(outside.path(), temp_dir.join("outside")).unwrap();
+        // a link to its own parent loops
+        symlink(temp_dir.join("baz"), temp_dir.join("baz/loop")).unwrap();
+
+        let walk = |symlinks| {
+            let mut files = nx_walker_with_options(
+                &temp_dir,
+                true,
+                WalkOptions {
+                    symlinks,
+                    ..Default::default()
+                },
+            )
+            .map(|file| (file.normalized_path, file.link_target))
+            .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+
+        let followed = walk(SymlinkPolicy::follow);
+        let followed = followed.iter().map(|(path, _)| path).collect::<Vec<_>>();
+        assert_eq!(
+            followed,
+            vec![
+                "bar.txt",
+                "baz/qux.txt",
+                "foo.txt",
+                "linked/qux.txt",
+                "test.txt"
+            ]
+        );
+
+        let recorded = walk(SymlinkPolicy::record);
+        assert!(recorded.contains(&(
+            "linked".to_string(),
+            Some(temp_dir.join("baz").to_normalized_string())
+        )));
+        assert!(recorded.contains(&(
+            "baz/loop".to_string(),
+            Some(temp_dir.join("baz").to_normalized_string())
+        )));
+        assert!(!recorded.iter().any(|(path, _)| path == "linked/qux.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn records_the_stats_of_the_content_of_symlinked_files() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = setup_fs();
+        temp_dir.child("content.txt").write_str("content").unwrap();
+        symlink(temp_dir.join("content.txt"), temp_dir.join("linked.txt")).unwrap();
+        symlink(temp_dir.join("baz"), temp_dir.join("linked")).unwrap();
+
+        let files = nx_walker_with_options(&temp_dir, true, WalkOptions::default())
+            .map(|file| (file.normalized_path.clone(), file))
+            .collect::<HashMap<_, _>>();
+        let linked = &files["linked.txt"];
+        assert_eq!(linked.size, 7);
+        assert_eq!(linked.mod_time, files["content.txt"].mod_time);
+        assert_eq!(linked.inode, files["content.txt"].inode);
+        // symlinks to directories are skipped
+        assert!(!files
+            .keys()
+            .any(|file| file.starts_with("linked/") || file == "linked"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn excludes_symlinked_submodules_and_nested_workspaces() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = setup_fs();
+        temp_dir
+            .child("modules/submodule/.git")
+            .write_str("gitdir: ../.git/modules/submodule")
+            .unwrap();
+        temp_dir
+            .child("modules/submodule/index.ts")
+            .write_str("data")
+            .unwrap();
+        temp_dir
+            .child("modules/nested/nx.json")
+            .write_str("{}")
+            .unwrap();
+        temp_dir
+            .child("modules/other.ts")
+            .write_str("data")
+            .unwrap();
+        symlink(temp_dir.join("modules"), temp_dir.join("linked")).unwrap();
+        symlink(
+            temp_dir.join("modules/submodule"),
+            temp_dir.join("submodule"),
+        )
+        .unwrap();
+        symlink(temp_dir.join("modules/nested"), temp_dir.join("nested")).unwrap();
+
+        let mut files = nx_walker_with_options(
+            &temp_dir,
+            true,
+            WalkOptions {
+                symlinks: SymlinkPolicy::follow,
+                include_submodules: false,
+                include_nested_workspaces: false,
+                ..Default::default()
+            },
+        )
+        .map(|file| file.normalized_path)
+        .filter(|file| !file.ends_with(".txt"))
+        .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, vec!["linked/other.ts", "modules/other.ts"]);
+    }
+
+    #[test]
+    fn excludes_submodules() {
+        let temp_dir = setup_fs();
+        temp_dir
+            .child("submodule")
+            .child(".git")
+            .write_str("gitdir: ../.git/modules/submodule")
+            .unwrap();
+        temp_dir
+            .child("submodule")
+            .child("index.ts")
+            .write_str("data")
+            .unwrap();
+
+        let walk = |include_submodules| {
+            nx_walker_with_options(
+                &temp_dir,
+                true,
+                WalkOptions {
+                    include_submodules,
+                    ..Default::default()
+                },
+            )
+            .any(|file| file.normalized_path == "submodule/index.ts")
+        };
+        assert!(walk(true));
+        assert!(!walk(false));
+    }
+
+    #[test]
+    fn excludes_and_finds_nested_workspaces() {
+        let temp_dir = setup_fs();
+        temp_dir.child("nx.json").write_str("{}").unwrap();
+        for nested_workspace in ["tools/legacy", "tools/legacy/inner", "apps/other"] {
+            temp_dir
+                .child(nested_workspace)
+                .child("nx.json")
+                .write_str("{}")
+                .unwrap();
+        }
+        temp_dir
+            .child("tools/legacy/index.ts")
+            .write_str("data")
+            .unwrap();
+
+        let walk = |include_nested_workspaces| {
+            let mut files = nx_walker_with_options(
+                &temp_dir,
+                true,
+                WalkOptions {
+                    include_nested_workspaces,
+                    ..Default::default()
+                },
+            )
+            .map(|file| file.normalized_path)
+            .filter(|file| file.contains("nx.json") || file.starts_with("tools"))
+            .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+        assert_eq!(
+            walk(true),
+            vec![
+                "apps/other/nx.json",
+                "nx.json",
+                "tools/legacy/index.ts",
+                "tools/legacy/inner/nx.json",
+                "tools/legacy/nx.json"
+            ]
+        );
+        assert_eq!(walk(false), vec!["nx.json"]);
+
+        assert_eq!(
+            find_nested_workspaces(&temp_dir, true, WalkOptions::default()),
+            vec!["apps/other", "tools/legacy"]
+        );
+    }
+
+    #[test]
+    fn handles_configured_ignore_files_and_globs() {
+        let temp_dir = setup_fs();
+        temp_dir.child(".nxignore").write_str("foo.txt").unwrap();
+        temp_dir
+            .child(".customignore")
+            .write_str("bar.txt")
+            .unwrap();
+        temp_dir.child("debug.log").write_str("log").unwrap();
+
+        let walk = |ignores| {
+            let mut files = nx_walker_with_options(
+                &temp_dir,
+                true,
+                WalkOptions {
+                    ignores,
+                    ..Default::default()
+                },
+            )
+            .map(|file| file.normalized_path)
+            .filter(|file| !file.starts_with('.'))
+            .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+
+        assert_eq!(
+            walk(IgnoreOptions::default()),
+            vec!["bar.txt", "baz/qux.txt", "debug.log", "test.txt"]
+        );
+        assert_eq!(
+            walk(IgnoreOptions {
+                ignore_files: Some(vec![".gitignore".into(), ".customignore".into()]),
+                ignore_globs: Some(vec!["*.log".into(), "baz/".into()]),
+            }),
+            vec!["foo.txt", "test.txt"]
+        );
+        // the configured globs can include files that the default globs ignore
+        assert!(walk(IgnoreOptions {
+            ignore_globs: Some(vec!["!node_modules".into()]),
+            ..Default::default()
+        })
+        .contains(&"node_modules/node-module-dep".to_string()));
+    }
 }
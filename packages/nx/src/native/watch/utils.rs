@@ -1,14 +1,16 @@
 use ignore::WalkBuilder;
 use ignore_files::IgnoreFile;
-use std::path::Path;
 use std::{fs, path::PathBuf};
 use tracing::trace;
 use watchexec_events::{Event, Tag};
 
+/// Finds the ignore files with one of the names in the root and below.
+/// Returns the `.gitignore` files, and the other ignore files ordered by precedence.
 pub(super) fn get_ignore_files<T: AsRef<str>>(
     use_ignore: bool,
     root: T,
-) -> Option<Vec<IgnoreFile>> {
+    ignore_file_names: &[String],
+) -> Option<(Vec<IgnoreFile>, Vec<IgnoreFile>)> {
     let root = root.as_ref();
     if use_ignore {
         let mut walker = WalkBuilder::new(root);
@@ -17,35 +19,40 @@ pub(super) fn get_ignore_files<T: AsRef<str>>(
 
         let node_folder = PathBuf::from(root).join("node_modules");
         walker.filter_entry(move |entry| !entry.path().starts_with(&node_folder));
-        Some(
-            walker
-                .build()
-                .flatten()
-                .filter(|result| result.path().ends_with(".gitignore"))
-                .map(|result| {
-                    let path: PathBuf = result.path().into();
-                    let parent: PathBuf = path.parent().unwrap_or(&path).into();
-                    IgnoreFile {
-                        path,
-                        applies_in: Some(parent),
-                        applies_to: None,
-                    }
-                })
-                .collect(),
-        )
-    } else {
-        None
-    }
-}
 
-pub(super) fn get_nx_ignore<P: AsRef<Path>>(origin: P) -> Option<IgnoreFile> {
-    let nx_ignore_path = PathBuf::from(origin.as_ref()).join(".nxignore");
-    if nx_ignore_path.exists() {
-        Some(IgnoreFile {
-            path: nx_ignore_path,
-            applies_in: Some(origin.as_ref().into()),
-            applies_to: None,
-        })
+        let mut git_ignore_files = vec![];
+        let mut custom_ignore_files = vec![];
+        for result in walker.build().flatten() {
+            let Some(precedence) = result
+                .file_name()
+                .to_str()
+                .and_then(|name| ignore_file_names.iter().position(|n| n == name))
+            else {
+                continue;
+            };
+            let path: PathBuf = result.path().into();
+            let parent: PathBuf = path.parent().unwrap_or(&path).into();
+            let ignore_file = IgnoreFile {
+                path,
+                applies_in: Some(parent),
+                applies_to: None,
+            };
+            if result.file_name() == ".gitignore" {
+                git_ignore_files.push(ignore_file);
+            } else {
+                custom_ignore_files.push((precedence, result.depth(), ignore_file));
+            }
+        }
+
+        // later names, and then files in deeper directories take precedence
+        custom_ignore_files.sort_by_key(|(precedence, depth, _)| (*precedence, *depth));
+        Some((
+            git_ignore_files,
+            custom_ignore_files
+                .into_iter()
+                .map(|(_, _, ignore_file)| ignore_file)
+                .collect(),
+        ))
     } else {
         None
     }
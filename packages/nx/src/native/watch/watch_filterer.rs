@@ -8,11 +8,15 @@ use ignore_files::IgnoreFilter;
 use watchexec_events::{Event, FileType, Priority, Source, Tag};
 use watchexec_filterer_ignore::IgnoreFilterer;
 
-use crate::native::watch::utils::{get_ignore_files, get_nx_ignore, transform_event};
+use crate::native::watch::utils::{get_ignore_files, transform_event};
 
 #[derive(Debug)]
 pub struct WatchFilterer {
-    pub nx_ignore: Option<IgnoreFilter>,
+    /// The ignore globs, which are checked before the ignore files like the walker does,
+    /// so that ignore files cannot include what the globs ignore
+    pub ignore_globs: IgnoreFilter,
+    /// The ignore files besides `.gitignore`, such as `.nxignore`, which take precedence over the `.gitignore` files
+    pub custom_ignore: Option<IgnoreFilter>,
     pub git_ignore: IgnoreFilterer,
 }
 
@@ -22,21 +26,27 @@ impl WatchFilterer {
         for (path, file_type) in event.paths() {
             let path = dunce::simplified(path);
             let is_dir = file_type.map_or(false, |t| matches!(t, FileType::Dir));
-            let nx_ignore_match_type = if let Some(nx_ignore) = &self.nx_ignore {
-                nx_ignore.match_path(path, is_dir)
+            if matches!(self.ignore_globs.match_path(path, is_dir), Match::Ignore(_)) {
+                trace!(?path, "ignore glob match, ignoring ignore files");
+                pass &= false;
+                continue;
+            }
+
+            let custom_ignore_match_type = if let Some(custom_ignore) = &self.custom_ignore {
+                custom_ignore.match_path(path, is_dir)
             } else {
                 Match::None
             };
 
-            // if the nxignore file contains this file as a whitelist,
+            // if a custom ignore file such as .nxignore contains this file as a whitelist,
             // we do not want gitignore to filter it out, so it will always pass as true
-            if matches!(nx_ignore_match_type, Match::Whitelist(_)) {
-                trace!(?path, "nxignore whitelist match, ignoring gitignore");
+            if matches!(custom_ignore_match_type, Match::Whitelist(_)) {
+                trace!(?path, "custom ignore whitelist match, ignoring gitignore");
                 pass &= true;
-            // If the nxignore file contains this file as an ignore,
+            // If a custom ignore file contains this file as an ignore,
             // then there's no point in checking the gitignore file
-            } else if matches!(nx_ignore_match_type, Match::Ignore(_)) {
-                trace!(?path, "nxignore ignore match, ignoring gitignore");
+            } else if matches!(custom_ignore_match_type, Match::Ignore(_)) {
+                trace!(?path, "custom ignore ignore match, ignoring gitignore");
                 pass &= false;
             } else {
                 pass &= self
@@ -116,26 +126,29 @@ impl Filterer for WatchFilterer {
 pub(super) async fn create_filter(
     origin: &str,
     additional_globs: &[String],
+    ignore_file_names: &[String],
     use_ignore: bool,
 ) -> anyhow::Result<WatchFilterer> {
-    let ignore_files = get_ignore_files(use_ignore, origin);
-    let nx_ignore_file = get_nx_ignore(origin);
+    let (git_ignore_files, custom_ignore_files) =
+        get_ignore_files(use_ignore, origin, ignore_file_names).unzip();
 
     trace!(
         ?use_ignore,
         ?additional_globs,
-        ?ignore_files,
+        ?git_ignore_files,
+        ?custom_ignore_files,
         "Using these ignore files for the watcher"
     );
-    let mut git_ignore = if let Some(ignore_files) = ignore_files {
-        IgnoreFilter::new(origin, &ignore_files)
+    let git_ignore = if let Some(git_ignore_files) = git_ignore_files {
+        IgnoreFilter::new(origin, &git_ignore_files)
             .await
             .map_err(anyhow::Error::from)?
     } else {
         IgnoreFilter::empty(origin)
     };
 
-    git_ignore
+    let mut ignore_globs = IgnoreFilter::empty(origin);
+    ignore_globs
         .add_globs(
             &additional_globs
                 .iter()
@@ -145,18 +158,18 @@ pub(super) async fn create_filter(
         )
         .map_err(anyhow::Error::from)?;
 
-    let nx_ignore = if let Some(nx_ignore_file) = nx_ignore_file {
-        Some(
-            IgnoreFilter::new(origin, &[nx_ignore_file])
+    let custom_ignore = match custom_ignore_files {
+        Some(custom_ignore_files) if !custom_ignore_files.is_empty() => Some(
+            IgnoreFilter::new(origin, &custom_ignore_files)
                 .await
                 .map_err(anyhow::Error::from)?,
-        )
-    } else {
-        None
+        ),
+        _ => None,
     };
 
     Ok(WatchFilterer {
+        ignore_globs,
         git_ignore: IgnoreFilterer(git_ignore),
-        nx_ignore,
+        custom_ignore,
     })
 }
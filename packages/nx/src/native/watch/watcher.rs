@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::path::MAIN_SEPARATOR;
 use std::sync::Arc;
 
+use crate::native::walker::IgnoreOptions;
 use crate::native::watch::types::{
     transform_event_to_watch_events, EventType, WatchEvent, WatchEventInternal,
 };
@@ -24,6 +25,7 @@ pub struct Watcher {
     pub origin: String,
     watch_exec: Arc<Watchexec>,
     additional_globs: Vec<String>,
+    ignore_files: Vec<String>,
     use_ignore: bool,
 }
 
@@ -33,22 +35,21 @@ impl Watcher {
     /// Will always ignore the following directories:
     /// * .git/
     /// * node_modules/
-    /// * .nx/
+    /// * .nx/cache/ and .nx/workspace-data/
+    ///   The rest of .nx/, such as .nx/installation, is watched, because it is part of the workspace context too.
+    /// * .yarn/cache/
+    ///
+    /// The ignore options should be the same as for the workspace context, so that both agree on which files exist.
     #[napi(constructor)]
     pub fn new(
         origin: String,
         additional_globs: Option<Vec<String>>,
         use_ignore: Option<bool>,
+        ignores: Option<IgnoreOptions>,
     ) -> Watcher {
+        let ignores = ignores.unwrap_or_default();
         // always have these globs come before the additional globs
-        let mut globs = vec![
-            ".git/".into(),
-            "node_modules/".into(),
-            ".nx/".into(),
-            "vitest.config.ts.timestamp*.mjs".into(),
-            "vite.config.ts.timestamp*.mjs".into(),
-            ".yarn/cache/".into(),
-        ];
+        let mut globs = ignores.ignore_globs();
         if let Some(additional_globs) = additional_globs {
             globs.extend(additional_globs);
         }
@@ -61,6 +62,7 @@ impl Watcher {
             },
             watch_exec: Arc::new(Watchexec::default()),
             additional_globs: globs,
+            ignore_files: ignores.ignore_files(),
             use_ignore: use_ignore.unwrap_or(true),
         }
     }
@@ -148,6 +150,7 @@ impl Watcher {
                     }
                 }
             }
+
             callback_tsfn.call(Ok(group_events), ThreadsafeFunctionCallMode::NonBlocking);
 
             action
@@ -155,13 +158,20 @@ impl Watcher {
 
         let origin = self.origin.clone();
         let additional_globs = self.additional_globs.clone();
+        let ignore_files = self.ignore_files.clone();
         let use_ignore = self.use_ignore;
         let watch_exec = self.watch_exec.clone();
         let start = async move {
             trace!("configuring watch exec");
             watch_exec.config.pathset([&origin.as_str()]);
             watch_exec.config.filterer(
-                watch_filterer::create_filter(&origin, &additional_globs, use_ignore).await?,
+                watch_filterer::create_filter(
+                    &origin,
+                    &additional_globs,
+                    &ignore_files,
+                    use_ignore,
+                )
+                .await?,
             );
             trace!("starting watch exec");
             watch_exec.main().await.map_err(anyhow::Error::from)?.ok();
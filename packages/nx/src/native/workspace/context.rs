@@ -1,42 +1,108 @@
 use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
-use crate::native::hasher::hash;
+use crate::native::hasher::{HashAlgorithm, Hasher};
 use crate::native::logger::enable_logger;
 use crate::native::project_graph::utils::{find_project_for_path, ProjectRootMappings};
 use crate::native::types::FileData;
-use crate::native::utils::{path::get_child_files, Normalize, NxCondvar, NxMutex};
+use crate::native::utils::{get_mod_time, path::get_child_files, Normalize, NxCondvar, NxMutex};
+use crate::native::walker::{find_nested_workspaces, IgnoreOptions, SymlinkPolicy, WalkOptions};
 use crate::native::workspace::files_archive::{read_files_archive, write_files_archive};
-use crate::native::workspace::files_hashing::{full_files_hash, selective_files_hash};
+use crate::native::workspace::files_hashing::{
+    full_files_hash, selective_files_hash, FileHashMode, FilesHashingOptions, LargeFileHashing,
+    LargeFileOptions, DEFAULT_LARGE_FILE_THRESHOLD,
+};
+use crate::native::workspace::files_query::{query_files, WorkspaceFilesPage, WorkspaceFilesQuery};
+use crate::native::workspace::files_search::{search_files, SearchMatch, SearchOptions};
 use crate::native::workspace::types::{
-    FileMap, NxWorkspaceFilesExternals, ProjectFiles, UpdatedWorkspaceFiles,
+    FileMap, NxWorkspaceFilesExternals, ProjectFiles, UpdatedWorkspaceFiles, WorkspaceFileSize,
+    WorkspaceFilesOptions,
 };
 use crate::native::workspace::{config_files, types::NxWorkspaceFiles, workspace_files};
 use napi::bindgen_prelude::External;
 use rayon::prelude::*;
 use tracing::{trace, warn};
-use xxhash_rust::xxh3;
+
+/// How workspaces within the workspace, which have their own nx.json, are handled
+#[napi(string_enum)]
+#[derive(Debug, Default, PartialEq)]
+pub enum NestedWorkspaces {
+    /// Their files are files of this workspace
+    #[allow(non_camel_case_types)]
+    #[default]
+    include,
+    /// Their files are left out of this workspace
+    #[allow(non_camel_case_types)]
+    exclude,
+    /// Their files are left out of this workspace, and each of them gets its own context
+    #[allow(non_camel_case_types)]
+    mount,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct WorkspaceContextOptions {
+    /// The algorithm that the files are hashed with, `xxh3` by default
+    pub hash_algorithm: Option<HashAlgorithm>,
+    /// Reads the hashes of tracked files that did not change from the git index, instead of reading the files.
+    /// Every file is then hashed as a git object id, so that the hashes do not depend on whether files changed.
+    pub use_git_index: Option<bool>,
+    /// Hashes the files that were modified after the workspace files were last hashed again,
+    /// even when their mod time, size and inode did not change
+    pub paranoid_file_hashing: Option<bool>,
+    /// How symlinks are walked, `content` by default
+    pub symlinks: Option<SymlinkPolicy>,
+    /// Whether to walk git submodules and nested repositories, `true` by default
+    pub include_submodules: Option<bool>,
+    /// Files with more bytes than this are streamed instead of read into memory at once, 8 MiB by default
+    pub large_file_threshold: Option<u32>,
+    /// Hashes files above the large file threshold by their size and mod time, without reading them
+    pub hash_large_files_by_stats: Option<bool>,
+    /// How workspaces within this workspace, which have their own nx.json, are handled, `include` by default
+    pub nested_workspaces: Option<NestedWorkspaces>,
+    /// Which files are ignored, which should be the same as for the watcher of the workspace
+    pub ignores: Option<IgnoreOptions>,
+}
 
 #[napi]
+#[derive(Clone)]
 pub struct WorkspaceContext {
     pub workspace_root: String,
     workspace_root_path: PathBuf,
     files_worker: FilesWorker,
+    hashing_options: FilesHashingOptions,
+    /// The roots of the nested workspaces that are not part of this workspace, which are found once they are needed.
+    /// Clones share the same roots.
+    nested_roots: Arc<OnceLock<Vec<String>>>,
+    nested_contexts: Option<NestedContexts>,
+}
+
+/// The contexts of mounted nested workspaces, which are only found once they are needed.
+/// Clones share the same contexts.
+#[derive(Clone)]
+struct NestedContexts {
+    cache_dir: PathBuf,
+    contexts: Arc<OnceLock<Vec<(String, WorkspaceContext)>>>,
 }
 
 type Files = Vec<(PathBuf, String)>;
 
-fn gather_and_hash_files(workspace_root: &Path, cache_dir: String) -> Vec<(PathBuf, String)> {
-    let archived_files = read_files_archive(&cache_dir);
+fn gather_and_hash_files(
+    workspace_root: &Path,
+    cache_dir: String,
+    hashing_options: FilesHashingOptions,
+) -> Vec<(PathBuf, String)> {
+    let archive_name = hashing_options.archive_name();
+    let archived_files = read_files_archive(&cache_dir, workspace_root, &archive_name);
 
     trace!("Gathering files in {}", workspace_root.display());
     let now = std::time::Instant::now();
     let file_hashes = if let Some(archived_files) = archived_files {
-        selective_files_hash(workspace_root, archived_files)
+        selective_files_hash(workspace_root, archived_files, hashing_options)
     } else {
-        full_files_hash(workspace_root)
+        full_files_hash(workspace_root, hashing_options)
     };
 
     let mut files = file_hashes
@@ -46,15 +112,21 @@ fn gather_and_hash_files(workspace_root: &Path, cache_dir: String) -> Vec<(PathB
     files.par_sort();
     trace!("hashed and sorted files in {:?}", now.elapsed());
 
-    write_files_archive(&cache_dir, file_hashes);
+    write_files_archive(&cache_dir, workspace_root, file_hashes, &archive_name);
 
     files
 }
 
+/// Clones share the same files, so that updates are seen by every clone
+#[derive(Clone)]
 struct FilesWorker(Option<Arc<(NxMutex<Files>, NxCondvar)>>);
 impl FilesWorker {
     #[cfg(not(target_arch = "wasm32"))]
-    fn gather_files(workspace_root: &Path, cache_dir: String) -> Self {
+    fn gather_files(
+        workspace_root: &Path,
+        cache_dir: String,
+        hashing_options: FilesHashingOptions,
+    ) -> Self {
         if !workspace_root.exists() {
             warn!(
                 "workspace root does not exist: {}",
@@ -72,7 +144,7 @@ impl FilesWorker {
             trace!("Initially locking files");
             let mut workspace_files = lock.lock().expect("Should be the first time locking files");
 
-            let files = gather_and_hash_files(&workspace_root, cache_dir);
+            let files = gather_and_hash_files(&workspace_root, cache_dir, hashing_options);
 
             *workspace_files = files;
             let files_len = workspace_files.len();
@@ -86,7 +158,11 @@ impl FilesWorker {
     }
 
     #[cfg(target_arch = "wasm32")]
-    fn gather_files(workspace_root: &Path, cache_dir: String) -> Self {
+    fn gather_files(
+        workspace_root: &Path,
+        cache_dir: String,
+        hashing_options: FilesHashingOptions,
+    ) -> Self {
         if !workspace_root.exists() {
             warn!(
                 "workspace root does not exist: {}",
@@ -97,7 +173,7 @@ impl FilesWorker {
 
         let workspace_root = workspace_root.to_owned();
 
-        let files = gather_and_hash_files(&workspace_root, cache_dir);
+        let files = gather_and_hash_files(&workspace_root, cache_dir, hashing_options);
 
         trace!("{} files retrieved", files.len());
 
@@ -145,6 +221,7 @@ impl FilesWorker {
         workspace_root_path: &Path,
         updated_files: Vec<&str>,
         deleted_files_and_directories: Vec<&str>,
+        hashing_options: &FilesHashingOptions,
     ) -> HashMap<String, String> {
         let Some(files_sync) = &self.0 else {
             trace!("there were no files because the workspace root did not exist");
@@ -173,11 +250,23 @@ impl FilesWorker {
             .par_iter()
             .filter_map(|path| {
                 let full_path = workspace_root_path.join(path);
-                let Ok(content) = std::fs::read(&full_path) else {
+                if hashing_options.walk.symlinks == SymlinkPolicy::record {
+                    if let Ok(link_target) = std::fs::read_link(&full_path) {
+                        let link_target = link_target.to_normalized_string();
+                        let hash = hashing_options.hash(link_target.as_bytes());
+                        return Some((path.to_string(), hash));
+                    }
+                }
+                let Ok(metadata) = std::fs::metadata(&full_path) else {
                     trace!("could not read file: {full_path:?}");
                     return None;
                 };
-                Some((path.to_string(), hash(&content)))
+                let hash = hashing_options.hash_file_content(
+                    &full_path.to_string_lossy(),
+                    metadata.len(),
+                    get_mod_time(&metadata),
+                )?;
+                Some((path.to_string(), hash))
             })
             .collect();
 
@@ -194,30 +283,183 @@ impl FilesWorker {
     }
 }
 
+/// The roots of the projects by their name when the options ask for diagnostics.
+/// Without the project roots of the options, they are read from the project root map, which cannot have duplicate roots.
+fn diagnostics_project_roots(
+    project_root_map: &HashMap<String, String>,
+    options: Option<WorkspaceFilesOptions>,
+) -> Option<HashMap<String, String>> {
+    let options = options?;
+    if !options.diagnostics.unwrap_or(false) {
+        return None;
+    }
+    Some(options.project_roots.unwrap_or_else(|| {
+        project_root_map
+            .iter()
+            .map(|(root, project)| (project.clone(), root.clone()))
+            .collect()
+    }))
+}
+
+/// Finds the nested workspace that the path is in, and the path relative to its root
+fn find_nested_workspace<'a>(nested_roots: &'a [String], path: &str) -> Option<(&'a str, String)> {
+    nested_roots.iter().find_map(|root| {
+        let path = Path::new(path).strip_prefix(root).ok()?;
+        (!path.as_os_str().is_empty()).then(|| (root.as_str(), path.to_normalized_string()))
+    })
+}
+
 #[napi]
 impl WorkspaceContext {
     #[napi(constructor)]
-    pub fn new(workspace_root: String, cache_dir: String) -> Self {
+    pub fn new(
+        workspace_root: String,
+        cache_dir: String,
+        options: Option<WorkspaceContextOptions>,
+    ) -> Self {
         enable_logger();
 
         trace!(?workspace_root);
 
+        let options = options.unwrap_or_default();
+        let nested_workspaces = options.nested_workspaces.unwrap_or_default();
+        let hashing_options = FilesHashingOptions {
+            hash_mode: if options.use_git_index.unwrap_or(false) {
+                FileHashMode::GitObjectId
+            } else {
+                FileHashMode::HashAlgorithm
+            },
+            hash_algorithm: options.hash_algorithm.unwrap_or_default(),
+            paranoid: options.paranoid_file_hashing.unwrap_or(false),
+            walk: WalkOptions {
+                symlinks: options.symlinks.unwrap_or_default(),
+                include_submodules: options.include_submodules.unwrap_or(true),
+                include_nested_workspaces: nested_workspaces == NestedWorkspaces::include,
+                ignores: options.ignores.unwrap_or_default(),
+            },
+            large_files: LargeFileOptions {
+                threshold: options
+                    .large_file_threshold
+                    .map_or(DEFAULT_LARGE_FILE_THRESHOLD, u64::from),
+                hashing: if options.hash_large_files_by_stats.unwrap_or(false) {
+                    LargeFileHashing::Stats
+                } else {
+                    LargeFileHashing::Stream
+                },
+            },
+        };
+
+        Self::with_hashing_options(
+            workspace_root,
+            cache_dir,
+            hashing_options,
+            nested_workspaces == NestedWorkspaces::mount,
+        )
+    }
+
+    fn with_hashing_options(
+        workspace_root: String,
+        cache_dir: String,
+        hashing_options: FilesHashingOptions,
+        mount_nested_workspaces: bool,
+    ) -> Self {
         let workspace_root_path = PathBuf::from(&workspace_root);
+        let nested_contexts = mount_nested_workspaces.then(|| NestedContexts {
+            cache_dir: Path::new(&cache_dir).join("nested-workspaces"),
+            contexts: Default::default(),
+        });
 
         WorkspaceContext {
-            files_worker: FilesWorker::gather_files(&workspace_root_path, cache_dir.clone()),
+            files_worker: FilesWorker::gather_files(
+                &workspace_root_path,
+                cache_dir,
+                hashing_options.clone(),
+            ),
             workspace_root,
             workspace_root_path,
+            hashing_options,
+            nested_roots: Default::default(),
+            nested_contexts,
+        }
+    }
+
+    pub(crate) fn hash_algorithm(&self) -> HashAlgorithm {
+        self.hashing_options.hash_algorithm
+    }
+
+    fn nested_roots(&self) -> &[String] {
+        if self.hashing_options.walk.include_nested_workspaces {
+            return &[];
         }
+        self.nested_roots.get_or_init(|| {
+            find_nested_workspaces(
+                &self.workspace_root_path,
+                true,
+                self.hashing_options.walk.clone(),
+            )
+        })
+    }
+
+    fn nested_contexts(&self) -> &[(String, WorkspaceContext)] {
+        let Some(nested_contexts) = &self.nested_contexts else {
+            return &[];
+        };
+        nested_contexts.contexts.get_or_init(|| {
+            self.nested_roots()
+                .iter()
+                .cloned()
+                .map(|root| {
+                    let cache_dir = nested_contexts.cache_dir.join(&root);
+                    if let Err(e) = std::fs::create_dir_all(&cache_dir) {
+                        trace!("could not create {}: {e:?}", cache_dir.display());
+                    }
+                    let context = WorkspaceContext::with_hashing_options(
+                        self.workspace_root_path.join(&root).to_normalized_string(),
+                        cache_dir.to_normalized_string(),
+                        self.hashing_options.clone(),
+                        true,
+                    );
+                    (root, context)
+                })
+                .collect()
+        })
+    }
+
+    /// The roots of the nested workspaces that are mounted as their own contexts, relative to this workspace
+    #[napi]
+    pub fn get_nested_workspaces(&self) -> Vec<String> {
+        self.nested_contexts()
+            .iter()
+            .map(|(root, _)| root.clone())
+            .collect()
+    }
+
+    /// The context of the mounted nested workspace at the root, which is relative to this workspace
+    #[napi]
+    pub fn get_nested_context(&self, root: String) -> Option<WorkspaceContext> {
+        self.nested_contexts()
+            .iter()
+            .find(|(nested_root, _)| *nested_root == root)
+            .map(|(_, context)| context.clone())
     }
 
     #[napi]
     pub fn get_workspace_files(
         &self,
         project_root_map: HashMap<String, String>,
+        options: Option<WorkspaceFilesOptions>,
     ) -> anyhow::Result<NxWorkspaceFiles> {
-        workspace_files::get_files(project_root_map, self.all_file_data())
-            .map_err(anyhow::Error::from)
+        let diagnostics_roots = diagnostics_project_roots(&project_root_map, options);
+        let mut workspace_files =
+            workspace_files::get_files(project_root_map, self.all_file_data())?;
+        if let Some(project_roots) = diagnostics_roots {
+            workspace_files.diagnostics = Some(workspace_files::diagnose_project_files(
+                &self.workspace_root_path,
+                project_roots,
+                &workspace_files.project_file_map,
+            ));
+        }
+        Ok(workspace_files)
     }
 
     #[napi]
@@ -264,12 +506,12 @@ impl WorkspaceContext {
             .map(|globs| {
                 let globbed_files =
                     config_files::glob_files(files, globs, None)?.collect::<Vec<_>>();
-                let mut hasher = xxh3::Xxh3::new();
+                let mut hasher = Hasher::with_algorithm(self.hashing_options.hash_algorithm);
                 for file in globbed_files {
                     hasher.update(file.file.as_bytes());
                     hasher.update(file.hash.as_bytes());
                 }
-                Ok(hasher.digest().to_string())
+                Ok(hasher.digest())
             })
             .collect::<napi::Result<Vec<_>>>()?;
 
@@ -285,23 +527,61 @@ impl WorkspaceContext {
         let files = &self.all_file_data();
         let globbed_files = config_files::glob_files(files, globs, exclude)?.collect::<Vec<_>>();
 
-        let mut hasher = xxh3::Xxh3::new();
+        let mut hasher = Hasher::with_algorithm(self.hashing_options.hash_algorithm);
         for file in globbed_files {
             hasher.update(file.file.as_bytes());
             hasher.update(file.hash.as_bytes());
         }
 
-        Ok(hasher.digest().to_string())
+        Ok(hasher.digest())
     }
 
+    /// Updates the files that changed, and returns the hashes of the updated files of this workspace.
+    /// Files of nested workspaces are not part of this workspace, they update the mounted nested contexts instead.
     #[napi]
     pub fn incremental_update(
         &self,
         updated_files: Vec<&str>,
         deleted_files: Vec<&str>,
     ) -> HashMap<String, String> {
-        self.files_worker
-            .update_files(&self.workspace_root_path, updated_files, deleted_files)
+        let nested_roots = self.nested_roots();
+        let mut nested_updates: HashMap<&str, (Vec<String>, Vec<String>)> = HashMap::new();
+        let updated_files = updated_files
+            .into_iter()
+            .filter(|file| match find_nested_workspace(nested_roots, file) {
+                Some((root, file)) => {
+                    nested_updates.entry(root).or_default().0.push(file);
+                    false
+                }
+                None => true,
+            })
+            .collect();
+        let deleted_files = deleted_files
+            .into_iter()
+            .filter(|file| match find_nested_workspace(nested_roots, file) {
+                Some((root, file)) => {
+                    nested_updates.entry(root).or_default().1.push(file);
+                    false
+                }
+                None => true,
+            })
+            .collect();
+
+        for (root, context) in self.nested_contexts() {
+            if let Some((updated_files, deleted_files)) = nested_updates.get(root.as_str()) {
+                context.incremental_update(
+                    updated_files.iter().map(String::as_str).collect(),
+                    deleted_files.iter().map(String::as_str).collect(),
+                );
+            }
+        }
+
+        self.files_worker.update_files(
+            &self.workspace_root_path,
+            updated_files,
+            deleted_files,
+            &self.hashing_options,
+        )
     }
 
     #[napi]
@@ -312,6 +592,7 @@ impl WorkspaceContext {
         global_files: External<Vec<FileData>>,
         updated_files: HashMap<String, String>,
         deleted_files: Vec<&str>,
+        options: Option<WorkspaceFilesOptions>,
     ) -> UpdatedWorkspaceFiles {
         trace!("updating project files");
         trace!("{project_root_mappings:?}");
@@ -391,6 +672,14 @@ impl WorkspaceContext {
             .into_iter()
             .map(|(file, hash)| FileData { file, hash })
             .collect::<Vec<_>>();
+        let diagnostics =
+            diagnostics_project_roots(&project_root_mappings, options).map(|project_roots| {
+                workspace_files::diagnose_project_files(
+                    &self.workspace_root_path,
+                    project_roots,
+                    &project_files_map,
+                )
+            });
 
         UpdatedWorkspaceFiles {
             file_map: FileMap {
@@ -402,6 +691,7 @@ impl WorkspaceContext {
                 global_files: External::new(non_project_files),
                 all_workspace_files: External::new(self.all_file_data()),
             },
+            diagnostics,
         }
     }
 
@@ -410,8 +700,112 @@ impl WorkspaceContext {
         self.files_worker.get_files()
     }
 
+    /// Searches the content of the workspace files that match the globs, or of every file when there are no globs
+    /// @returns The matches of the regex pattern in each line, sorted by file and line
+    #[napi]
+    pub fn search(
+        &self,
+        pattern: String,
+        globs: Vec<String>,
+        options: Option<SearchOptions>,
+    ) -> anyhow::Result<Vec<SearchMatch>> {
+        search_files(
+            &self.workspace_root_path,
+            &self.all_file_data(),
+            &pattern,
+            globs,
+            options.unwrap_or_default(),
+        )
+    }
+
+    /// Queries the workspace files with their size and mod time, one page at a time
+    #[napi]
+    pub fn query_files(
+        &self,
+        query: WorkspaceFilesQuery,
+        project_root_map: Option<ProjectRootMappings>,
+    ) -> anyhow::Result<WorkspaceFilesPage> {
+        query_files(
+            &self.workspace_root_path,
+            &self.all_file_data(),
+            &query,
+            project_root_map.as_ref(),
+        )
+    }
+
+    /// The largest files of the workspace, which are good candidates for the .nxignore when they slow down hashing
+    #[napi]
+    pub fn get_largest_files(&self, count: u32) -> Vec<WorkspaceFileSize> {
+        let mut files = self
+            .all_file_data()
+            .into_par_iter()
+            .filter_map(|file_data| {
+                let metadata = std::fs::metadata(self.workspace_root_path.join(&file_data.file));
+                Some(WorkspaceFileSize {
+                    size: metadata.ok()?.len() as i64,
+                    file: file_data.file,
+                })
+            })
+            .collect::<Vec<_>>();
+        files.par_sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.file.cmp(&b.file)));
+        files.truncate(count as usize);
+        files
+    }
+
     #[napi]
     pub fn get_files_in_directory(&self, directory: String) -> Vec<String> {
         get_child_files(directory, self.files_worker.get_files())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+
+    #[test]
+    fn should_update_the_files_of_mounted_nested_workspaces() {
+        let temp = TempDir::new().unwrap();
+        temp.child("nx.json").write_str("{}").unwrap();
+        temp.child("main.ts").write_str("main").unwrap();
+        temp.child("tools/legacy/nx.json").write_str("{}").unwrap();
+        temp.child("tools/legacy/index.ts")
+            .write_str("index")
+            .unwrap();
+
+        let context = WorkspaceContext::new(
+            temp.display().to_string(),
+            temp.child(".nx/workspace-data").display().to_string(),
+            Some(WorkspaceContextOptions {
+                nested_workspaces: Some(NestedWorkspaces::mount),
+                ..Default::default()
+            }),
+        );
+        let nested_context = context.get_nested_context("tools/legacy".into()).unwrap();
+        let hash_of = |context: &WorkspaceContext, file: &str| {
+            context
+                .all_file_data()
+                .into_iter()
+                .find(|f| f.file == file)
+                .map(|f| f.hash)
+        };
+        let index_hash = hash_of(&nested_context, "index.ts");
+        assert!(index_hash.is_some());
+
+        temp.child("tools/legacy/index.ts")
+            .write_str("changed")
+            .unwrap();
+        temp.child("tools/legacy/new.ts").write_str("new").unwrap();
+        let updated = context.incremental_update(
+            vec!["main.ts", "tools/legacy/index.ts", "tools/legacy/new.ts"],
+            vec!["tools/legacy/nx.json"],
+        );
+
+        assert_eq!(updated.keys().collect::<Vec<_>>(), vec!["main.ts"]);
+        assert_eq!(hash_of(&context, "tools/legacy/index.ts"), None);
+        assert_ne!(hash_of(&nested_context, "index.ts"), index_hash);
+        assert!(hash_of(&nested_context, "new.ts").is_some());
+        assert_eq!(hash_of(&nested_context, "nx.json"), None);
+    }
+}
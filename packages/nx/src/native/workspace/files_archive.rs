@@ -1,16 +1,40 @@
 use anyhow::anyhow;
 use hashbrown::HashMap;
-use rkyv::{Archive, Deserialize, Infallible, Serialize};
+use rkyv::{AlignedVec, Archive, Deserialize, Infallible, Serialize};
 use std::ops::{Deref, DerefMut};
 use std::path::Path;
 
 use tracing::trace;
 
+use crate::native::utils::get_mod_time;
+use crate::native::walker::NxFile;
+
 const NX_FILES_ARCHIVE: &str = "nx_files.nxt";
 
+/// What a file looked like when it was hashed. The file is hashed again when any of these changed.
+#[derive(Archive, Serialize, Deserialize, PartialEq, Debug, Default, Clone, Copy)]
+#[archive(check_bytes)]
+pub struct NxFileStats {
+    pub mod_time: i64,
+    pub size: u64,
+    pub inode: u64,
+    pub change_time: i64,
+}
+
+impl From<&NxFile> for NxFileStats {
+    fn from(file: &NxFile) -> Self {
+        NxFileStats {
+            mod_time: file.mod_time,
+            size: file.size,
+            inode: file.inode,
+            change_time: file.change_time,
+        }
+    }
+}
+
 #[derive(Archive, Serialize, Deserialize, PartialEq, Debug)]
 #[archive(check_bytes)]
-pub struct NxFileHashed(pub String, pub i64);
+pub struct NxFileHashed(pub String, pub NxFileStats);
 
 #[derive(Archive, Deserialize, Serialize, Debug, PartialEq)]
 #[archive(check_bytes)]
@@ -38,24 +62,156 @@ impl FromIterator<(String, NxFileHashed)> for NxFileHashes {
     }
 }
 
-pub fn read_files_archive<P: AsRef<Path>>(cache_dir: P) -> Option<NxFileHashes> {
+/// Bumped whenever the layout of the archive changes
+const ARCHIVE_VERSION: u32 = 2;
+const ARCHIVE_MAGIC: &[u8; 4] = b"NXFA";
+
+/// Written before the archived files, so that archives of another version, workspace or hashing options
+/// are detected without validating the files.
+/// Hashes are only reused when they were computed with the same options, see `FilesHashingOptions::archive_name`.
+#[derive(Debug, PartialEq)]
+struct NxFilesArchiveHeader {
+    version: u32,
+    workspace_root: String,
+    archive_name: String,
+}
+
+impl NxFilesArchiveHeader {
+    fn new(workspace_root: &Path, archive_name: &str) -> Self {
+        Self {
+            version: ARCHIVE_VERSION,
+            workspace_root: workspace_root.to_string_lossy().into_owned(),
+            archive_name: archive_name.to_string(),
+        }
+    }
+
+    fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(ARCHIVE_MAGIC);
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        for value in [&self.workspace_root, &self.archive_name] {
+            bytes.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(value.as_bytes());
+        }
+    }
+
+    /// Reads the header, and returns the archived files after it
+    fn read(bytes: &[u8]) -> anyhow::Result<(Self, &[u8])> {
+        let (magic, bytes) = split(bytes, ARCHIVE_MAGIC.len())?;
+        if magic != ARCHIVE_MAGIC {
+            return Err(anyhow!(
+                "archive does not start with a files archive header"
+            ));
+        }
+        let (version, bytes) = split_u32(bytes)?;
+        if version != ARCHIVE_VERSION {
+            // the rest of the header could have a different layout
+            return Ok((
+                Self {
+                    version,
+                    workspace_root: String::new(),
+                    archive_name: String::new(),
+                },
+                bytes,
+            ));
+        }
+        let (workspace_root, bytes) = split_string(bytes)?;
+        let (archive_name, bytes) = split_string(bytes)?;
+        Ok((
+            Self {
+                version,
+                workspace_root,
+                archive_name,
+            },
+            bytes,
+        ))
+    }
+
+    fn ensure_compatible(&self, expected: &Self) -> anyhow::Result<()> {
+        if self.version != expected.version {
+            return Err(anyhow!(
+                "archive has format version {}, not {}",
+                self.version,
+                expected.version
+            ));
+        }
+        if self.workspace_root != expected.workspace_root {
+            return Err(anyhow!(
+                "archive belongs to the workspace at {}, not {}",
+                self.workspace_root,
+                expected.workspace_root
+            ));
+        }
+        if self.archive_name != expected.archive_name {
+            return Err(anyhow!(
+                "archive was hashed with {}, not {}",
+                self.archive_name,
+                expected.archive_name
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn split(bytes: &[u8], length: usize) -> anyhow::Result<(&[u8], &[u8])> {
+    if bytes.len() < length {
+        return Err(anyhow!("archive header is truncated"));
+    }
+    Ok(bytes.split_at(length))
+}
+
+fn split_u32(bytes: &[u8]) -> anyhow::Result<(u32, &[u8])> {
+    let (value, bytes) = split(bytes, 4)?;
+    Ok((
+        u32::from_le_bytes([value[0], value[1], value[2], value[3]]),
+        bytes,
+    ))
+}
+
+fn split_string(bytes: &[u8]) -> anyhow::Result<(String, &[u8])> {
+    let (length, bytes) = split_u32(bytes)?;
+    let (value, bytes) = split(bytes, length as usize)?;
+    Ok((String::from_utf8(value.to_vec())?, bytes))
+}
+
+/// The files of the archive, and when the archive was written
+#[derive(Debug, PartialEq)]
+pub struct NxFilesArchive {
+    pub files: NxFileHashes,
+    pub mod_time: i64,
+}
+
+pub fn read_files_archive<P: AsRef<Path>>(
+    cache_dir: P,
+    workspace_root: &Path,
+    archive_name: &str,
+) -> Option<NxFilesArchive> {
     let now = std::time::Instant::now();
     let archive_path = cache_dir.as_ref().join(NX_FILES_ARCHIVE);
     if !archive_path.exists() {
         return None;
     }
 
-    let bytes = std::fs::read(archive_path)
+    let expected_header = NxFilesArchiveHeader::new(workspace_root, archive_name);
+    let bytes = std::fs::read(&archive_path)
         .map_err(anyhow::Error::from)
         .and_then(|bytes| {
-            // let archived = unsafe { rkyv::archived_root::<NxFilesArchive>(&bytes) };
-            let archived = rkyv::check_archived_root::<NxFileHashes>(&bytes)
+            let (header, files) = NxFilesArchiveHeader::read(&bytes)?;
+            header.ensure_compatible(&expected_header)?;
+
+            // the archived files need to be aligned, which they are not after the header
+            let mut aligned = AlignedVec::with_capacity(files.len());
+            aligned.extend_from_slice(files);
+            let archived = rkyv::check_archived_root::<NxFileHashes>(&aligned)
                 .map_err(|_| anyhow!("invalid archive file"))?;
-            <ArchivedNxFileHashes as Deserialize<NxFileHashes, Infallible>>::deserialize(
-                archived,
-                &mut rkyv::Infallible,
-            )
-            .map_err(anyhow::Error::from)
+            let files =
+                <ArchivedNxFileHashes as Deserialize<NxFileHashes, Infallible>>::deserialize(
+                    archived,
+                    &mut rkyv::Infallible,
+                )?;
+            Ok(NxFilesArchive {
+                files,
+                mod_time: get_mod_time(&std::fs::metadata(&archive_path)?),
+            })
         });
 
     match bytes {
@@ -70,13 +226,30 @@ pub fn read_files_archive<P: AsRef<Path>>(cache_dir: P) -> Option<NxFileHashes>
     }
 }
 
-pub fn write_files_archive<P: AsRef<Path>>(cache_dir: P, files: NxFileHashes) {
+/// Writes the archive to a temporary file first, and then renames it,
+/// so that a concurrent reader or a crash never sees a partially written archive.
+pub fn write_files_archive<P: AsRef<Path>>(
+    cache_dir: P,
+    workspace_root: &Path,
+    files: NxFileHashes,
+    archive_name: &str,
+) {
     let now = std::time::Instant::now();
     let archive_path = cache_dir.as_ref().join(NX_FILES_ARCHIVE);
+    let temp_path = cache_dir
+        .as_ref()
+        .join(format!("{NX_FILES_ARCHIVE}.{}.tmp", std::process::id()));
     let result = rkyv::to_bytes::<_, 2048>(&files)
         .map_err(anyhow::Error::from)
         .and_then(|encoded| {
-            std::fs::write(archive_path, encoded)?;
+            let mut bytes = Vec::with_capacity(encoded.len() + 256);
+            NxFilesArchiveHeader::new(workspace_root, archive_name).write(&mut bytes);
+            bytes.extend_from_slice(&encoded);
+
+            std::fs::write(&temp_path, bytes)?;
+            std::fs::rename(&temp_path, &archive_path).inspect_err(|_| {
+                let _ = std::fs::remove_file(&temp_path);
+            })?;
             Ok(())
         });
 
@@ -89,3 +262,90 @@ pub fn write_files_archive<P: AsRef<Path>>(cache_dir: P, files: NxFileHashes) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archived_files() -> NxFileHashes {
+        [
+            (
+                "a.txt".to_string(),
+                NxFileHashed(
+                    "1".into(),
+                    NxFileStats {
+                        mod_time: 10,
+                        size: 5,
+                        inode: 1,
+                        change_time: 10,
+                    },
+                ),
+            ),
+            (
+                "b/c.txt".to_string(),
+                NxFileHashed("2".into(), Default::default()),
+            ),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn should_only_read_archives_of_the_same_workspace_and_hash_algorithm() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let workspace_root = Path::new("/workspace");
+        write_files_archive(&cache_dir, workspace_root, archived_files(), "xxh3");
+
+        assert_eq!(
+            read_files_archive(&cache_dir, workspace_root, "xxh3").map(|archive| archive.files),
+            Some(archived_files())
+        );
+        assert_eq!(
+            read_files_archive(&cache_dir, workspace_root, "blake3"),
+            None
+        );
+        assert_eq!(
+            read_files_archive(&cache_dir, Path::new("/other"), "xxh3"),
+            None
+        );
+        // nothing is left behind besides the archive
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn should_detect_incompatible_and_truncated_archives() {
+        let workspace_root = Path::new("/workspace");
+        let mut bytes = vec![];
+        NxFilesArchiveHeader::new(workspace_root, "xxh3").write(&mut bytes);
+        let header_length = bytes.len();
+        bytes.extend_from_slice(&rkyv::to_bytes::<_, 2048>(&archived_files()).unwrap());
+
+        let (header, files) = NxFilesArchiveHeader::read(&bytes).unwrap();
+        assert_eq!(header, NxFilesArchiveHeader::new(workspace_root, "xxh3"));
+        assert_eq!(files.len(), bytes.len() - header_length);
+
+        let mut newer_version = bytes.clone();
+        newer_version[4..8].copy_from_slice(&(ARCHIVE_VERSION + 1).to_le_bytes());
+        let (header, _) = NxFilesArchiveHeader::read(&newer_version).unwrap();
+        assert_eq!(
+            header
+                .ensure_compatible(&NxFilesArchiveHeader::new(workspace_root, "xxh3"))
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "archive has format version {}, not {ARCHIVE_VERSION}",
+                ARCHIVE_VERSION + 1
+            )
+        );
+
+        assert!(NxFilesArchiveHeader::read(&bytes[..header_length - 1]).is_err());
+
+        let cache_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            cache_dir.path().join(NX_FILES_ARCHIVE),
+            &bytes[..bytes.len() - 8],
+        )
+        .unwrap();
+        assert_eq!(read_files_archive(&cache_dir, workspace_root, "xxh3"), None);
+    }
+}
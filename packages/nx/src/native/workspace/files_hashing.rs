@@ -3,30 +3,163 @@ use std::path::Path;
 use std::thread::available_parallelism;
 
 use rayon::prelude::*;
-use tracing::trace;
+use tracing::{debug, trace};
 
-use crate::native::hasher::hash_file_path;
-use crate::native::walker::{nx_walker, NxFile};
-use crate::native::workspace::files_archive::{NxFileHashed, NxFileHashes};
+use crate::native::hasher::{
+    hash_file_path_streaming, hash_file_path_with_algorithm, hash_with_algorithm, HashAlgorithm,
+    STREAMED_FILE_SIZE,
+};
+use crate::native::walker::{nx_walker_with_options, NxFile, WalkOptions};
+use crate::native::workspace::files_archive::{
+    NxFileHashed, NxFileHashes, NxFileStats, NxFilesArchive,
+};
+use crate::native::workspace::git_index::{
+    hash_git_blob, hash_git_blob_file, read_git_index, GitIndex,
+};
 
-pub fn full_files_hash(workspace_root: &Path) -> NxFileHashes {
-    let files = nx_walker(workspace_root, true).collect::<Vec<_>>();
+/// How the contents of workspace files are hashed
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FileHashMode {
+    /// With the hash algorithm of the workspace
+    #[default]
+    HashAlgorithm,
+    /// As git object ids, so that the hashes of unchanged tracked files can be read from the git index
+    GitObjectId,
+}
+
+/// How files above the large file threshold are hashed
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LargeFileHashing {
+    /// By their content, which is streamed instead of read at once
+    #[default]
+    Stream,
+    /// By their size and mod time only, without reading them.
+    /// These hashes differ between machines, so tasks that use large files are not cached remotely.
+    Stats,
+}
+
+/// Files above 8 MiB are not read into memory at once
+pub const DEFAULT_LARGE_FILE_THRESHOLD: u64 = STREAMED_FILE_SIZE;
+
+#[derive(Clone, Copy, Debug)]
+pub struct LargeFileOptions {
+    /// Files with more bytes than this are large files
+    pub threshold: u64,
+    pub hashing: LargeFileHashing,
+}
+
+impl Default for LargeFileOptions {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_LARGE_FILE_THRESHOLD,
+            hashing: LargeFileHashing::default(),
+        }
+    }
+}
+
+/// How the workspace files are walked and hashed
+#[derive(Clone, Debug, Default)]
+pub struct FilesHashingOptions {
+    pub hash_mode: FileHashMode,
+    pub hash_algorithm: HashAlgorithm,
+    pub walk: WalkOptions,
+    /// Hashes the files that were modified after the files archive was written, even when their stats did not change
+    pub paranoid: bool,
+    pub large_files: LargeFileOptions,
+}
+
+impl FilesHashingOptions {
+    pub fn hash(&self, content: &[u8]) -> String {
+        match self.hash_mode {
+            FileHashMode::HashAlgorithm => hash_with_algorithm(self.hash_algorithm, content),
+            FileHashMode::GitObjectId => hash_git_blob(content),
+        }
+    }
+
+    fn hash_file_path(&self, path: &str) -> Option<String> {
+        match self.hash_mode {
+            FileHashMode::HashAlgorithm => hash_file_path_with_algorithm(self.hash_algorithm, path),
+            FileHashMode::GitObjectId => std::fs::read(path)
+                .ok()
+                .map(|content| hash_git_blob(&content)),
+        }
+    }
+
+    fn hash_file_path_streaming(&self, path: &str) -> Option<String> {
+        match self.hash_mode {
+            FileHashMode::HashAlgorithm => hash_file_path_streaming(self.hash_algorithm, path),
+            FileHashMode::GitObjectId => hash_git_blob_file(Path::new(path)),
+        }
+    }
+
+    /// Identifies the hashes in the files archive, so that hashes of another mode, algorithm,
+    /// way of hashing large files or way of walking symlinks and submodules are not reused
+    pub fn archive_name(&self) -> String {
+        let hashing = match self.hash_mode {
+            FileHashMode::HashAlgorithm => self.hash_algorithm.as_ref(),
+            FileHashMode::GitObjectId => "git",
+        };
+        let large_files = match self.large_files.hashing {
+            LargeFileHashing::Stream => "stream",
+            LargeFileHashing::Stats => "stats",
+        };
+        format!(
+            "{hashing};large_files={large_files}:{};symlinks={:?};submodules={}",
+            self.large_files.threshold, self.walk.symlinks, self.walk.include_submodules
+        )
+    }
+
+    fn read_git_index(&self, workspace_root: &Path) -> Option<GitIndex> {
+        if self.hash_mode != FileHashMode::GitObjectId {
+            return None;
+        }
+        read_git_index(workspace_root)
+            .inspect_err(|e| trace!("could not read the git index: {e:?}"))
+            .ok()
+    }
+
+    /// Hashes the content of the file, streaming large files or hashing them by their stats
+    pub fn hash_file_content(&self, path: &str, size: u64, mod_time: i64) -> Option<String> {
+        if size <= self.large_files.threshold {
+            return self.hash_file_path(path);
+        }
+        match self.large_files.hashing {
+            LargeFileHashing::Stream => self.hash_file_path_streaming(path),
+            LargeFileHashing::Stats => Some(self.hash(format!("{size}:{mod_time}").as_bytes())),
+        }
+    }
+}
+
+pub fn full_files_hash(workspace_root: &Path, options: FilesHashingOptions) -> NxFileHashes {
+    let files =
+        nx_walker_with_options(workspace_root, true, options.walk.clone()).collect::<Vec<_>>();
     trace!("Found {} files", files.len());
-    hash_files(files).into_iter().collect()
+    let git_index = options.read_git_index(workspace_root);
+    hash_files(files, &options, git_index.as_ref())
+        .into_iter()
+        .collect()
 }
 
 pub fn selective_files_hash(
     workspace_root: &Path,
-    mut archived_files: NxFileHashes,
+    archive: NxFilesArchive,
+    options: FilesHashingOptions,
 ) -> NxFileHashes {
-    let files = nx_walker(workspace_root, true).collect::<Vec<_>>();
+    let NxFilesArchive {
+        files: mut archived_files,
+        mod_time: archive_mod_time,
+    } = archive;
+    let files =
+        nx_walker_with_options(workspace_root, true, options.walk.clone()).collect::<Vec<_>>();
     let mut archived = vec![];
     let mut not_archived = vec![];
     let now = std::time::Instant::now();
 
     for file in files {
         if let Some(archived_file) = archived_files.remove(&file.normalized_path) {
-            if archived_file.1 == file.mod_time {
+            // files modified in the same second as the archive could have changed without changing their stats
+            let is_newer_than_archive = options.paranoid && file.mod_time >= archive_mod_time;
+            if archived_file.1 == NxFileStats::from(&file) && !is_newer_than_archive {
                 archived.push((file.normalized_path, archived_file));
                 continue;
             }
@@ -40,13 +173,33 @@ pub fn selective_files_hash(
         return archived.into_iter().collect();
     }
 
+    let git_index = options.read_git_index(workspace_root);
     archived
         .into_iter()
-        .chain(hash_files(not_archived))
+        .chain(hash_files(not_archived, &options, git_index.as_ref()))
         .collect()
 }
 
-fn hash_files(files: Vec<NxFile>) -> Vec<(String, NxFileHashed)> {
+/// How many of the largest files are reported when large files are hashed
+const REPORTED_LARGE_FILES: usize = 10;
+
+fn hash_files(
+    files: Vec<NxFile>,
+    options: &FilesHashingOptions,
+    git_index: Option<&GitIndex>,
+) -> Vec<(String, NxFileHashed)> {
+    report_large_files(&files, options.large_files.threshold);
+
+    let hash_file = |file: &NxFile| {
+        if let Some(link_target) = &file.link_target {
+            return Some(options.hash(link_target.as_bytes()));
+        }
+        git_index
+            .and_then(|git_index| git_index.get_clean_hash(file))
+            .map(String::from)
+            .or_else(|| options.hash_file_content(&file.full_path, file.size, file.mod_time))
+    };
+
     let num_parallelism = cmp::max(available_parallelism().map_or(2, |n| n.get()) / 3, 2);
     let chunks = files.len() / num_parallelism;
 
@@ -56,20 +209,26 @@ fn hash_files(files: Vec<NxFile>) -> Vec<(String, NxFileHashed)> {
         files
             .into_par_iter()
             .filter_map(|file| {
-                hash_file_path(&file.full_path)
-                    .map(|hash| (file.normalized_path, NxFileHashed(hash, file.mod_time)))
+                hash_file(&file).map(|hash| {
+                    let stats = NxFileStats::from(&file);
+                    (file.normalized_path, NxFileHashed(hash, stats))
+                })
             })
             .collect::<Vec<_>>()
     } else {
-        trace!("hashing workspace files in {} chunks of {}", num_parallelism, chunks);
+        trace!(
+            "hashing workspace files in {} chunks of {}",
+            num_parallelism,
+            chunks
+        );
         files
             .par_chunks(chunks)
             .flat_map_iter(|chunks| {
                 chunks.iter().filter_map(|file| {
-                    hash_file_path(&file.full_path).map(|hash| {
+                    hash_file(file).map(|hash| {
                         (
                             file.normalized_path.clone(),
-                            NxFileHashed(hash, file.mod_time),
+                            NxFileHashed(hash, NxFileStats::from(file)),
                         )
                     })
                 })
@@ -81,13 +240,36 @@ fn hash_files(files: Vec<NxFile>) -> Vec<(String, NxFileHashed)> {
     files
 }
 
+/// Logs the largest files above the threshold, which are good candidates for the .nxignore
+fn report_large_files(files: &[NxFile], threshold: u64) {
+    let mut large_files = files
+        .iter()
+        .filter(|file| file.link_target.is_none() && file.size > threshold)
+        .collect::<Vec<_>>();
+    if large_files.is_empty() {
+        return;
+    }
+    large_files.sort_by_key(|file| cmp::Reverse(file.size));
+    debug!(
+        "hashing {} files larger than {} bytes, the largest are:",
+        large_files.len(),
+        threshold
+    );
+    for file in large_files.iter().take(REPORTED_LARGE_FILES) {
+        debug!("  {} ({} bytes)", file.normalized_path, file.size);
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
+
     use assert_fs::prelude::*;
     use assert_fs::TempDir;
 
-    use crate::native::utils::get_mod_time;
-    use crate::native::workspace::files_archive::{NxFileHashed, NxFileHashes};
+    use super::*;
+    use crate::native::utils::{get_inode_and_change_time, get_mod_time};
+    use crate::native::walker::SymlinkPolicy;
 
     fn setup_fs() -> TempDir {
         let temp = TempDir::new().unwrap();
@@ -103,29 +285,42 @@ mod tests {
         temp
     }
 
+    fn stats(path: &Path) -> NxFileStats {
+        let metadata = path.metadata().unwrap();
+        let (inode, change_time) = get_inode_and_change_time(&metadata);
+        NxFileStats {
+            mod_time: get_mod_time(&metadata),
+            size: metadata.len(),
+            inode,
+            change_time,
+        }
+    }
+
+    fn archive(files: NxFileHashes, mod_time: i64) -> NxFilesArchive {
+        NxFilesArchive { files, mod_time }
+    }
+
     #[test]
     fn should_selectively_hash_workspace() {
         let temp = setup_fs();
         let archived_files = vec![
             (
                 String::from("test.txt"),
-                NxFileHashed(
-                    String::from("hash1"),
-                    get_mod_time(&temp.child("test.txt").metadata().unwrap()),
-                ),
+                NxFileHashed(String::from("hash1"), stats(&temp.child("test.txt"))),
             ),
             (
                 String::from("foo.txt"),
-                NxFileHashed(
-                    String::from("hash2"),
-                    get_mod_time(&temp.child("foo.txt").metadata().unwrap()),
-                ),
+                NxFileHashed(String::from("hash2"), stats(&temp.child("foo.txt"))),
             ),
+            // this file was resized within the same mod time
             (
                 String::from("bar.txt"),
                 NxFileHashed(
                     String::from("hash3"),
-                    get_mod_time(&temp.child("bar.txt").metadata().unwrap()),
+                    NxFileStats {
+                        size: 1,
+                        ..stats(&temp.child("bar.txt"))
+                    },
                 ),
             ),
             // this file was modified, so the mod time in the archive should be less than whats on the fs to simulate a write
@@ -133,33 +328,172 @@ mod tests {
                 String::from("modified.txt"),
                 NxFileHashed(
                     String::from("hash4"),
-                    get_mod_time(&temp.child("modified.txt").metadata().unwrap()) - 10,
+                    NxFileStats {
+                        mod_time: get_mod_time(&temp.child("modified.txt").metadata().unwrap())
+                            - 10,
+                        ..stats(&temp.child("modified.txt"))
+                    },
                 ),
             ),
             // this file is does not exist on the fs, aka it was deleted
             (
                 String::from("baz/qux.txt"),
-                NxFileHashed(String::from("hash5"), 0),
+                NxFileHashed(String::from("hash5"), Default::default()),
             ),
         ]
         .into_iter()
         .collect::<NxFileHashes>();
 
-        let hashed_files = super::selective_files_hash(temp.path(), archived_files);
+        let hashed_files =
+            selective_files_hash(temp.path(), archive(archived_files, 0), Default::default());
         let mut hashed_files = hashed_files
             .iter()
-            .map(|(path, _)| path.as_str())
+            .map(|(path, hashed)| (path.as_str(), hashed.0.starts_with("hash")))
             .collect::<Vec<_>>();
         hashed_files.sort();
         assert_eq!(
             hashed_files,
             vec![
-                "bar.txt",
-                "baz/new.txt",
-                "foo.txt",
-                "modified.txt",
-                "test.txt"
+                ("bar.txt", false),
+                ("baz/new.txt", false),
+                ("foo.txt", true),
+                ("modified.txt", false),
+                ("test.txt", true)
             ]
         )
     }
+
+    #[test]
+    fn should_rehash_files_newer_than_the_archive_when_paranoid() {
+        let temp = setup_fs();
+        let archived_files = || {
+            ["test.txt", "foo.txt"]
+                .into_iter()
+                .map(|path| {
+                    (
+                        path.to_string(),
+                        NxFileHashed(String::from("archived"), stats(&temp.child(path))),
+                    )
+                })
+                .collect::<NxFileHashes>()
+        };
+        let file_mod_time = stats(&temp.child("test.txt")).mod_time;
+        let paranoid = FilesHashingOptions {
+            paranoid: true,
+            ..Default::default()
+        };
+        let is_archived = |hashed_files: &NxFileHashes| hashed_files["test.txt"].0 == "archived";
+
+        // the archive was written in the same second as the files were modified
+        let hashed_files = selective_files_hash(
+            temp.path(),
+            archive(archived_files(), file_mod_time),
+            paranoid.clone(),
+        );
+        assert!(!is_archived(&hashed_files));
+
+        let hashed_files = selective_files_hash(
+            temp.path(),
+            archive(archived_files(), file_mod_time + 10),
+            paranoid,
+        );
+        assert!(is_archived(&hashed_files));
+
+        let hashed_files = selective_files_hash(
+            temp.path(),
+            archive(archived_files(), file_mod_time),
+            Default::default(),
+        );
+        assert!(is_archived(&hashed_files));
+    }
+
+    #[test]
+    fn should_stream_or_skip_the_content_of_large_files() {
+        let temp = setup_fs();
+        let with_large_files = |hashing| FilesHashingOptions {
+            large_files: LargeFileOptions {
+                threshold: 7,
+                hashing,
+            },
+            ..Default::default()
+        };
+        let hashes = |options| full_files_hash(temp.path(), options);
+
+        let read = hashes(FilesHashingOptions::default());
+        let streamed = hashes(with_large_files(LargeFileHashing::Stream));
+        assert_eq!(read, streamed);
+
+        let by_stats = hashes(with_large_files(LargeFileHashing::Stats));
+        // only files with more than 7 bytes are hashed by their stats
+        assert_eq!(by_stats["test.txt"], read["test.txt"]);
+        assert_ne!(by_stats["foo.txt"], read["foo.txt"]);
+        assert_ne!(by_stats["baz/new.txt"], read["baz/new.txt"]);
+    }
+
+    #[test]
+    fn should_hash_with_the_hash_algorithm_of_the_options() {
+        let temp = setup_fs();
+        let hashes = |hash_algorithm| {
+            full_files_hash(
+                temp.path(),
+                FilesHashingOptions {
+                    hash_algorithm,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let blake3 = hashes(HashAlgorithm::blake3);
+        assert_eq!(
+            blake3["test.txt"].0,
+            hash_with_algorithm(HashAlgorithm::blake3, b"content")
+        );
+        assert_ne!(
+            blake3["test.txt"].0,
+            hashes(HashAlgorithm::xxh3)["test.txt"].0
+        );
+    }
+
+    #[test]
+    fn should_name_the_archive_by_the_hashing_options() {
+        let options = FilesHashingOptions::default();
+        assert_eq!(
+            options.archive_name(),
+            "xxh3;large_files=stream:8388608;symlinks=content;submodules=true"
+        );
+
+        let with_large_files = |threshold, hashing| FilesHashingOptions {
+            large_files: LargeFileOptions { threshold, hashing },
+            ..Default::default()
+        };
+        assert_ne!(
+            with_large_files(DEFAULT_LARGE_FILE_THRESHOLD, LargeFileHashing::Stats).archive_name(),
+            options.archive_name()
+        );
+        assert_ne!(
+            with_large_files(1024, LargeFileHashing::Stream).archive_name(),
+            options.archive_name()
+        );
+
+        let with_walk = |walk| FilesHashingOptions {
+            walk,
+            ..Default::default()
+        };
+        assert_ne!(
+            with_walk(WalkOptions {
+                symlinks: SymlinkPolicy::record,
+                ..Default::default()
+            })
+            .archive_name(),
+            options.archive_name()
+        );
+        assert_ne!(
+            with_walk(WalkOptions {
+                include_submodules: false,
+                ..Default::default()
+            })
+            .archive_name(),
+            options.archive_name()
+        );
+    }
 }
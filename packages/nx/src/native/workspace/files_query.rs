@@ -0,0 +1,311 @@
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use rayon::prelude::*;
+
+use crate::native::glob::{build_glob_set, NxGlobSet};
+use crate::native::project_graph::utils::{find_project_for_path, ProjectRootMappings};
+use crate::native::types::FileData;
+
+/// How many files are returned at once when the query has no limit
+const DEFAULT_PAGE_SIZE: u32 = 1000;
+
+#[napi(object)]
+#[derive(Default)]
+pub struct WorkspaceFilesQuery {
+    /// Only files that match any of these globs
+    pub globs: Option<Vec<String>>,
+    /// Leaves out files that match any of these globs
+    pub exclude: Option<Vec<String>>,
+    /// Only files of these projects, which needs the project root map
+    pub projects: Option<Vec<String>>,
+    /// Only files with one of these extensions, such as `ts` or `d.ts`
+    pub extensions: Option<Vec<String>>,
+    /// Only files of at least this many bytes
+    pub min_size: Option<i64>,
+    /// Only files of at most this many bytes
+    pub max_size: Option<i64>,
+    /// Only files modified at or after this time, in milliseconds since the unix epoch
+    pub modified_after: Option<i64>,
+    /// Only files modified before this time, in milliseconds since the unix epoch
+    pub modified_before: Option<i64>,
+    /// Only files after this one, which is the `next` file of the previous page
+    pub after: Option<String>,
+    /// How many files are returned at most, 1000 by default
+    pub limit: Option<u32>,
+}
+
+#[napi(object)]
+#[derive(Debug, PartialEq)]
+pub struct WorkspaceFileMetadata {
+    pub file: String,
+    pub hash: String,
+    pub project: Option<String>,
+    pub size: i64,
+    /// In milliseconds since the unix epoch
+    pub mod_time: i64,
+}
+
+#[napi(object)]
+#[derive(Debug, PartialEq)]
+pub struct WorkspaceFilesPage {
+    pub files: Vec<WorkspaceFileMetadata>,
+    /// Pass this as `after` to get the next page, which is missing on the last page
+    pub next: Option<String>,
+}
+
+struct FilesFilter<'a> {
+    globs: Option<NxGlobSet>,
+    exclude: Option<NxGlobSet>,
+    projects: Option<Vec<String>>,
+    extensions: Option<Vec<String>>,
+    project_root_map: Option<&'a ProjectRootMappings>,
+    after: Option<&'a Path>,
+}
+
+impl<'a> FilesFilter<'a> {
+    fn new(
+        query: &'a WorkspaceFilesQuery,
+        project_root_map: Option<&'a ProjectRootMappings>,
+    ) -> anyhow::Result<Self> {
+        let glob_set = |globs: &Option<Vec<String>>| {
+            globs
+                .as_ref()
+                .filter(|globs| !globs.is_empty())
+                .map(|globs| build_glob_set(globs))
+                .transpose()
+        };
+        if query.projects.is_some() && project_root_map.is_none() {
+            anyhow::bail!("files can only be queried by project with the project root map");
+        }
+        Ok(Self {
+            globs: glob_set(&query.globs)?,
+            exclude: glob_set(&query.exclude)?,
+            projects: query.projects.clone(),
+            extensions: query.extensions.as_ref().map(|extensions| {
+                extensions
+                    .iter()
+                    .map(|extension| format!(".{}", extension.trim_start_matches('.')))
+                    .collect()
+            }),
+            project_root_map,
+            after: query.after.as_deref().map(Path::new),
+        })
+    }
+
+    fn project(&self, file: &str) -> Option<String> {
+        self.project_root_map
+            .and_then(|project_root_map| find_project_for_path(file, project_root_map))
+            .map(String::from)
+    }
+
+    /// Whether the file matches the filters that do not need to read the file
+    fn matches(&self, file: &FileData, project: Option<&str>) -> bool {
+        let path = file.file.as_str();
+        // the workspace files are sorted as paths, which is not the same as sorting them as strings
+        self.after.is_none_or(|after| Path::new(path) > after)
+            && self.globs.as_ref().is_none_or(|globs| globs.is_match(path))
+            && self
+                .exclude
+                .as_ref()
+                .is_none_or(|exclude| !exclude.is_match(path))
+            && self.extensions.as_ref().is_none_or(|extensions| {
+                extensions.iter().any(|extension| path.ends_with(extension))
+            })
+            && self.projects.as_ref().is_none_or(|projects| {
+                project.is_some_and(|project| projects.iter().any(|p| p == project))
+            })
+    }
+}
+
+impl WorkspaceFilesQuery {
+    fn matches_metadata(&self, file: &WorkspaceFileMetadata) -> bool {
+        self.min_size.is_none_or(|min_size| file.size >= min_size)
+            && self.max_size.is_none_or(|max_size| file.size <= max_size)
+            && self
+                .modified_after
+                .is_none_or(|modified_after| file.mod_time >= modified_after)
+            && self
+                .modified_before
+                .is_none_or(|modified_before| file.mod_time < modified_before)
+    }
+}
+
+/// Queries the sorted workspace files one page at a time.
+/// Only the files that match the other filters are read, and only until the page is full.
+pub(super) fn query_files(
+    workspace_root: &Path,
+    files: &[FileData],
+    query: &WorkspaceFilesQuery,
+    project_root_map: Option<&ProjectRootMappings>,
+) -> anyhow::Result<WorkspaceFilesPage> {
+    let filter = FilesFilter::new(query, project_root_map)?;
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1) as usize;
+
+    let candidates = files
+        .par_iter()
+        .filter_map(|file| {
+            let project = filter.project(&file.file);
+            filter
+                .matches(file, project.as_deref())
+                .then_some((file, project))
+        })
+        .collect::<Vec<_>>();
+
+    let mut page = Vec::with_capacity(limit + 1);
+    // one more file than the limit tells whether there is a next page
+    for chunk in candidates.chunks(limit + 1) {
+        let matched = chunk
+            .par_iter()
+            .filter_map(|(file, project)| {
+                let metadata = std::fs::metadata(workspace_root.join(&file.file)).ok()?;
+                let mod_time = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |modified| modified.as_millis() as i64);
+                Some(WorkspaceFileMetadata {
+                    file: file.file.clone(),
+                    hash: file.hash.clone(),
+                    project: project.clone(),
+                    size: metadata.len() as i64,
+                    mod_time,
+                })
+            })
+            .filter(|file| query.matches_metadata(file))
+            .collect::<Vec<_>>();
+        page.extend(matched);
+        if page.len() > limit {
+            break;
+        }
+    }
+
+    let next = if page.len() > limit {
+        page.truncate(limit);
+        page.last().map(|file| file.file.clone())
+    } else {
+        None
+    };
+    Ok(WorkspaceFilesPage { files: page, next })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+
+    use super::*;
+
+    fn setup_files() -> (TempDir, Vec<FileData>) {
+        let temp = TempDir::new().unwrap();
+        let files = [
+            ("libs/a/index.ts", "a"),
+            ("libs/a/index.d.ts", "a"),
+            ("libs/a-b/big.ts", "bigger content"),
+            ("libs/a/src/lib.ts", "lib"),
+            ("package.json", "{}"),
+        ];
+        for (file, content) in files {
+            temp.child(file).write_str(content).unwrap();
+        }
+        let mut files = files
+            .into_iter()
+            .map(|(file, _)| FileData {
+                file: file.into(),
+                hash: "hash".into(),
+            })
+            .collect::<Vec<_>>();
+        files.sort_by(|a, b| Path::new(&a.file).cmp(Path::new(&b.file)));
+        (temp, files)
+    }
+
+    fn file_names(page: &WorkspaceFilesPage) -> Vec<&str> {
+        page.files.iter().map(|file| file.file.as_str()).collect()
+    }
+
+    #[test]
+    fn should_query_files_by_glob_extension_project_and_size() {
+        let (temp, files) = setup_files();
+        let project_root_map: ProjectRootMappings = HashMap::from([
+            ("libs/a".to_string(), "a".to_string()),
+            ("libs/a-b".to_string(), "a-b".to_string()),
+        ]);
+
+        let query = |query: WorkspaceFilesQuery| {
+            query_files(temp.path(), &files, &query, Some(&project_root_map)).unwrap()
+        };
+
+        let page = query(WorkspaceFilesQuery {
+            globs: Some(vec!["libs/**".into()]),
+            exclude: Some(vec!["**/*.d.ts".into()]),
+            ..Default::default()
+        });
+        assert_eq!(
+            file_names(&page),
+            vec!["libs/a/index.ts", "libs/a/src/lib.ts", "libs/a-b/big.ts"]
+        );
+        assert_eq!(page.files[2].project.as_deref(), Some("a-b"));
+        assert_eq!(page.files[2].size, 14);
+
+        let page = query(WorkspaceFilesQuery {
+            extensions: Some(vec!["d.ts".into(), ".json".into()]),
+            ..Default::default()
+        });
+        assert_eq!(file_names(&page), vec!["libs/a/index.d.ts", "package.json"]);
+
+        let page = query(WorkspaceFilesQuery {
+            projects: Some(vec!["a".into()]),
+            max_size: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(
+            file_names(&page),
+            vec!["libs/a/index.d.ts", "libs/a/index.ts"]
+        );
+
+        let page = query(WorkspaceFilesQuery {
+            modified_after: Some(page.files[0].mod_time + 60_000),
+            ..Default::default()
+        });
+        assert!(page.files.is_empty());
+
+        assert!(query_files(
+            temp.path(),
+            &files,
+            &WorkspaceFilesQuery {
+                projects: Some(vec!["a".into()]),
+                ..Default::default()
+            },
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn should_query_files_one_page_at_a_time() {
+        let (temp, files) = setup_files();
+        let mut after = None;
+        let mut pages = vec![];
+        loop {
+            let page = query_files(
+                temp.path(),
+                &files,
+                &WorkspaceFilesQuery {
+                    after,
+                    limit: Some(2),
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+            pages.push(file_names(&page).len());
+            after = page.next;
+            if after.is_none() {
+                break;
+            }
+        }
+        assert_eq!(pages, vec![2, 2, 1]);
+    }
+}
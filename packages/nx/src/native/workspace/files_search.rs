@@ -0,0 +1,203 @@
+use std::path::Path;
+
+use rayon::prelude::*;
+use regex::RegexBuilder;
+use tracing::trace;
+
+use crate::native::types::FileData;
+use crate::native::workspace::config_files;
+
+/// Files with more bytes than this are not searched by default
+const DEFAULT_MAX_FILE_SIZE: u32 = 1024 * 1024;
+
+#[napi(object)]
+#[derive(Default)]
+pub struct SearchOptions {
+    /// Leaves out files that match any of these globs
+    pub exclude: Option<Vec<String>>,
+    /// Matches the pattern regardless of case
+    pub case_insensitive: Option<bool>,
+    /// Returns at most this many matches, of the first files that match
+    pub max_matches: Option<u32>,
+    /// Files with more bytes than this are not searched, 1 MiB by default
+    pub max_file_size: Option<u32>,
+}
+
+#[napi(object)]
+#[derive(Debug, PartialEq)]
+pub struct SearchMatch {
+    pub file: String,
+    /// Starts at 1
+    pub line: u32,
+    /// Starts at 1, and counts characters instead of bytes
+    pub column: u32,
+    /// The line that matched, without the line ending
+    pub text: String,
+    /// The part of the line that matched
+    pub matched: String,
+}
+
+/// Searches the content of the workspace files line by line, in the order of the files.
+/// Files that are not valid UTF-8, such as binary files, are not searched.
+pub(super) fn search_files(
+    workspace_root: &Path,
+    files: &[FileData],
+    pattern: &str,
+    globs: Vec<String>,
+    options: SearchOptions,
+) -> anyhow::Result<Vec<SearchMatch>> {
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(options.case_insensitive.unwrap_or(false))
+        .build()?;
+    let max_file_size = u64::from(options.max_file_size.unwrap_or(DEFAULT_MAX_FILE_SIZE));
+
+    let files: Vec<&FileData> = if globs.is_empty() {
+        let exclude = options.exclude.unwrap_or_default();
+        config_files::glob_files(files, vec!["**/*".into()], Some(exclude))?.collect()
+    } else {
+        config_files::glob_files(files, globs, options.exclude)?.collect()
+    };
+
+    let now = std::time::Instant::now();
+    let mut matches = files
+        .par_iter()
+        .flat_map_iter(|file_data| {
+            let full_path = workspace_root.join(&file_data.file);
+            let content = std::fs::metadata(&full_path)
+                .ok()
+                .filter(|metadata| metadata.len() <= max_file_size)
+                .and_then(|_| std::fs::read_to_string(&full_path).ok())
+                .filter(|content| regex.is_match(content));
+            let Some(content) = content else {
+                return vec![];
+            };
+
+            content
+                .lines()
+                .enumerate()
+                .flat_map(|(index, line)| {
+                    regex.find_iter(line).map(move |found| SearchMatch {
+                        file: file_data.file.clone(),
+                        line: index as u32 + 1,
+                        column: line[..found.start()].chars().count() as u32 + 1,
+                        text: line.to_string(),
+                        matched: found.as_str().to_string(),
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    trace!(
+        "searched {} files for {pattern} in {:?}",
+        files.len(),
+        now.elapsed()
+    );
+
+    if let Some(max_matches) = options.max_matches {
+        matches.truncate(max_matches as usize);
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+
+    use super::*;
+
+    fn setup_files() -> (TempDir, Vec<FileData>) {
+        let temp = TempDir::new().unwrap();
+        let files = [
+            (
+                "libs/a/index.ts",
+                "import { b } from 'b';\nexport const a = 'äb' + b;\n",
+            ),
+            ("libs/b/index.ts", "export const b = 'B';\n"),
+            ("libs/b/README.md", "# b\n"),
+        ];
+        for (file, content) in files {
+            temp.child(file).write_str(content).unwrap();
+        }
+        temp.child("image.png")
+            .write_binary(&[0xff, 0xfe, b'b'])
+            .unwrap();
+        let mut files = files
+            .iter()
+            .map(|(file, _)| file.to_string())
+            .chain(["image.png".to_string()])
+            .map(|file| FileData {
+                file,
+                hash: "hash".into(),
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        (temp, files)
+    }
+
+    #[test]
+    fn should_search_the_lines_of_matching_files() {
+        let (temp, files) = setup_files();
+
+        let matches = search_files(
+            temp.path(),
+            &files,
+            "b\\b",
+            vec!["**/*.ts".into()],
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            matches
+                .iter()
+                .map(|m| (m.file.as_str(), m.line, m.column))
+                .collect::<Vec<_>>(),
+            vec![
+                ("libs/a/index.ts", 1, 10),
+                ("libs/a/index.ts", 1, 20),
+                ("libs/a/index.ts", 2, 20),
+                ("libs/a/index.ts", 2, 25),
+                ("libs/b/index.ts", 1, 14),
+            ]
+        );
+        assert_eq!(matches[2].text, "export const a = 'äb' + b;");
+        assert_eq!(matches[2].matched, "b");
+    }
+
+    #[test]
+    fn should_search_every_file_with_options() {
+        let (temp, files) = setup_files();
+
+        let matches = search_files(
+            temp.path(),
+            &files,
+            "^(# )?B",
+            vec![],
+            SearchOptions {
+                case_insensitive: Some(true),
+                exclude: Some(vec!["**/*.ts".into()]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            matches.iter().map(|m| m.file.as_str()).collect::<Vec<_>>(),
+            vec!["libs/b/README.md"]
+        );
+
+        let matches = search_files(
+            temp.path(),
+            &files,
+            "b",
+            vec![],
+            SearchOptions {
+                max_matches: Some(2),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(matches.len(), 2);
+
+        assert!(search_files(temp.path(), &files, "(", vec![], Default::default()).is_err());
+    }
+}
@@ -0,0 +1,492 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::Metadata;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{anyhow, bail};
+use sha1_smol::Sha1;
+use tracing::trace;
+
+use crate::native::hasher::read_file_in_chunks;
+use crate::native::utils::Normalize;
+use crate::native::walker::NxFile;
+
+const ENTRY_HEADER_LENGTH: usize = 62;
+const EXTENDED_FLAG: u16 = 0x4000;
+const STAGE_MASK: u16 = 0x3000;
+const INTENT_TO_ADD_FLAG: u16 = 0x2000;
+const FILE_TYPE_MASK: u32 = 0o170000;
+const REGULAR_FILE_TYPE: u32 = 0o100000;
+
+struct GitIndexEntry {
+    modified: (u32, u32),
+    size: u32,
+    object_id: String,
+}
+
+/// The tracked files of the git index, by their path relative to the workspace root
+pub struct GitIndex {
+    entries: HashMap<String, GitIndexEntry>,
+    /// Files that were modified at or after the index was written could have changed without changing their stats
+    modified: (u32, u32),
+}
+
+impl GitIndex {
+    /// Gets the object id of a file when it did not change since it was added to the index.
+    /// Git decides the same way whether a file changed, by comparing its size and modified time with the index.
+    pub fn get_clean_hash(&self, file: &NxFile) -> Option<&str> {
+        let entry = self.entries.get(&file.normalized_path)?;
+        let metadata = std::fs::symlink_metadata(&file.full_path).ok()?;
+        if !metadata.is_file() || metadata.len() as u32 != entry.size {
+            return None;
+        }
+
+        let (seconds, nanoseconds) = get_modified(&metadata)?;
+        let (entry_seconds, entry_nanoseconds) = entry.modified;
+        // git only records nanoseconds on some platforms
+        let unchanged = seconds == entry_seconds
+            && (entry_nanoseconds == 0 || nanoseconds == entry_nanoseconds);
+        if !unchanged || entry.modified >= self.modified {
+            return None;
+        }
+        Some(&entry.object_id)
+    }
+}
+
+/// Reads the git index of the repository that contains the workspace.
+/// Only the sha1 object format is supported.
+///
+/// Files that git converts between the index and the working tree, e.g. with LFS or other filters,
+/// are left out, so that they are hashed by their content whether or not they changed.
+/// The index is not used at all when `core.autocrlf` converts the line endings of every text file.
+pub fn read_git_index(workspace_root: &Path) -> anyhow::Result<GitIndex> {
+    let (repository_root, git_dir) = find_git_dir(workspace_root)
+        .ok_or_else(|| anyhow!("{} is not in a git repository", workspace_root.display()))?;
+    if std::fs::read_to_string(git_dir.join("config"))
+        .is_ok_and(|config| config.contains("objectformat = sha256"))
+    {
+        bail!("git repositories with sha256 object ids are not supported");
+    }
+
+    let index_path = git_dir.join("index");
+    let modified = get_modified(&std::fs::metadata(&index_path)?)
+        .ok_or_else(|| anyhow!("could not read when the git index was modified"))?;
+    let bytes = std::fs::read(&index_path)?;
+    let prefix = workspace_root
+        .strip_prefix(&repository_root)?
+        .to_normalized_string();
+
+    let now = std::time::Instant::now();
+    let mut entries = parse_entries(&bytes, &prefix)?;
+    let converted_files = find_converted_files(workspace_root, entries.keys())?;
+    entries.retain(|file, _| !converted_files.contains(file));
+    trace!(
+        "read {} git index entries in {:?}",
+        entries.len(),
+        now.elapsed()
+    );
+
+    Ok(GitIndex { entries, modified })
+}
+
+/// Finds the files whose content in the working tree is not the content of their object in the index.
+/// Filters, `ident` and `working-tree-encoding` change the content of a file when it is checked out,
+/// and so do `text` and `eol=crlf` when line endings are checked out as CRLF.
+fn find_converted_files<'a>(
+    workspace_root: &Path,
+    files: impl Iterator<Item = &'a String>,
+) -> anyhow::Result<HashSet<String>> {
+    let config = read_line_ending_config(workspace_root)?;
+    let autocrlf = config.get("core.autocrlf").map(String::as_str);
+    if autocrlf == Some("true") {
+        bail!("core.autocrlf converts the line endings of the files in the working tree");
+    }
+    let crlf_checkout = match config.get("core.eol").map(String::as_str) {
+        Some("crlf") => true,
+        Some("lf") => false,
+        _ => cfg!(windows),
+    };
+
+    let mut child = Command::new("git")
+        .args([
+            "check-attr",
+            "-z",
+            "--stdin",
+            "filter",
+            "ident",
+            "working-tree-encoding",
+            "text",
+            "eol",
+        ])
+        .current_dir(workspace_root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let input = files.fold(Vec::new(), |mut input, file| {
+        input.extend_from_slice(file.as_bytes());
+        input.push(0);
+        input
+    });
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("could not write to git check-attr"))?;
+    // git writes the attributes while it reads the paths, so they are written from another thread
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    writer
+        .join()
+        .map_err(|_| anyhow!("could not write to git check-attr"))??;
+    if !output.status.success() {
+        bail!(
+            "git check-attr failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let output = String::from_utf8_lossy(&output.stdout);
+    let fields = output.split('\0').collect::<Vec<_>>();
+    Ok(fields
+        .chunks_exact(3)
+        .filter(|attribute| {
+            let [_, name, value] = attribute else {
+                return false;
+            };
+            match (*name, *value) {
+                (_, "unspecified" | "unset") => false,
+                ("text", _) => crlf_checkout,
+                ("eol", value) => value == "crlf",
+                _ => true,
+            }
+        })
+        .map(|attribute| attribute[0].to_string())
+        .collect())
+}
+
+/// Reads the git config that decides how line endings are checked out.
+/// Git is asked for it, so that the global and system config are included.
+fn read_line_ending_config(workspace_root: &Path) -> anyhow::Result<HashMap<String, String>> {
+    let output = Command::new("git")
+        .args(["config", "--get-regexp", r"^core\.(autocrlf|eol)$"])
+        .current_dir(workspace_root)
+        .output()?;
+    // the exit code is 1 when none of the keys are set
+    if !output.status.success() && output.status.code() != Some(1) {
+        bail!(
+            "git config failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(key, value)| (key.to_lowercase(), value.to_lowercase()))
+        .collect())
+}
+
+/// Finds the repository root and the git directory, which is somewhere else for worktrees and submodules
+fn find_git_dir(workspace_root: &Path) -> Option<(PathBuf, PathBuf)> {
+    workspace_root.ancestors().find_map(|directory| {
+        let dot_git = directory.join(".git");
+        if dot_git.is_dir() {
+            return Some((directory.to_owned(), dot_git));
+        }
+        let git_file = std::fs::read_to_string(&dot_git).ok()?;
+        let git_dir = git_file.strip_prefix("gitdir:")?.trim();
+        Some((directory.to_owned(), directory.join(git_dir)))
+    })
+}
+
+fn get_modified(metadata: &Metadata) -> Option<(u32, u32)> {
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified.as_secs() as u32, modified.subsec_nanos()))
+}
+
+/// Parses the entries of versions 2 to 4 of the index format.
+/// Conflicted, intent-to-add, symlink and submodule entries are skipped, so that their files are hashed.
+fn parse_entries(bytes: &[u8], prefix: &str) -> anyhow::Result<HashMap<String, GitIndexEntry>> {
+    if bytes.get(0..4) != Some(b"DIRC".as_slice()) {
+        bail!("invalid git index signature");
+    }
+    let version = read_u32(bytes, 4)?;
+    if !(2..=4).contains(&version) {
+        bail!("unsupported git index version {version}");
+    }
+    let count = read_u32(bytes, 8)? as usize;
+
+    let mut entries = HashMap::with_capacity(count);
+    let mut offset = 12;
+    let mut path: Vec<u8> = vec![];
+    for _ in 0..count {
+        let header = bytes
+            .get(offset..offset + ENTRY_HEADER_LENGTH)
+            .ok_or_else(|| anyhow!("git index entry is out of bounds"))?;
+        let mode = read_u32(header, 24)?;
+        let flags = u16::from_be_bytes([header[60], header[61]]);
+        let entry = GitIndexEntry {
+            modified: (read_u32(header, 8)?, read_u32(header, 12)?),
+            size: read_u32(header, 36)?,
+            object_id: header[40..60]
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+        };
+
+        let mut position = offset + ENTRY_HEADER_LENGTH;
+        let mut extended_flags = 0;
+        if flags & EXTENDED_FLAG != 0 {
+            let extended = bytes
+                .get(position..position + 2)
+                .ok_or_else(|| anyhow!("git index entry is out of bounds"))?;
+            extended_flags = u16::from_be_bytes([extended[0], extended[1]]);
+            position += 2;
+        }
+
+        if version == 4 {
+            // paths are compressed by removing the end of the previous path and appending the rest
+            let (removed, length) =
+                read_offset(&bytes[position..]).ok_or_else(|| anyhow!("invalid git index path"))?;
+            path.truncate(path.len().saturating_sub(removed));
+            position += length;
+        } else {
+            path.clear();
+        }
+        let end = bytes[position..]
+            .iter()
+            .position(|byte| *byte == 0)
+            .map(|length| position + length)
+            .ok_or_else(|| anyhow!("invalid git index path"))?;
+        path.extend_from_slice(&bytes[position..end]);
+        offset = if version == 4 {
+            end + 1
+        } else {
+            // entries are padded with 1 to 8 null bytes
+            offset + (end - offset + 8) / 8 * 8
+        };
+
+        if flags & STAGE_MASK != 0
+            || extended_flags & INTENT_TO_ADD_FLAG != 0
+            || mode & FILE_TYPE_MASK != REGULAR_FILE_TYPE
+        {
+            continue;
+        }
+        let Ok(entry_path) = std::str::from_utf8(&path) else {
+            continue;
+        };
+        // the prefix of a workspace at the root of the repository is "."
+        let entry_path = if prefix.is_empty() || prefix == "." {
+            entry_path
+        } else {
+            match entry_path
+                .strip_prefix(prefix)
+                .and_then(|path| path.strip_prefix('/'))
+            {
+                Some(entry_path) => entry_path,
+                None => continue,
+            }
+        };
+        entries.insert(entry_path.to_string(), entry);
+    }
+    Ok(entries)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> anyhow::Result<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| anyhow!("git index is truncated"))
+}
+
+/// Reads the variable length offsets of index version 4, returning the offset and the bytes it took
+fn read_offset(bytes: &[u8]) -> Option<(usize, usize)> {
+    let mut byte = *bytes.first()?;
+    let mut value = (byte & 0x7f) as usize;
+    let mut length = 1;
+    while byte & 0x80 != 0 {
+        byte = *bytes.get(length)?;
+        length += 1;
+        value = ((value + 1) << 7) | (byte & 0x7f) as usize;
+    }
+    Some((value, length))
+}
+
+/// Hashes content the same way as `git hash-object`, which is the SHA-1 of a blob header and the content
+pub fn hash_git_blob(content: &[u8]) -> String {
+    let mut sha1 = Sha1::new();
+    sha1.update(format!("blob {}\0", content.len()).as_bytes());
+    sha1.update(content);
+    sha1.digest().to_string()
+}
+
+/// Hashes the file the same way as `hash_git_blob`, by streaming it instead of reading the whole file
+pub fn hash_git_blob_file(path: &Path) -> Option<String> {
+    // the header needs the size before the content is read
+    let size = std::fs::metadata(path).ok()?.len();
+    let mut sha1 = Sha1::new();
+    sha1.update(format!("blob {size}\0").as_bytes());
+    let mut read = 0;
+    read_file_in_chunks(path, |chunk| {
+        read += chunk.len() as u64;
+        sha1.update(chunk);
+    })
+    .ok()?;
+    if read != size {
+        trace!("{} changed while it was hashed", path.display());
+        return None;
+    }
+    Some(sha1.digest().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+    use std::time::{Duration, SystemTime};
+
+    fn git(directory: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(directory)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    fn nx_file(workspace_root: &Path, path: &str) -> NxFile {
+        NxFile {
+            full_path: workspace_root.join(path).display().to_string(),
+            normalized_path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_hash_blobs_like_git() {
+        assert_eq!(
+            hash_git_blob(b""),
+            "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"
+        );
+        assert_eq!(
+            hash_git_blob(b"hello\n"),
+            "ce013625030ba8dba906f756967f9e9ca394464a"
+        );
+        // content that spans several blocks
+        let content = "content".repeat(100);
+        let temp = TempDir::new().unwrap();
+        temp.child("file.txt").write_str(&content).unwrap();
+        git(temp.path(), &["init", "-q"]);
+        assert_eq!(
+            hash_git_blob(content.as_bytes()),
+            git(temp.path(), &["hash-object", "file.txt"])
+        );
+        assert_eq!(
+            hash_git_blob_file(&temp.child("file.txt")),
+            Some(hash_git_blob(content.as_bytes()))
+        );
+    }
+
+    #[test]
+    fn should_read_the_hashes_of_unchanged_files_from_the_index() {
+        let temp = TempDir::new().unwrap();
+        let workspace = temp.child("workspace");
+        for file in ["apps/app/main.ts", "libs/lib/index.ts", "modified.ts"] {
+            workspace.child(file).write_str(file).unwrap();
+            // files modified in the same second as the index are not trusted
+            std::fs::File::options()
+                .write(true)
+                .open(workspace.child(file).path())
+                .unwrap()
+                .set_modified(SystemTime::now() - Duration::from_secs(10))
+                .unwrap();
+        }
+        git(temp.path(), &["init", "-q"]);
+        git(temp.path(), &["add", "."]);
+        workspace.child("modified.ts").write_str("changed").unwrap();
+        workspace.child("untracked.ts").write_str("new").unwrap();
+
+        let index = read_git_index(workspace.path()).unwrap();
+
+        let hash = |path| {
+            index
+                .get_clean_hash(&nx_file(workspace.path(), path))
+                .map(String::from)
+        };
+        assert_eq!(
+            hash("apps/app/main.ts"),
+            Some(hash_git_blob(b"apps/app/main.ts"))
+        );
+        assert_eq!(
+            hash("libs/lib/index.ts"),
+            Some(git(
+                temp.path(),
+                &["rev-parse", ":workspace/libs/lib/index.ts"]
+            ))
+        );
+        assert_eq!(hash("modified.ts"), None);
+        assert_eq!(hash("untracked.ts"), None);
+    }
+
+    #[test]
+    fn should_not_read_the_hashes_of_files_that_git_converts() {
+        let temp = TempDir::new().unwrap();
+        let files = [
+            (
+                ".gitattributes",
+                "*.ident ident\n*.crlf eol=crlf\n*.lfs filter=lfs\n",
+            ),
+            ("file.ts", "content"),
+            ("file.ident", "$Id$"),
+            ("file.crlf", "content\r\n"),
+            ("file.lfs", "content"),
+        ];
+        for (file, content) in files {
+            temp.child(file).write_str(content).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(temp.child(file).path())
+                .unwrap()
+                .set_modified(SystemTime::now() - Duration::from_secs(10))
+                .unwrap();
+        }
+        git(temp.path(), &["init", "-q"]);
+        git(temp.path(), &["config", "core.autocrlf", "false"]);
+        git(temp.path(), &["add", "."]);
+
+        let index = read_git_index(temp.path()).unwrap();
+
+        let hash = |path| index.get_clean_hash(&nx_file(temp.path(), path));
+        assert_eq!(hash("file.ts"), Some(hash_git_blob(b"content").as_str()));
+        assert_eq!(hash("file.ident"), None);
+        assert_eq!(hash("file.crlf"), None);
+        assert_eq!(hash("file.lfs"), None);
+
+        git(temp.path(), &["config", "core.autocrlf", "true"]);
+        assert!(read_git_index(temp.path()).is_err());
+    }
+
+    #[test]
+    fn should_read_compressed_paths_of_index_version_4() {
+        let temp = TempDir::new().unwrap();
+        for file in ["a/b/one.ts", "a/b/two.ts", "a/c.ts"] {
+            temp.child(file).write_str(file).unwrap();
+        }
+        git(temp.path(), &["init", "-q"]);
+        git(temp.path(), &["update-index", "--index-version", "4"]);
+        git(temp.path(), &["add", "."]);
+
+        let bytes = std::fs::read(temp.path().join(".git/index")).unwrap();
+        let entries = parse_entries(&bytes, "").unwrap();
+        let mut paths = entries.keys().collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(paths, vec!["a/b/one.ts", "a/b/two.ts", "a/c.ts"]);
+        assert_eq!(
+            entries["a/b/two.ts"].object_id,
+            hash_git_blob(b"a/b/two.ts")
+        );
+    }
+}
@@ -8,6 +8,9 @@ pub mod context;
 mod errors;
 mod files_archive;
 mod files_hashing;
+mod files_query;
+mod files_search;
+mod git_index;
 pub mod types;
 pub mod workspace_files;
 
@@ -16,6 +16,54 @@ pub struct NxWorkspaceFiles {
     pub project_file_map: ProjectFiles,
     pub global_files: Vec<FileData>,
     pub external_references: Option<NxWorkspaceFilesExternals>,
+    pub diagnostics: Option<ProjectFilesDiagnostics>,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct WorkspaceFilesOptions {
+    /// Reports files within the roots of several projects, projects without files, and project roots that do not exist
+    pub diagnostics: Option<bool>,
+    /// The roots of the projects by their name, which the diagnostics are reported for.
+    /// Projects with the same root share one entry of the project root map, so they are only reported when these are given.
+    pub project_roots: Option<HashMap<String, String>>,
+}
+
+#[napi(object)]
+#[derive(Debug, Default, PartialEq)]
+pub struct ProjectFilesDiagnostics {
+    /// Files within the roots of several projects, which only belong to the project with the nearest root
+    pub conflicting_files: Vec<FileOwnershipConflict>,
+    /// Projects that have no files
+    pub empty_projects: Vec<String>,
+    /// Projects whose roots do not exist in the workspace
+    pub missing_project_roots: Vec<MissingProjectRoot>,
+    /// Roots of several projects, whose files only belong to one of them
+    pub duplicate_project_roots: Vec<DuplicateProjectRoot>,
+}
+
+#[napi(object)]
+#[derive(Debug, PartialEq)]
+pub struct FileOwnershipConflict {
+    pub file: String,
+    /// The project with the nearest root, which the file belongs to
+    pub project: String,
+    /// The other projects whose roots contain the file, from the nearest root to the farthest
+    pub other_projects: Vec<String>,
+}
+
+#[napi(object)]
+#[derive(Debug, PartialEq)]
+pub struct MissingProjectRoot {
+    pub project: String,
+    pub root: String,
+}
+
+#[napi(object)]
+#[derive(Debug, PartialEq)]
+pub struct DuplicateProjectRoot {
+    pub root: String,
+    pub projects: Vec<String>,
 }
 
 #[napi(object)]
@@ -29,6 +77,7 @@ pub struct NxWorkspaceFilesExternals {
 pub struct UpdatedWorkspaceFiles {
     pub file_map: FileMap,
     pub external_references: NxWorkspaceFilesExternals,
+    pub diagnostics: Option<ProjectFilesDiagnostics>,
 }
 
 #[napi(object)]
@@ -36,3 +85,10 @@ pub struct FileMap {
     pub project_file_map: ProjectFiles,
     pub non_project_files: Vec<FileData>,
 }
+
+#[napi(object)]
+#[derive(Debug, PartialEq)]
+pub struct WorkspaceFileSize {
+    pub file: String,
+    pub size: i64,
+}
@@ -6,7 +6,11 @@ use rayon::prelude::*;
 use tracing::trace;
 
 use crate::native::types::FileData;
-use crate::native::workspace::types::{FileLocation, NxWorkspaceFiles, NxWorkspaceFilesExternals};
+use crate::native::utils::Normalize;
+use crate::native::workspace::types::{
+    DuplicateProjectRoot, FileLocation, FileOwnershipConflict, MissingProjectRoot,
+    NxWorkspaceFiles, NxWorkspaceFilesExternals, ProjectFiles, ProjectFilesDiagnostics,
+};
 
 pub(super) fn get_files(
     project_root_map: HashMap<String, String>,
@@ -78,6 +82,7 @@ pub(super) fn get_files(
             global_files: global_files_external,
             all_workspace_files,
         }),
+        diagnostics: None,
     })
 }
 
@@ -87,3 +92,199 @@ fn transform_root_map(root_map: HashMap<String, String>) -> hashbrown::HashMap<P
         .map(|(project_root, project_name)| (PathBuf::from(project_root), project_name))
         .collect()
 }
+
+/// Finds files within the roots of several projects, projects without files, project roots that do not exist,
+/// and roots of several projects.
+/// Files only belong to the project with the nearest root, so the other projects silently lose them.
+pub(super) fn diagnose_project_files(
+    workspace_root: &Path,
+    project_roots: HashMap<String, String>,
+    project_file_map: &ProjectFiles,
+) -> ProjectFilesDiagnostics {
+    let mut root_map: hashbrown::HashMap<PathBuf, Vec<String>> = hashbrown::HashMap::new();
+    for (project, root) in &project_roots {
+        root_map
+            .entry(PathBuf::from(root))
+            .or_default()
+            .push(project.clone());
+    }
+    root_map.values_mut().for_each(|projects| projects.sort());
+
+    let mut conflicting_files = project_file_map
+        .par_iter()
+        .flat_map_iter(|(project, files)| {
+            let root_map = &root_map;
+            files.iter().filter_map(move |file_data| {
+                let other_projects = find_claiming_projects(Path::new(&file_data.file), root_map)
+                    .filter(|other_project| *other_project != project)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                (!other_projects.is_empty()).then(|| FileOwnershipConflict {
+                    file: file_data.file.clone(),
+                    project: project.clone(),
+                    other_projects,
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+    conflicting_files.par_sort_by(|a, b| a.file.cmp(&b.file));
+
+    let mut empty_projects = project_roots
+        .keys()
+        .filter(|project| {
+            project_file_map
+                .get(*project)
+                .is_none_or(|files| files.is_empty())
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    empty_projects.sort();
+
+    let mut missing_project_roots = project_roots
+        .iter()
+        .filter(|(_, root)| !workspace_root.join(root).is_dir())
+        .map(|(project, root)| MissingProjectRoot {
+            project: project.clone(),
+            root: Path::new(root).to_normalized_string(),
+        })
+        .collect::<Vec<_>>();
+    missing_project_roots.sort_by(|a, b| a.project.cmp(&b.project));
+
+    let mut duplicate_project_roots = root_map
+        .iter()
+        .filter(|(_, projects)| projects.len() > 1)
+        .map(|(root, projects)| DuplicateProjectRoot {
+            root: root.to_normalized_string(),
+            projects: projects.clone(),
+        })
+        .collect::<Vec<_>>();
+    duplicate_project_roots.sort_by(|a, b| a.root.cmp(&b.root));
+
+    ProjectFilesDiagnostics {
+        conflicting_files,
+        empty_projects,
+        missing_project_roots,
+        duplicate_project_roots,
+    }
+}
+
+/// The projects whose roots contain the file, from the nearest root to the farthest
+fn find_claiming_projects<'a>(
+    file: &'a Path,
+    root_map: &'a hashbrown::HashMap<PathBuf, Vec<String>>,
+) -> impl Iterator<Item = &'a String> {
+    file.ancestors()
+        .skip(1)
+        .map(|parent| {
+            if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            }
+        })
+        .filter_map(|root| root_map.get(root))
+        .flatten()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_diagnose_conflicting_empty_and_missing_projects() {
+        let temp = tempfile::tempdir().unwrap();
+        for directory in ["libs/a/nested", "libs/empty"] {
+            std::fs::create_dir_all(temp.path().join(directory)).unwrap();
+        }
+        let project_roots = HashMap::from([
+            ("root".to_string(), ".".to_string()),
+            ("a".to_string(), "libs/a".to_string()),
+            ("nested".to_string(), "libs/a/nested".to_string()),
+            ("empty".to_string(), "libs/empty".to_string()),
+            ("missing".to_string(), "libs/missing".to_string()),
+        ]);
+        let project_root_map = project_roots
+            .iter()
+            .map(|(project, root)| (root.clone(), project.clone()))
+            .collect::<HashMap<_, _>>();
+        let file = |file: &str| FileData {
+            file: file.into(),
+            hash: "hash".into(),
+        };
+        let files = vec![
+            file("libs/a/index.ts"),
+            file("libs/a/nested/index.ts"),
+            file("package.json"),
+        ];
+
+        let workspace_files = get_files(project_root_map, files).unwrap();
+        let diagnostics = diagnose_project_files(
+            temp.path(),
+            project_roots,
+            &workspace_files.project_file_map,
+        );
+
+        assert_eq!(
+            diagnostics,
+            ProjectFilesDiagnostics {
+                conflicting_files: vec![
+                    FileOwnershipConflict {
+                        file: "libs/a/index.ts".into(),
+                        project: "a".into(),
+                        other_projects: vec!["root".into()],
+                    },
+                    FileOwnershipConflict {
+                        file: "libs/a/nested/index.ts".into(),
+                        project: "nested".into(),
+                        other_projects: vec!["a".into(), "root".into()],
+                    },
+                ],
+                empty_projects: vec!["empty".into(), "missing".into()],
+                missing_project_roots: vec![MissingProjectRoot {
+                    project: "missing".into(),
+                    root: "libs/missing".into(),
+                }],
+                duplicate_project_roots: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn should_diagnose_projects_with_the_same_root() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join("libs/a")).unwrap();
+        let project_roots = HashMap::from([
+            ("a".to_string(), "libs/a".to_string()),
+            ("a-e2e".to_string(), "libs/a".to_string()),
+        ]);
+        let project_root_map = HashMap::from([("libs/a".to_string(), "a".to_string())]);
+        let files = vec![FileData {
+            file: "libs/a/index.ts".into(),
+            hash: "hash".into(),
+        }];
+
+        let workspace_files = get_files(project_root_map, files).unwrap();
+        let diagnostics = diagnose_project_files(
+            temp.path(),
+            project_roots,
+            &workspace_files.project_file_map,
+        );
+
+        assert_eq!(
+            diagnostics.duplicate_project_roots,
+            vec![DuplicateProjectRoot {
+                root: "libs/a".into(),
+                projects: vec!["a".into(), "a-e2e".into()],
+            }]
+        );
+        assert_eq!(
+            diagnostics.conflicting_files,
+            vec![FileOwnershipConflict {
+                file: "libs/a/index.ts".into(),
+                project: "a".into(),
+                other_projects: vec!["a-e2e".into()],
+            }]
+        );
+        assert_eq!(diagnostics.empty_projects, vec!["a-e2e".to_string()]);
+    }
+}
@@ -21,6 +21,7 @@ import { getCloudOptions } from '../nx-cloud/utilities/get-cloud-options';
 import { isCI } from '../utils/is-ci';
 import { output } from '../utils/output';
 import { logger } from '../utils/logger';
+import { getHashAlgorithm } from '../hasher/hash-algorithm';
 
 export type CachedResult = {
   terminalOutput: string;
@@ -89,7 +90,13 @@ export function getCache(options: DefaultTasksRunnerOptions): DbCache | Cache {
 }
 
 export class DbCache {
-  private cache = new NxCache(workspaceRoot, cacheDir, getDbConnection());
+  private cache = new NxCache(
+    workspaceRoot,
+    cacheDir,
+    getDbConnection(),
+    undefined,
+    getHashAlgorithm()
+  );
 
   private remoteCache: RemoteCacheV2 | null;
   private remoteCachePromise: Promise<RemoteCacheV2>;
@@ -1,5 +1,7 @@
 import { readFileSync } from 'node:fs';
 import ignore from 'ignore';
+import { readNxJson, type NxJsonConfiguration } from '../config/nx-json';
+import type { IgnoreOptions } from '../native';
 import { readFileIfExisting } from './fileutils';
 import { joinPathFragments } from './path';
 import { workspaceRoot } from './workspace-root';
@@ -9,14 +11,20 @@ import { workspaceRoot } from './workspace-root';
  */
 export const ALWAYS_IGNORE = getAlwaysIgnore();
 
+/**
+ * The globs of the files that are ignored, including the ignore files and globs configured in nx.json
+ */
 export function getIgnoredGlobs(
   root: string = workspaceRoot,
-  prependRoot: boolean = true
+  prependRoot: boolean = true,
+  nxJson: NxJsonConfiguration = readNxJson(root)
 ) {
-  const files = ['.gitignore', '.nxignore'];
+  const files = getIgnoreFileNames(nxJson);
+  const ignoreGlobs = getNativeIgnoreOptions(nxJson).ignoreGlobs ?? [];
   if (prependRoot) {
     return [
       ...getAlwaysIgnore(root),
+      ...getIgnoredGlobsFromLines(ignoreGlobs, root),
       ...files.flatMap((f) =>
         getIgnoredGlobsFromFile(joinPathFragments(root, f), root)
       ),
@@ -24,6 +32,7 @@ export function getIgnoredGlobs(
   } else {
     return [
       ...getAlwaysIgnore(),
+      ...getIgnoredGlobsFromLines(ignoreGlobs),
       ...files.flatMap((f) =>
         getIgnoredGlobsFromFile(joinPathFragments(root, f))
       ),
@@ -43,36 +52,66 @@ export function getAlwaysIgnore(root?: string) {
   return root ? paths.map((x) => joinPathFragments(root, x)) : paths;
 }
 
+/**
+ * The ignore options of the native workspace context and watcher.
+ * Both need the same options, so that they agree on which files exist.
+ */
+export function getNativeIgnoreOptions(
+  nxJson: NxJsonConfiguration
+): IgnoreOptions {
+  return {
+    ignoreFiles: nxJson.ignoreFiles,
+    ignoreGlobs: nxJson.ignoreGlobs,
+  };
+}
+
+/**
+ * The names of the ignore files, which change the files of the workspace when they change
+ */
+export function getIgnoreFileNames(nxJson: NxJsonConfiguration): string[] {
+  return nxJson.ignoreFiles ?? ['.gitignore', '.nxignore'];
+}
+
+/**
+ * Matches the files that are ignored by the ignore files and globs configured in nx.json
+ */
 export function getIgnoreObject(
-  root: string = workspaceRoot
+  root: string = workspaceRoot,
+  nxJson: NxJsonConfiguration = readNxJson(root)
 ): ReturnType<typeof ignore> {
   const ig = ignore();
-  ig.add(readFileIfExisting(`${root}/.gitignore`));
-  ig.add(readFileIfExisting(`${root}/.nxignore`));
+  for (const file of getIgnoreFileNames(nxJson)) {
+    ig.add(readFileIfExisting(`${root}/${file}`));
+  }
+  // the globs are added last, so that the ignore files cannot include what they ignore, like in the native walker
+  ig.add(getNativeIgnoreOptions(nxJson).ignoreGlobs ?? []);
   return ig;
 }
 
 function getIgnoredGlobsFromFile(file: string, root?: string): string[] {
   try {
-    const results = [];
     const contents = readFileSync(file, 'utf-8');
-    const lines = contents.split('\n');
-    for (const line of lines) {
-      const trimmed = line.trim();
-      if (!trimmed || trimmed.startsWith('#')) {
-        continue;
-      } else if (trimmed.startsWith('/')) {
-        if (root) {
-          results.push(joinPathFragments(root, trimmed));
-        } else {
-          results.push(joinPathFragments('.', trimmed));
-        }
+    return getIgnoredGlobsFromLines(contents.split('\n'), root);
+  } catch (e) {
+    return [];
+  }
+}
+
+function getIgnoredGlobsFromLines(lines: string[], root?: string): string[] {
+  const results = [];
+  for (const line of lines) {
+    const trimmed = line.trim();
+    if (!trimmed || trimmed.startsWith('#')) {
+      continue;
+    } else if (trimmed.startsWith('/')) {
+      if (root) {
+        results.push(joinPathFragments(root, trimmed));
       } else {
-        results.push(trimmed);
+        results.push(joinPathFragments('.', trimmed));
       }
+    } else {
+      results.push(trimmed);
     }
-    return results;
-  } catch (e) {
-    return [];
   }
+  return results;
 }
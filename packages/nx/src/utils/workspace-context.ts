@@ -1,8 +1,16 @@
-import type { NxWorkspaceFilesExternals, WorkspaceContext } from '../native';
+import type {
+  NestedWorkspaces,
+  NxWorkspaceFilesExternals,
+  SymlinkPolicy,
+  WorkspaceContext,
+} from '../native';
 import { performance } from 'perf_hooks';
 import { workspaceDataDirectoryForWorkspace } from './cache-directory';
 import { isOnDaemon } from '../daemon/is-on-daemon';
 import { daemonClient } from '../daemon/client/client';
+import { readNxJson } from '../config/nx-json';
+import { getHashAlgorithm } from '../hasher/hash-algorithm';
+import { getNativeIgnoreOptions } from './ignore';
 
 let workspaceContext: WorkspaceContext | undefined;
 
@@ -10,9 +18,21 @@ export function setupWorkspaceContext(workspaceRoot: string) {
   const { WorkspaceContext } =
     require('../native') as typeof import('../native');
   performance.mark('workspace-context');
+  const nxJson = readNxJson(workspaceRoot);
   workspaceContext = new WorkspaceContext(
     workspaceRoot,
-    workspaceDataDirectoryForWorkspace(workspaceRoot)
+    workspaceDataDirectoryForWorkspace(workspaceRoot),
+    {
+      hashAlgorithm: getHashAlgorithm(nxJson),
+      useGitIndex: nxJson.useGitIndex,
+      paranoidFileHashing: nxJson.paranoidFileHashing,
+      largeFileThreshold: nxJson.largeFileThreshold,
+      hashLargeFilesByStats: nxJson.hashLargeFilesByStats,
+      nestedWorkspaces: nxJson.nestedWorkspaces as NestedWorkspaces,
+      symlinks: nxJson.symlinks as SymlinkPolicy,
+      includeSubmodules: nxJson.includeSubmodules,
+      ignores: getNativeIgnoreOptions(nxJson),
+    }
   );
   performance.mark('workspace-context:end');
   performance.measure(
@@ -175,6 +195,15 @@ function ensureContextAvailable(workspaceRoot: string) {
   }
 }
 
+/**
+ * Returns the workspace context of this process, creating it if it doesn't exist.
+ * It should only be used on the daemon or when the daemon is disabled.
+ */
+export function getWorkspaceContext(workspaceRoot: string) {
+  ensureContextAvailable(workspaceRoot);
+  return workspaceContext;
+}
+
 export function resetWorkspaceContext() {
   workspaceContext = undefined;
 }